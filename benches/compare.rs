@@ -0,0 +1,101 @@
+//! ```Lazy``` against ```once_cell::sync::Lazy``` and ```std::sync::LazyLock```: reads from 1, 4 and 16 threads,
+//! the latency of the first initialization, and reads racing with the initialization
+
+use std::{sync::{Barrier, LazyLock}, time::{Duration, Instant}, hint::black_box};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const THREADS: [usize; 3] = [1, 4, 16];
+
+static LAIZY: laizy::Lazy<u64> = laizy::Lazy::new(|| 1);
+static ONCE_CELL: once_cell::sync::Lazy<u64> = once_cell::sync::Lazy::new(|| 1);
+static STD: LazyLock<u64> = LazyLock::new(|| 1);
+
+/// Runs `f` `iters` times on each of `threads` threads, returning the time taken by the slowest one
+fn contended (threads: usize, iters: u64, f: impl Fn() + Sync) -> Duration {
+    let barrier = Barrier::new(threads);
+    std::thread::scope(|s| {
+        let handles = (0..threads).map(|_| s.spawn(|| {
+            barrier.wait();
+            let start = Instant::now();
+            for _ in 0..iters { f() }
+            start.elapsed()
+        })).collect::<Vec<_>>();
+
+        handles.into_iter().map(|h| h.join().unwrap()).max().unwrap()
+    })
+}
+
+fn read (c: &mut Criterion) {
+    black_box((*LAIZY, *ONCE_CELL, *STD));
+
+    let mut group = c.benchmark_group("read");
+    for threads in THREADS {
+        group.bench_with_input(BenchmarkId::new("laizy", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(threads, iters, || { black_box(*LAIZY); }))
+        });
+        group.bench_with_input(BenchmarkId::new("once_cell", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(threads, iters, || { black_box(*ONCE_CELL); }))
+        });
+        group.bench_with_input(BenchmarkId::new("std", threads), &threads, |b, &threads| {
+            b.iter_custom(|iters| contended(threads, iters, || { black_box(*STD); }))
+        });
+    }
+    group.finish();
+}
+
+fn first_init (c: &mut Criterion) {
+    let mut group = c.benchmark_group("first initialization");
+    group.bench_function("laizy", |b| b.iter(|| {
+        let lazy = laizy::Lazy::new(|| black_box(1u64));
+        black_box(*lazy)
+    }));
+    group.bench_function("once_cell", |b| b.iter(|| {
+        let lazy = once_cell::sync::Lazy::new(|| black_box(1u64));
+        black_box(*lazy)
+    }));
+    group.bench_function("std", |b| b.iter(|| {
+        let lazy = LazyLock::new(|| black_box(1u64));
+        black_box(*lazy)
+    }));
+    group.finish();
+}
+
+/// Every thread reads a fresh cell, racing for its initialization
+fn mixed (c: &mut Criterion) {
+    const THREADS: usize = 4;
+
+    fn race<L: Sync> (iters: u64, new: impl Fn() -> L, read: impl Fn(&L) + Sync) -> Duration {
+        let mut total = Duration::ZERO;
+        for _ in 0..iters {
+            let lazy = new();
+            total += contended(THREADS, 1, || read(&lazy));
+        }
+        total
+    }
+
+    let mut group = c.benchmark_group("mixed read/init");
+    group.bench_function("laizy", |b| b.iter_custom(|iters| {
+        race(iters, || laizy::Lazy::new(|| black_box(1u64)), |lazy| { black_box(**lazy); })
+    }));
+    group.bench_function("once_cell", |b| b.iter_custom(|iters| {
+        race(iters, || once_cell::sync::Lazy::new(|| black_box(1u64)), |lazy| { black_box(**lazy); })
+    }));
+    group.bench_function("std", |b| b.iter_custom(|iters| {
+        race(iters, || LazyLock::new(|| black_box(1u64)), |lazy| { black_box(**lazy); })
+    }));
+    group.finish();
+}
+
+#[cfg(feature = "futures")]
+fn async_read (c: &mut Criterion) {
+    let lazy = laizy::async_lazy(async { 1u64 });
+    futures::executor::block_on(lazy.get());
+
+    c.bench_function("async read", |b| b.iter(|| black_box(*futures::executor::block_on(lazy.get()))));
+}
+
+#[cfg(feature = "futures")]
+criterion_group!(benches, read, first_init, mixed, async_read);
+#[cfg(not(feature = "futures"))]
+criterion_group!(benches, read, first_init, mixed);
+criterion_main!(benches);
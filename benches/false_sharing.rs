@@ -0,0 +1,38 @@
+//! Two threads hammering two adjacent lazy counters, with and without cache-line padding
+
+use std::{sync::atomic::{AtomicUsize, Ordering}, time::{Duration, Instant}};
+use criterion::{criterion_group, criterion_main, Criterion};
+use laizy::{Lazy, padded};
+
+struct Adjacent {
+    a: Lazy<AtomicUsize>,
+    b: Lazy<AtomicUsize>
+}
+
+struct Padded {
+    a: padded::Lazy<AtomicUsize>,
+    b: padded::Lazy<AtomicUsize>
+}
+
+static ADJACENT: Adjacent = Adjacent { a: Lazy::new(|| AtomicUsize::new(0)), b: Lazy::new(|| AtomicUsize::new(0)) };
+static PADDED: Padded = Padded { a: padded::Lazy::new(|| AtomicUsize::new(0)), b: padded::Lazy::new(|| AtomicUsize::new(0)) };
+
+/// Increments `a` and `b` `iters` times each, from two different threads
+fn hammer (iters: u64, a: &'static AtomicUsize, b: &'static AtomicUsize) -> Duration {
+    let start = Instant::now();
+    std::thread::scope(|s| {
+        s.spawn(|| for _ in 0..iters { a.fetch_add(1, Ordering::Relaxed); });
+        s.spawn(|| for _ in 0..iters { b.fetch_add(1, Ordering::Relaxed); });
+    });
+    start.elapsed()
+}
+
+fn false_sharing (c: &mut Criterion) {
+    let mut group = c.benchmark_group("adjacent counters");
+    group.bench_function("unpadded", |b| b.iter_custom(|iters| hammer(iters, ADJACENT.a.get(), ADJACENT.b.get())));
+    group.bench_function("padded", |b| b.iter_custom(|iters| hammer(iters, PADDED.a.get(), PADDED.b.get())));
+    group.finish();
+}
+
+criterion_group!(benches, false_sharing);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! Accesses to an already initialized ```AsyncLazy``` through a shared and an exclusive borrow
+
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::FutureExt;
+
+fn get_mut (c: &mut Criterion) {
+    let mut lazy = laizy::async_lazy(async { 1u64 });
+    lazy.get().now_or_never();
+
+    let mut group = c.benchmark_group("initialized async read");
+    group.bench_function("get", |b| b.iter(|| black_box(*lazy.get().now_or_never().unwrap())));
+    group.bench_function("get_mut", |b| b.iter(|| black_box(*lazy.get_mut().now_or_never().unwrap())));
+    group.finish();
+
+    let mut group = c.benchmark_group("async initialization");
+    group.bench_function("get", |b| b.iter(|| {
+        let lazy = laizy::async_lazy(async { black_box(1u64) });
+        black_box(*lazy.get().now_or_never().unwrap())
+    }));
+    group.bench_function("get_mut", |b| b.iter(|| {
+        let mut lazy = laizy::async_lazy(async { black_box(1u64) });
+        black_box(*lazy.get_mut().now_or_never().unwrap())
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, get_mut);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! Reads of an already initialized, shared ```Lazy``` from several threads at once
+
+use std::{sync::{Barrier, atomic::{AtomicU8, Ordering}}, time::{Duration, Instant}, hint::black_box};
+use criterion::{criterion_group, criterion_main, Criterion};
+use laizy::Lazy;
+
+const THREADS: usize = 8;
+
+static HOT: Lazy<u64> = Lazy::new(|| 1);
+static STATE: AtomicU8 = AtomicU8::new(2);
+
+/// Runs `f` `iters` times on each of `THREADS` threads, returning the time taken by the slowest one
+fn contended (iters: u64, f: impl Fn() + Sync) -> Duration {
+    let barrier = Barrier::new(THREADS);
+    std::thread::scope(|s| {
+        let handles = (0..THREADS).map(|_| s.spawn(|| {
+            barrier.wait();
+            let start = Instant::now();
+            for _ in 0..iters { f() }
+            start.elapsed()
+        })).collect::<Vec<_>>();
+
+        handles.into_iter().map(|h| h.join().unwrap()).max().unwrap()
+    })
+}
+
+fn read (c: &mut Criterion) {
+    HOT.get();
+
+    let mut group = c.benchmark_group("hot static read");
+    group.bench_function("load", |b| b.iter_custom(|iters| contended(iters, || { black_box(*HOT.get()); })));
+    // what every read used to cost: a `compare_exchange` on the shared state
+    group.bench_function("compare_exchange", |b| b.iter_custom(|iters| contended(iters, || {
+        let _ = black_box(STATE.compare_exchange(0, 1, Ordering::Acquire, Ordering::Acquire));
+    })));
+    group.finish();
+}
+
+criterion_group!(benches, read);
+criterion_main!(benches);
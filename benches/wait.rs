@@ -0,0 +1,43 @@
+//! Compares the CPU time burnt by a thread waiting on a slow initializer against a plain spin loop.
+//! Run with ```cargo bench --bench wait``` (with and without ```--features std```)
+
+use std::{time::{Duration, Instant}, sync::atomic::{AtomicBool, Ordering}};
+use laizy::Lazy;
+
+const INIT_TIME: Duration = Duration::from_millis(200);
+
+#[cfg(unix)]
+fn thread_cpu_time () -> Duration {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time () -> Duration {
+    Duration::ZERO
+}
+
+/// Returns the (wall, cpu) time taken by `f`
+fn measure (f: impl FnOnce()) -> (Duration, Duration) {
+    let (wall, cpu) = (Instant::now(), thread_cpu_time());
+    f();
+    (wall.elapsed(), thread_cpu_time() - cpu)
+}
+
+fn main () {
+    let lazy = Lazy::new(|| { std::thread::sleep(INIT_TIME); 0u8 });
+    let (wall, cpu) = std::thread::scope(|s| {
+        s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+        s.spawn(|| measure(|| { lazy.get(); })).join().unwrap()
+    });
+    println!("laizy wait: {wall:?} wall, {cpu:?} cpu");
+
+    let flag = AtomicBool::new(false);
+    let (wall, cpu) = std::thread::scope(|s| {
+        s.spawn(|| { std::thread::sleep(INIT_TIME); flag.store(true, Ordering::Release) });
+        s.spawn(|| measure(|| while !flag.load(Ordering::Acquire) { core::hint::spin_loop() })).join().unwrap()
+    });
+    println!("plain spin: {wall:?} wall, {cpu:?} cpu");
+}
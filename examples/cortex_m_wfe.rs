@@ -0,0 +1,67 @@
+//! Waiting with `wfe` on a Cortex-M core. To run it on QEMU's LM3S6965 board:
+//!
+//! ```sh
+//! RUSTFLAGS="-C link-arg=-Texamples/cortex_m_wfe.x" cargo run --example cortex_m_wfe --features cortex-m-wfe --target thumbv7m-none-eabi
+//! ```
+//!
+//! with the runner `qemu-system-arm -cpu cortex-m3 -machine lm3s6965evb -nographic -semihosting-config enable=on,target=native -kernel`.
+//! On any other target it runs on the host, where `Wfe` spins
+
+#![cfg_attr(target_os = "none", no_std)]
+#![cfg_attr(target_os = "none", no_main)]
+
+use laizy::{Lazy, policy::Repanic, strategy::Wfe};
+
+static ANSWER: Lazy<u32, fn() -> u32, Repanic, Wfe> = Lazy::with_policy(|| (1..=12).sum::<u32>() - 36);
+
+#[cfg(target_os = "none")]
+mod device {
+    use core::arch::asm;
+
+    #[link_section = ".vector_table.reset_vector"]
+    #[no_mangle]
+    static RESET_VECTOR: unsafe extern "C" fn() -> ! = reset;
+
+    extern "C" {
+        static mut __sdata: u32;
+        static mut __edata: u32;
+        static __sidata: u32;
+        static mut __sbss: u32;
+        static mut __ebss: u32;
+    }
+
+    #[no_mangle]
+    unsafe extern "C" fn reset () -> ! {
+        // initialize `.data` and `.bss`, where the cell lives
+        let data = core::ptr::addr_of_mut!(__sdata);
+        let len = core::ptr::addr_of!(__edata) as usize - data as usize;
+        core::ptr::copy_nonoverlapping(core::ptr::addr_of!(__sidata).cast::<u8>(), data.cast::<u8>(), len);
+
+        let bss = core::ptr::addr_of_mut!(__sbss);
+        let len = core::ptr::addr_of!(__ebss) as usize - bss as usize;
+        core::ptr::write_bytes(bss.cast::<u8>(), 0, len);
+
+        exit(*super::ANSWER.get() == 42)
+    }
+
+    /// Exits QEMU via semihosting (`SYS_EXIT`)
+    fn exit (success: bool) -> ! {
+        let reason: u32 = if success { 0x20026 } else { 0x20024 };
+        unsafe { asm!("bkpt 0xab", in("r0") 0x18, in("r1") reason, options(nostack)) };
+        loop {}
+    }
+
+    #[panic_handler]
+    fn panic (_: &core::panic::PanicInfo) -> ! {
+        exit(false)
+    }
+}
+
+#[cfg(not(target_os = "none"))]
+fn main () {
+    let handles = (0..4).map(|_| std::thread::spawn(|| *ANSWER.get())).collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 42);
+    }
+    println!("the answer is {}", *ANSWER);
+}
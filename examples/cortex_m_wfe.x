@@ -0,0 +1,26 @@
+/* Memory layout of QEMU's LM3S6965 board, for the `cortex_m_wfe` example */
+MEMORY
+{
+  FLASH : ORIGIN = 0x00000000, LENGTH = 256K
+  RAM : ORIGIN = 0x20000000, LENGTH = 64K
+}
+
+ENTRY(reset);
+
+SECTIONS
+{
+  .vector_table ORIGIN(FLASH) :
+  {
+    LONG(ORIGIN(RAM) + LENGTH(RAM));
+    KEEP(*(.vector_table.reset_vector));
+  } > FLASH
+
+  .text : { *(.text .text.*); } > FLASH
+  .rodata : { *(.rodata .rodata.*); } > FLASH
+
+  .data : { __sdata = .; *(.data .data.*); . = ALIGN(4); __edata = .; } > RAM AT > FLASH
+  __sidata = LOADADDR(.data);
+  .bss (NOLOAD) : { __sbss = .; *(.bss .bss.*); . = ALIGN(4); __ebss = .; } > RAM
+
+  /DISCARD/ : { *(.ARM.exidx .ARM.exidx.*); }
+}
@@ -0,0 +1,209 @@
+//! Lazy values shared between many owners
+
+use core::{cell::UnsafeCell, ops::Deref, sync::atomic::Ordering};
+use crate::sync::AtomicU8;
+use alloc::sync::{Arc, Weak};
+use crate::{Lazy, UNINIT, INITIALIZING, policy::{PoisonPolicy, Repanic}, strategy::{WaitStrategy, DefaultStrategy}};
+
+/// A clonable handle to a shared [`Lazy`], initialized once across all of its clones.
+///
+/// Every clone points to the same cell, so it can be handed to many owners (or moved into spawned threads and tasks)
+/// without needing a ```static```. Besides construction, it's used exactly like the inner cell, which it dereferences to
+///
+/// ```rust
+/// use laizy::LazyArc;
+///
+/// let config = LazyArc::new(|| String::from("config"));
+/// let handle = std::thread::spawn({
+///     let config = config.clone();
+///     move || config.get().len()
+/// });
+///
+/// assert_eq!(handle.join().unwrap(), 6);
+/// assert_eq!(config.try_unwrap().ok().as_deref(), Some("config"));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct LazyArc<T, F = fn() -> T, P = Repanic, S: WaitStrategy = DefaultStrategy> {
+    inner: Arc<Lazy<T, F, P, S>>,
+    /// Shared by every handle to the cell (and every weak handle built from them), so that they all recreate the same one
+    current: Arc<Current<T, F, P, S>>
+}
+
+impl<T, F> LazyArc<T, F> {
+    /// Builds a new ```LazyArc``` value
+    #[inline(always)]
+    pub fn new (f: F) -> Self {
+        Self::from_lazy(Lazy::new(f))
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> LazyArc<T, F, P, S> {
+    /// Shares an existing cell
+    #[inline(always)]
+    pub fn from_lazy (lazy: Lazy<T, F, P, S>) -> Self {
+        let inner = Arc::new(lazy);
+        let current = Arc::new(Current {
+            lock: AtomicU8::new(UNINIT),
+            weak: UnsafeCell::new(Arc::downgrade(&inner))
+        });
+        Self { inner, current }
+    }
+
+    /// Returns ```true``` if both handles point to the same cell
+    #[inline(always)]
+    pub fn ptr_eq (&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the number of handles pointing to this cell
+    #[inline(always)]
+    pub fn handle_count (&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// Builds a weak handle to the cell, which doesn't keep it (nor its value) alive. Every weak handle built from
+    /// the same cell (through any of its handles) recreates the same new one
+    #[inline(always)]
+    pub fn downgrade (&self) -> WeakLazy<T, F, P, S> {
+        WeakLazy { shared: self.current.clone() }
+    }
+
+    /// Returns the inner cell if this is its only handle, or the handle back otherwise
+    #[inline(always)]
+    pub fn try_into_lazy (self) -> Result<Lazy<T, F, P, S>, Self> {
+        let Self { inner, current } = self;
+        Arc::try_unwrap(inner).map_err(|inner| Self { inner, current })
+    }
+}
+
+impl<T, F: FnOnce() -> T, P: PoisonPolicy<F>, S: WaitStrategy> LazyArc<T, F, P, S> {
+    /// Returns the inner value (initializing it if necessary) if this is the cell's only handle, or the handle back otherwise
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization function ran, but panicked (the value is poisoned).
+    #[inline]
+    pub fn try_unwrap (self) -> Result<T, Self> {
+        self.try_into_lazy().map(Lazy::into_inner)
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Clone for LazyArc<T, F, P, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), current: self.current.clone() }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> core::fmt::Debug for LazyArc<T, F, P, S> where Lazy<T, F, P, S>: core::fmt::Debug {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LazyArc").field("inner", &self.inner).finish()
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Deref for LazyArc<T, F, P, S> {
+    type Target = Lazy<T, F, P, S>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> From<Lazy<T, F, P, S>> for LazyArc<T, F, P, S> {
+    #[inline(always)]
+    fn from(lazy: Lazy<T, F, P, S>) -> Self {
+        Self::from_lazy(lazy)
+    }
+}
+
+/// A weak handle to the cell of a [`LazyArc`], built by [`LazyArc::downgrade`], which doesn't keep it alive.
+///
+/// Once every [`LazyArc`] pointing to the cell is dropped, [`get_or_recreate`](WeakLazy::get_or_recreate) builds a new
+/// one, which the handle (and every other weak handle to the cell) points to from then on. This allows caching an expensive value for as
+/// long as someone uses it
+///
+/// ```rust
+/// use laizy::LazyArc;
+///
+/// let make: fn() -> Vec<u8> = || vec![0; 1024];
+/// let buffer = LazyArc::new(make);
+/// let weak = buffer.downgrade();
+/// assert!(weak.upgrade().is_some());
+///
+/// drop(buffer);
+/// assert!(weak.upgrade().is_none());
+/// assert_eq!(weak.get_or_recreate(make).get().len(), 1024);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct WeakLazy<T, F = fn() -> T, P = Repanic, S: WaitStrategy = DefaultStrategy> {
+    shared: Arc<Current<T, F, P, S>>
+}
+
+/// Cell currently pointed to by the [`WeakLazy`] handles to a cell, behind a lock
+struct Current<T, F, P, S: WaitStrategy> {
+    lock: AtomicU8,
+    weak: UnsafeCell<Weak<Lazy<T, F, P, S>>>
+}
+
+impl<T, F, P, S: WaitStrategy> WeakLazy<T, F, P, S> {
+    /// Returns a strong handle to the cell, or ```None``` if every strong handle to it has been dropped
+    #[inline]
+    pub fn upgrade (&self) -> Option<LazyArc<T, F, P, S>> {
+        self.with_current(|weak| weak.upgrade()).map(|inner| LazyArc { inner, current: self.shared.clone() })
+    }
+
+    /// Returns a strong handle to the cell, or builds a new one (initialized by `f`) if every strong handle to it has
+    /// been dropped. Concurrent callers (through any weak handle to the cell) get the same new cell, whose value is
+    /// initialized once, while `f` is dropped by everyone else
+    pub fn get_or_recreate (&self, f: F) -> LazyArc<T, F, P, S> {
+        let inner = self.with_current(|weak| match weak.upgrade() {
+            Some(inner) => inner,
+            None => {
+                let inner = Arc::new(Lazy::with_policy(f));
+                *weak = Arc::downgrade(&inner);
+                inner
+            }
+        });
+        LazyArc { inner, current: self.shared.clone() }
+    }
+
+    /// Runs `f` on the current cell, while holding the lock. The value isn't initialized while holding it, so it's
+    /// only ever held for a short while
+    fn with_current<R> (&self, f: impl FnOnce(&mut Weak<Lazy<T, F, P, S>>) -> R) -> R {
+        let Current { lock, weak } = &*self.shared;
+        while lock.compare_exchange_weak(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            S::wait(lock, INITIALIZING);
+        }
+
+        // releases the lock even if `f` panics, or a dropped initializer does
+        struct Unlock<'a, S: WaitStrategy>(&'a AtomicU8, core::marker::PhantomData<S>);
+        impl<S: WaitStrategy> Drop for Unlock<'_, S> {
+            #[inline(always)]
+            fn drop(&mut self) {
+                self.0.store(UNINIT, Ordering::Release);
+                S::notify(self.0)
+            }
+        }
+
+        let _unlock = Unlock::<S>(lock, core::marker::PhantomData);
+        unsafe { f(&mut *weak.get()) }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Clone for WeakLazy<T, F, P, S> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> core::fmt::Debug for WeakLazy<T, F, P, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WeakLazy").finish_non_exhaustive()
+    }
+}
+
+// The weak handle is only touched while holding the lock, and it hands out `LazyArc`s
+unsafe impl<T, F, P, S: WaitStrategy> Send for Current<T, F, P, S> where Lazy<T, F, P, S>: Send + Sync {}
+unsafe impl<T, F, P, S: WaitStrategy> Sync for Current<T, F, P, S> where Lazy<T, F, P, S>: Send + Sync {}
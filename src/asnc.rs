@@ -1,177 +1,1428 @@
-use core::{mem::MaybeUninit, sync::atomic::{Ordering, AtomicU8}, cell::UnsafeCell};
-use core::{mem::ManuallyDrop};
-use futures::{Future, task::AtomicWaker};
-use crate::{utils::{AwaitInit}};
+use core::{mem::MaybeUninit, sync::atomic::Ordering, cell::UnsafeCell};
+use crate::sync::{AtomicU8, AtomicUsize, Track};
+use core::{mem::ManuallyDrop, marker::PhantomData, pin::Pin, task::{Context, Poll}};
+use core::{future::IntoFuture, ptr::NonNull};
+
+mod get;
+mod timeout;
+mod wait;
+mod in_place;
+mod once;
+mod try_lazy;
+mod retry;
+mod hook;
+mod stream;
+mod select;
+mod guard;
+mod ready;
+mod cached;
+mod timed;
+mod join;
+#[cfg(feature = "tokio")]
+mod blocking;
+#[cfg(any(feature = "tokio", feature = "async-once-cell"))]
+mod interop;
+pub mod local;
+#[cfg(feature = "alloc")]
+mod shared;
+#[cfg(feature = "alloc")]
+mod boxed;
+#[cfg(feature = "std")]
+mod map;
+pub use get::{Get, GetMut};
+#[cfg(feature = "alloc")]
+use get::Suspended;
+#[cfg(feature = "alloc")]
+pub use shared::SharedAsyncLazy;
+#[cfg(feature = "alloc")]
+pub use boxed::{BoxedAsyncLazy, LocalBoxedAsyncLazy, BoxedAsyncLazyFn};
+#[cfg(feature = "std")]
+pub use map::AsyncLazyMap;
+pub use hook::{Hooked, HookedFuture};
+pub use stream::{LazyStream, AsyncLazyStream};
+pub use guard::AsyncInitGuard;
+pub use ready::SyncView;
+pub use cached::AsyncCached;
+pub use timed::{Clock, AsyncTimedLazy};
+#[cfg(feature = "std")]
+pub use timed::StdClock;
+#[cfg(feature = "tokio")]
+pub use blocking::{Blocking, SpawnBlocking};
+pub use select::{Either, Select, Race, select, race};
+pub use retry::{RetryPolicy, Retry, Retrying};
+pub use try_lazy::{AsyncTryLazy, TryGet};
+pub use once::{AsyncOnce, GetOrInit, GetOrTryInit};
+pub use in_place::GetInPlace;
+pub use timeout::{GetTimeout, Elapsed};
+pub use wait::Wait;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::utils::Join;
+    pub use super::join::{Flatten, TryTuple};
+}
+
+use core::future::Future;
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, CANCELLED, PARKED, TAKEN, ABORTING, SUSPENDED, State, poisoned, cancelled, taken, abandoned, utils::{AwaitInit, AsyncPoisonGuard, Waiters, Join}};
+use crate::strategy::{WaitStrategy, DefaultStrategy};
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicU64;
 
 #[cfg(not(debug_assertions))]
 use core::hint::unreachable_unchecked;
 
 /// A lazy value that initializes via future
+///
+/// Like [`Lazy`](crate::Lazy), it's invariant over both ```T``` and ```F```
 #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
-#[derive(Debug)]
 pub struct AsyncLazy<T, F> {
     state: AtomicU8,
-    waker: AtomicWaker,
+    waiters: Waiters,
+    /// Number of [`clone`](AsyncLazy::clone)s currently reading the initialization future
+    clones: AtomicUsize,
+    /// When the initialization started, in nanoseconds since [`StdClock`]'s epoch
+    #[cfg(feature = "std")]
+    started: AtomicU64,
     value: UnsafeCell<MaybeUninit<T>>,
-    f: UnsafeCell<MaybeUninit<F>>
+    f: UnsafeCell<MaybeUninit<F>>,
+    /// Initialization future handed back by a dropped [`get`](AsyncLazy::get) future, while ```SUSPENDED```
+    #[cfg(feature = "alloc")]
+    suspended: UnsafeCell<Option<Suspended>>,
+    /// Accesses to the value, the initialization future and the suspended future
+    track: Track
 }
 
-// Values that `AsyncLazy::state` can be
-const UNINIT: u8 = UNINIT;
-const INITIALIZING: u8 = INITIALIZING;
-const INIT: u8 = INIT;
-
 impl<T, F> AsyncLazy<T, F> {
-    /// Builds a new ```AsyncLazy``` value
-    #[inline(always)]
-    pub const fn new (f: F) -> Self {
-        Self {
-            state: AtomicU8::new(UNINIT),
-            waker: AtomicWaker::new(),
-            value: UnsafeCell::new(MaybeUninit::uninit()),
-            f: UnsafeCell::new(MaybeUninit::new(f))
+    loom_const! {
+        /// Builds a new ```AsyncLazy``` value. `f` can be anything that converts into a future (see [`IntoFuture`]), and it's
+        /// only converted once the value is first needed, so the side effects of the conversion are deferred too
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                waiters: Waiters::new(),
+                clones: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                started: AtomicU64::new(0),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                f: UnsafeCell::new(MaybeUninit::new(f)),
+                #[cfg(feature = "alloc")]
+                suspended: UnsafeCell::new(None),
+                track: Track::new()
+            }
         }
     }
 
-    /// Builds an ```AsyncLazy``` value that's already initialized
-    #[inline(always)]
-    pub const fn init (value: T) -> Self {
-        Self {
-            state: AtomicU8::new(INIT),
-            waker: AtomicWaker::new(),
-            value: UnsafeCell::new(MaybeUninit::new(value)),
-            f: UnsafeCell::new(MaybeUninit::uninit())
+    loom_const! {
+        /// Builds an ```AsyncLazy``` value that's already initialized
+        #[inline(always)]
+        pub const fn init (value: T) -> Self {
+            Self {
+                state: AtomicU8::new(INIT),
+                waiters: Waiters::new(),
+                clones: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                started: AtomicU64::new(0),
+                value: UnsafeCell::new(MaybeUninit::new(value)),
+                f: UnsafeCell::new(MaybeUninit::uninit()),
+                #[cfg(feature = "alloc")]
+                suspended: UnsafeCell::new(None),
+                track: Track::new()
+            }
         }
     }
 
+    /// Returns the current state of the cell
+    #[inline(always)]
+    pub fn state (&self) -> State {
+        State::from_raw(self.state.load(Ordering::Acquire))
+    }
+
     /// Returns ```true``` if the value is uninitialized, ```false``` otherwise
     #[inline(always)]
     pub fn is_uninit (&self) -> bool {
-        self.state.load(Ordering::Acquire) == UNINIT
+        self.state() == State::Uninit
     }
     
     /// Returns ```true``` if the value is currently initializing, ```false``` otherwise
     #[inline(always)]
+    pub fn is_initializing (&self) -> bool {
+        self.state() == State::Initializing
+    }
+    
+    /// Returns ```true``` if the value has already initialized, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initialized (&self) -> bool {
+        self.state() == State::Initialized
+    }
+
+    /// Returns ```true``` if the value is currently initializing, ```false``` otherwise
+    #[deprecated(note = "easily mistaken for `is_initialized`, use `is_initializing` instead")]
+    #[inline(always)]
     pub fn is_init (&self) -> bool {
-        self.state.load(Ordering::Acquire) == INITIALIZING
+        self.is_initializing()
     }
     
     /// Returns ```true``` if the value has already initialized, ```false``` otherwise
+    #[deprecated(note = "use `is_initialized` instead")]
     #[inline(always)]
     pub fn has_init (&self) -> bool {
-        self.state.load(Ordering::Acquire) > INITIALIZING
+        self.is_initialized()
+    }
+
+    /// Returns ```true``` if the initialization future panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state() == State::Poisoned
+    }
+
+    /// Returns ```true``` if the initialization was cancelled (see [`get`](AsyncLazy::get)), ```false``` otherwise
+    #[inline(always)]
+    pub fn is_cancelled (&self) -> bool {
+        self.state() == State::Cancelled
+    }
+
+    /// Returns how many tasks are currently waiting for someone else's initialization of the value. Never polls the future
+    #[inline(always)]
+    pub fn waiter_count (&self) -> usize {
+        self.waiters.len()
+    }
+
+    /// Returns for how long the initialization has been running, if it's started but hasn't finished yet (including
+    /// while it's left partially polled by [`poll_get`](AsyncLazy::poll_get), or by a dropped [`get`](AsyncLazy::get)
+    /// future). Never polls the future
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn initializing_since (&self) -> Option<core::time::Duration> {
+        match self.state.load(Ordering::Acquire) {
+            INITIALIZING | PARKED | SUSPENDED => {
+                let started = core::time::Duration::from_nanos(self.started.load(Ordering::Relaxed));
+                Some(crate::Clock::now(&StdClock).saturating_sub(started))
+            },
+            _ => None
+        }
+    }
+
+    /// Records the start of the initialization, after the state has been set to ```INITIALIZING``` from `prev`
+    /// (resuming a partially polled future doesn't start it again)
+    #[inline(always)]
+    fn mark_started (&self, prev: u8) {
+        #[cfg(feature = "std")]
+        if prev == UNINIT {
+            self.started.store(crate::Clock::now(&StdClock).as_nanos() as u64, Ordering::Relaxed);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = prev;
+    }
+
+    /// Waits for every [`clone`](AsyncLazy::clone) still reading the initialization future, after the state has been
+    /// moved out of ```UNINIT``` or ```PARKED```, and before the future is touched. Clones only take a moment, and no new
+    /// ones start reading it once the state moved
+    #[inline]
+    fn wait_for_clones (&self) {
+        // pairs with the fence in `clone`: either it sees the new state, or we see its count
+        crate::sync::fence(Ordering::SeqCst);
+        let mut backoff = crate::utils::Backoff::new();
+        while self.clones.load(Ordering::Acquire) > 0 {
+            backoff.snooze()
+        }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                Some((&*self.value.get()).assume_init_ref())
+            },
+            _ => None
+        }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get_mut (&mut self) -> Option<&mut T> {
+        match *self.state.get_mut() {
+            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) }
+            _ => None
+        }
+    }
+
+    /// Returns a reference to the inner value, without checking if it's initialized (which is only asserted in debug builds)
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized, and its initialization must happen-before this call: e.g. the calling thread (or one that
+    /// synchronized with it since) observed [`is_initialized`](AsyncLazy::is_initialized) return ```true```, or awaited a
+    /// [`get`](AsyncLazy::get) future. Between that and this call, the value mustn't have been [taken](AsyncLazy::take) nor the cell [reset](AsyncLazy::reset)
+    #[inline(always)]
+    pub unsafe fn get_unchecked (&self) -> &T {
+        debug_assert_eq!(self.state.load(Ordering::Acquire), INIT, "`AsyncLazy::get_unchecked` called on an uninitialized cell");
+        self.track.read();
+        (*self.value.get()).assume_init_ref()
+    }
+
+    /// Returns a mutable reference to the inner value, without checking if it's initialized (which is only asserted in debug builds)
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized (see [`get_unchecked`](AsyncLazy::get_unchecked))
+    #[inline(always)]
+    pub unsafe fn get_mut_unchecked (&mut self) -> &mut T {
+        debug_assert_eq!(*self.state.get_mut(), INIT, "`AsyncLazy::get_mut_unchecked` called on an uninitialized cell");
+        self.value.get_mut().assume_init_mut()
+    }
+
+    /// Moves the value out of the cell into a leaked allocation if it's initialized, so that it lives (without ever
+    /// being dropped) for the rest of the program. Returns ```None``` otherwise, dropping the cell
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn leak_initialized (mut self) -> Option<&'static mut T> where T: 'static {
+        self.take().map(|value| alloc::boxed::Box::leak(alloc::boxed::Box::new(value)))
+    }
+
+    /// Returns a pointer to the slot the value is (or will be) stored in. The address is stable for as long as the cell
+    /// isn't moved, but it may only be dereferenced while the value is initialized (see [`is_initialized`](AsyncLazy::is_initialized))
+    #[inline(always)]
+    pub const fn as_ptr (&self) -> *const T {
+        self.value.get().cast()
+    }
+
+    /// Returns a non-null pointer to the slot the value is (or will be) stored in (see [`as_ptr`](AsyncLazy::as_ptr))
+    #[inline(always)]
+    pub const fn as_non_null (&self) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(self.value.get().cast()) }
+    }
+
+    /// Returns a reference to the inner value once it's initialized by someone else, without ever polling the initialization
+    /// future itself (even if nobody has started initializing it yet).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future panicked (the value is poisoned), or if it was cancelled
+    #[inline(always)]
+    pub fn wait (&self) -> Wait<'_, T> {
+        Wait::new(&self.value, AwaitInit::settled(&self.state, &self.waiters))
+    }
+
+    /// Initializes the cell with `value`, dropping the initialization future without polling it, and waking every task
+    /// waiting on it.
+    ///
+    /// Returns `value` back if the initialization already started (or finished)
+    pub fn set (&self, value: T) -> Result<(), T> {
+        if self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(value)
+        }
+
+        self.wait_for_clones();
+        self.track.write();
+        unsafe {
+            let guard = AsyncPoisonGuard::new(&self.state, &self.waiters);
+            core::ptr::drop_in_place((*self.f.get()).as_mut_ptr());
+            guard.disarm();
+
+            self.value.get().write(MaybeUninit::new(value));
+            self.complete()
+        }
+    }
+
+    /// Moves the value out of the cell if it's initialized, leaving it empty.
+    ///
+    /// Afterwards, the value is never initialized again (the initialization future is gone), so [`try_get`](AsyncLazy::try_get)
+    /// returns ```None```, and [`get`](AsyncLazy::get) or [`into_inner`](AsyncLazy::into_inner) panic
+    #[inline]
+    pub fn take (&mut self) -> Option<T> {
+        let state = *self.state.get_mut();
+        match state {
+            INIT => unsafe {
+                *self.state.get_mut() = TAKEN;
+                Some(self.value.get_mut().assume_init_read())
+            },
+            _ => None
+        }
+    }
+
+    /// Stores `value` in the cell, marking it as initialized, and returns the previous value (if any).
+    ///
+    /// If the cell was still uninitialized, its initialization future is dropped without being polled
+    pub fn replace (&mut self, value: T) -> Option<T> {
+        let state = *self.state.get_mut();
+        let prev = match state {
+            INIT => return Some(unsafe { core::mem::replace(self.value.get_mut().assume_init_mut(), value) }),
+
+            // nothing left to drop until the value is written, in case dropping the future panics
+            UNINIT | PARKED => unsafe {
+                *self.state.get_mut() = TAKEN;
+                self.f.get_mut().assume_init_drop();
+                None
+            },
+
+            // handed back by a dropped `get` future
+            SUSPENDED => unsafe {
+                *self.state.get_mut() = TAKEN;
+                self.drop_suspended();
+                None
+            },
+
+            // taken, poisoned, cancelled, or abandoned by a leaked `get` future (no value, nor future)
+            _ => None
+        };
+
+        self.value.get_mut().write(value);
+        *self.state.get_mut() = INIT;
+        prev
+    }
+
+    /// Returns a reference to the initialization future if it's still stored in the cell (it hasn't been started by
+    /// a [`get`](AsyncLazy::get) future, or it was left partially polled by [`poll_get`](AsyncLazy::poll_get)), ```None``` otherwise
+    #[inline]
+    pub fn initializer (&mut self) -> Option<&F> {
+        let state = *self.state.get_mut();
+        match state {
+            UNINIT | PARKED => unsafe { Some(self.f.get_mut().assume_init_ref()) },
+            _ => None
+        }
+    }
+
+    /// Moves the initialization future out of the cell if it's still stored in it (see [`initializer`](AsyncLazy::initializer)).
+    ///
+    /// Afterwards, nothing is left to initialize the cell, so it stays initializing forever (every [`get`](AsyncLazy::get)
+    /// future stays pending, and [`get_mut`](AsyncLazy::get_mut) panics) until it's [`replace`](AsyncLazy::replace)d or [`reset`](AsyncLazy::reset)
+    #[inline]
+    pub fn take_initializer (&mut self) -> Option<F> {
+        let state = *self.state.get_mut();
+        match state {
+            UNINIT | PARKED => unsafe {
+                *self.state.get_mut() = INITIALIZING;
+                Some(self.f.get_mut().assume_init_read())
+            },
+            _ => None
+        }
+    }
+
+    /// Applies `f` to the value if it's initialized. Otherwise, the cell is initialized with `default` (dropping its
+    /// initialization future without polling it, if it's still stored) before applying `f` to it
+    #[inline]
+    pub fn update_or<M: FnOnce(&mut T)> (&mut self, default: T, f: M) {
+        if *self.state.get_mut() != INIT {
+            self.replace(default);
+        }
+
+        f(unsafe { self.value.get_mut().assume_init_mut() })
+    }
+
+    /// Drops the value (or initialization future) of the cell, and makes it uninitialized again, with `f` as its new
+    /// initialization future. Works from any state, including after being poisoned, cancelled or taken
+    pub fn reset (&mut self, f: F) {
+        let prev = core::mem::replace(&mut *self.state.get_mut(), TAKEN);
+
+        // nothing left to drop until the future is written, in case dropping the previous contents panics
+        match prev {
+            INIT => unsafe { self.value.get_mut().assume_init_drop() },
+            UNINIT | PARKED => unsafe { self.f.get_mut().assume_init_drop() },
+            SUSPENDED => unsafe { self.drop_suspended() },
+            _ => {}
+        }
+
+        self.f.get_mut().write(f);
+        self.waiters.clear();
+        *self.state.get_mut() = UNINIT;
+    }
+
+    /// Wraps the initialization future with `wrap` (e.g. to time-limit or instrument it) if it's still stored in the
+    /// cell (see [`initializer`](AsyncLazy::initializer)). Otherwise, the new cell is left as this one was: initialized
+    /// with the same value, or poisoned, cancelled or taken. A future handed back to the cell by a dropped [`get`](AsyncLazy::get)
+    /// future has already been converted, so it can't be wrapped: it's dropped, and the new cell is cancelled
+    pub fn map_initializer<G: IntoFuture<Output = T>, W: FnOnce(F) -> G> (self, wrap: W) -> AsyncLazy<T, G> {
+        let mut this = ManuallyDrop::new(self);
+        // nobody can be waiting on a cell we own, but `poll_get` may have left its caller's waker in it
+        unsafe { core::ptr::drop_in_place(&mut this.waiters) }
+
+        let state = *this.state.get_mut();
+        match state {
+            UNINIT | PARKED => unsafe {
+                let f = core::ptr::read(this.f.get()).assume_init();
+                AsyncLazy::new(wrap(f))
+            },
+
+            INIT => unsafe {
+                let value = core::ptr::read(this.value.get()).assume_init();
+                AsyncLazy::init(value)
+            },
+
+            // nothing to move
+            state => AsyncLazy {
+                state: AtomicU8::new(match state {
+                    SUSPENDED => unsafe {
+                        this.drop_suspended();
+                        CANCELLED
+                    },
+                    state => state
+                }),
+                waiters: Waiters::new(),
+                clones: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                started: AtomicU64::new(0),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                f: UnsafeCell::new(MaybeUninit::uninit()),
+                #[cfg(feature = "alloc")]
+                suspended: UnsafeCell::new(None),
+                track: Track::new()
+            }
+        }
+    }
+
+    /// Marks the cell as initialized (after the value has been written), waking every task waiting for it.
+    ///
+    /// If the initialization was [aborted](AsyncLazy::abort) in the meantime, the cell is cancelled instead, and the value
+    /// is moved back out of it
+    #[inline]
+    fn complete (&self) -> Result<(), T> {
+        let result = match self.state.compare_exchange(INITIALIZING, INIT, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => Ok(()),
+            Err(_aborting) => unsafe {
+                debug_assert_eq!(_aborting, ABORTING);
+                let value = (*self.value.get()).assume_init_read();
+                self.state.store(CANCELLED, Ordering::Release);
+                Err(value)
+            }
+        };
+
+        DefaultStrategy::notify(&self.state);
+        self.waiters.wake_all();
+        result
+    }
+
+    /// Drops the initialization future handed back by a dropped [`get`](AsyncLazy::get) future. The state must have been moved
+    /// out of ```SUSPENDED``` by the caller
+    #[inline]
+    unsafe fn drop_suspended (&self) {
+        self.track.write();
+        #[cfg(feature = "alloc")]
+        drop((*self.suspended.get()).take())
+    }
+
+    /// Aborts the initialization if it's running (or left partially polled by [`poll_get`](AsyncLazy::poll_get), or by a
+    /// dropped [`get`](AsyncLazy::get) future), returning ```true``` if it did, and ```false``` if there was no
+    /// initialization to abort.
+    ///
+    /// This doesn't preempt the initialization future. Instead, the cell is cancelled, which every task waiting on it
+    /// observes right away, and the task running the initialization future is woken up, so that it drops the future (and
+    /// panics, like the waiting ones) the next time it polls it. If the future finishes before that, its value is discarded.
+    /// Afterwards, the cell can be [`reset`](AsyncLazy::reset)
+    pub fn abort (&self) -> bool {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            match current {
+                INITIALIZING => match self.state.compare_exchange_weak(INITIALIZING, ABORTING, Ordering::Acquire, Ordering::Acquire) {
+                    Ok(_) => break,
+                    Err(state) => current = state
+                },
+
+                // nobody is running the future, so we drop it ourselves
+                PARKED | SUSPENDED => match self.state.compare_exchange_weak(current, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                    Ok(_) => unsafe {
+                        let guard = AsyncPoisonGuard::new(&self.state, &self.waiters);
+                        match current {
+                            PARKED => {
+                                self.wait_for_clones();
+                                self.track.write();
+                                core::ptr::drop_in_place((*self.f.get()).as_mut_ptr())
+                            },
+                            _ => self.drop_suspended()
+                        }
+                        drop(guard);
+                        return true
+                    },
+                    Err(state) => current = state
+                },
+
+                _ => return false
+            }
+        }
+
+        // before `wake_all`, which forgets about the driver
+        self.waiters.wake_driver();
+        DefaultStrategy::notify(&self.state);
+        self.waiters.wake_all();
+        true
     }
 }
 
-impl<T, F: Future<Output = T>> AsyncLazy<T, F> {
+impl<T, F: IntoFuture<Output = T>> AsyncLazy<T, F> {
     /// Returns a reference to the inner value, initializing or waiting for it of necesary
+    ///
+    /// # Cancellation
+    ///
+    /// The initialization future is polled by whichever ```get``` future started it. With the ```alloc``` feature, it's
+    /// boxed, so that if that ```get``` future is dropped before the initialization finishes (e.g. it lost a ```select!```,
+    /// or timed out), the partially polled future is handed back to the cell as is, and the next ```get``` future (one that
+    /// was waiting, or a later one) resumes it. Without it, the initialization future is pinned on the dropped ```get```
+    /// future, so it's dropped along with it, and the cell is cancelled.
+    ///
+    /// # Wake order
+    ///
+    /// Tasks waiting for another task's initialization are woken first-come-first-served once it finishes, in the order
+    /// their futures were first polled, so early waiters aren't scheduled behind a flood of late ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future panicked (the value is poisoned), if it was cancelled, or if the value was
+    /// [taken](AsyncLazy::take)
     #[inline(always)]
-    pub async fn get (&self) -> &T {
-        match self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed) {
-            // uninitialized
-            Ok(UNINIT) => unsafe {
-                let f = core::mem::replace(&mut *self.f.get(), MaybeUninit::uninit());
-                (&mut *self.value.get()).write(f.assume_init().await);
+    pub fn get (&self) -> Get<'_, T, F> {
+        Get::new(self)
+    }
 
-                #[cfg(debug_assertions)]
-                assert_eq!(self.state.swap(INIT, Ordering::Release), INITIALIZING);
-                #[cfg(not(debug_assertions))]
-                self.state.store(INIT, Ordering::Release);
-                self.waker.wake();
+    /// Returns a clone of the inner value, initializing or waiting for it if necessary, so that it can outlive the borrow
+    /// of the cell (e.g. to move it into a spawned task)
+    #[inline(always)]
+    pub async fn get_owned (&self) -> T where T: Clone {
+        self.get().await.clone()
+    }
+
+    /// Returns a reference to the inner value, initializing it with `g` instead of the stored future if nobody started
+    /// the initialization yet (the stored future is then dropped without being polled). Otherwise, `g` is dropped
+    /// without being polled, and this behaves like [`get`](AsyncLazy::get).
+    ///
+    /// If `g` panics or is cancelled, the cell goes back to how it was, stored future included (see [`begin_init`](AsyncLazy::begin_init))
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`get`](AsyncLazy::get) does, or if `g` panics
+    pub async fn get_or_init_with<G: IntoFuture<Output = T>> (&self, g: G) -> &T {
+        match self.begin_init() {
+            Some(guard) => {
+                let value = g.await;
+                guard.finish(value)
             },
+            None => self.get().await
+        }
+    }
 
-            // currently initializing
-            Err(INITIALIZING) => AwaitInit::new(INIT, &self.state, &self.waker).await,
+    /// Returns a reference to the inner value of a ```static``` cell, initializing or waiting for it if necessary.
+    /// Unlike [`get`](AsyncLazy::get), the returned future is guaranteed to be ```Send + 'static```, so it can be spawned
+    #[inline(always)]
+    pub fn get_static (&'static self) -> impl Future<Output = &'static T> + Send + 'static where T: Send + Sync, F: Send, F::IntoFuture: Send {
+        self.get()
+    }
 
-            // initialized
-            Err(INIT) => {},
+    /// Starts initializing the value of a ```static``` cell right away, on a new tokio task. Any later access
+    /// either finds the value initialized, or waits for the spawned initialization like it would for any other task's.
+    ///
+    /// The returned handle resolves once the value is initialized, and to an error if the initialization panicked
+    /// (or the task was cancelled, cancelling the cell)
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub fn spawn_init (&'static self) -> tokio::task::JoinHandle<()> where T: 'static + Send + Sync, F: 'static + Send, F::IntoFuture: Send {
+        let get = self.get_static();
+        tokio::spawn(async move { get.await; })
+    }
+
+    /// Starts initializing the value of a ```static``` cell right away, on a new task of `spawner`, like
+    /// [`spawn_init`](AsyncLazy::spawn_init) does for tokio, but for any executor implementing [```Spawn```](futures_task::Spawn)
+    ///
+    /// # Errors
+    ///
+    /// Returns the spawner's error if it couldn't spawn the task (e.g. it's shutting down). The cell is left untouched
+    #[cfg_attr(docsrs, doc(cfg(feature = "spawn")))]
+    #[cfg(feature = "spawn")]
+    #[inline]
+    pub fn spawn_init_with<S: ?Sized + futures_task::Spawn> (&'static self, spawner: &S) -> Result<(), futures_task::SpawnError> where T: 'static + Send + Sync, F: 'static + Send, F::IntoFuture: Send {
+        let get = self.get_static();
+        spawner.spawn_obj(futures_task::FutureObj::new(alloc::boxed::Box::new(async move { get.await; })))
+    }
+
+    /// Starts initializing the value of a leaked cell right away, on a new task of the single-threaded `spawner`, like
+    /// [`spawn_init_with`](AsyncLazy::spawn_init_with), but without requiring the value nor its initialization future to be ```Send```.
+    ///
+    /// Since the cell won't be ```Sync``` for ```!Send``` futures, every access to it (including the spawned task) has to stay on the same thread
+    ///
+    /// # Errors
+    ///
+    /// Returns the spawner's error if it couldn't spawn the task (e.g. it's shutting down). The cell is left untouched
+    #[cfg_attr(docsrs, doc(cfg(feature = "spawn")))]
+    #[cfg(feature = "spawn")]
+    #[inline]
+    pub fn spawn_init_local<S: ?Sized + futures_task::LocalSpawn> (&'static self, spawner: &S) -> Result<(), futures_task::SpawnError> where T: 'static, F: 'static {
+        let get = self.get();
+        spawner.spawn_local_obj(futures_task::LocalFutureObj::new(alloc::boxed::Box::new(async move { get.await; })))
+    }
+
+    /// Starts initializing the value of a leaked cell right away, on a new task of the browser's event loop (through
+    /// ```wasm_bindgen_futures::spawn_local```), like [`spawn_init_local`](AsyncLazy::spawn_init_local) does for other
+    /// single-threaded executors
+    #[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    #[inline]
+    pub fn spawn_init_wasm (&'static self) where T: 'static, F: 'static {
+        let get = self.get();
+        wasm_bindgen_futures::spawn_local(async move { get.await; })
+    }
+
+    /// Returns a mutable reference to the inner value, initializing it if necesary.
+    ///
+    /// Since the cell is borrowed mutably, nobody else can be initializing it, so the returned future accesses its state
+    /// non-atomically, and never waits for other tasks.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`get`](AsyncLazy::get), and if the cell was left initializing with nothing to finish
+    /// the initialization (its initializer was [taken](AsyncLazy::take_initializer), or the future initializing it was
+    /// leaked), since it'd stay pending forever otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> GetMut<'_, T, F> {
+        GetMut::new(self)
+    }
+
+    /// Applies `f` to the inner value, initializing or waiting for it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`get`](AsyncLazy::get)
+    #[inline]
+    pub async fn update<M: FnOnce(&mut T)> (&mut self, f: M) {
+        f(self.get_mut().await)
+    }
+
+    /// Returns a reference to the inner value like [`get`](AsyncLazy::get), unless `deadline` finishes first, in which case
+    /// it resolves to [`Elapsed`] instead. Any future can be used as the deadline (e.g. a runtime's sleep future).
+    ///
+    /// If this task was the one initializing the value when the deadline finished, it hands the initialization off right
+    /// away, like a dropped [`get`](AsyncLazy::get) future does (see its cancellation section): the partially polled future
+    /// goes back to the cell, and the next ```get``` future resumes it, instead of the cell being left initializing until the
+    /// returned future is dropped
+    #[inline(always)]
+    pub fn get_timeout<D: Future> (&self, deadline: D) -> GetTimeout<'_, T, F, D> {
+        GetTimeout::new(self, deadline)
+    }
+
+    /// Returns a reference to the inner value, blocking the current thread while initializing or waiting for it if necessary.
+    ///
+    /// The initialization future is driven by the current thread, which is parked until it's woken up. So, calling this from
+    /// inside of an asynchronous context will block the whole executor thread, and deadlock if that thread is the one that
+    /// has to make progress for the value to be initialized (e.g. on a single-threaded runtime)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future panicked (the value is poisoned), or if it was cancelled
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn get_blocking (&self) -> &T {
+        match self.try_get() {
+            Some(value) => value,
+            None => crate::utils::block_on(self.get())
+        }
+    }
+
+    /// Consumes the cell, building a new one whose value is the result of applying `m` to this one's.
+    /// Neither the initialization future nor `m` are called until the new cell is first needed
+    #[inline]
+    pub fn map<U, M: FnOnce(T) -> U> (self, m: M) -> AsyncLazy<U, impl Future<Output = U>> {
+        AsyncLazy::new(async move { m(self.into_inner().await) })
+    }
+
+    /// Builds a new cell whose value is the result of applying `m` to this one's (initializing it through
+    /// [`get`](AsyncLazy::get) if necessary). Unlike [`map`](AsyncLazy::map), this doesn't consume the cell,
+    /// so it can be used on ```static``` ones
+    #[inline]
+    pub fn map_ref<'a, U, M: 'a + FnOnce(&'a T) -> U> (&'a self, m: M) -> AsyncLazy<U, impl Future<Output = U> + 'a> {
+        AsyncLazy::new(async move { m(self.get().await) })
+    }
+
+    /// Returns references to the values of both cells, initializing or waiting for them concurrently
+    /// (through [`get`](AsyncLazy::get), so other tasks waiting on either cell aren't affected)
+    #[inline]
+    pub fn zip<'a, U, G: IntoFuture<Output = U>> (&'a self, other: &'a AsyncLazy<U, G>) -> impl Future<Output = (&'a T, &'a U)> {
+        Join::new(self.get(), other.get())
+    }
+
+    /// Consumes both cells, building a new one whose value is the pair of both values, initialized concurrently
+    /// when the new cell is first needed
+    #[inline]
+    pub fn join<U, G: IntoFuture<Output = U>> (self, other: AsyncLazy<U, G>) -> AsyncLazy<(T, U), impl Future<Output = (T, U)>> {
+        AsyncLazy::new(Join::new(self.into_inner(), other.into_inner()))
+    }
+
+    /// Returns a reference to the value of this cell or, if its initialization failed (it panicked or was cancelled),
+    /// to the value of `fallback`, initializing or waiting for either of them if necessary.
+    ///
+    /// Without ```std```, panics can't be caught, so the fallback is only used if this cell had already failed by the time
+    /// it's checked, and a panic while waiting on (or running) its initialization is propagated instead
+    pub async fn or_else<'a, G: IntoFuture<Output = T>> (&'a self, fallback: &'a AsyncLazy<T, G>) -> &'a T {
+        match self.state() {
+            State::Poisoned | State::Cancelled => {},
+
+            #[cfg(feature = "std")]
+            _ => {
+                let mut get = core::pin::pin!(self.get());
+                let primary = core::future::poll_fn(|cx| {
+                    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| get.as_mut().poll(cx))) {
+                        Ok(Poll::Ready(value)) => Poll::Ready(Some(value)),
+                        Ok(Poll::Pending) => Poll::Pending,
+                        // the cell is poisoned by now
+                        Err(_) => Poll::Ready(None)
+                    }
+                }).await;
+
+                if let Some(value) = primary {
+                    return value
+                }
+            },
+
+            #[cfg(not(feature = "std"))]
+            _ => return self.get().await
+        }
+
+        fallback.get().await
+    }
+
+    /// Moves the value (initializing it if necessary) into a leaked allocation, so that it lives (without ever
+    /// being dropped) for the rest of the program
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`into_inner`](AsyncLazy::into_inner)
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub async fn leak (self) -> &'static mut T where T: 'static {
+        alloc::boxed::Box::leak(alloc::boxed::Box::new(self.into_inner().await))
+    }
+
+    /// Returns the inner value, initializing it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future ran, but panicked (the value is poisoned), or was cancelled, if the value was
+    /// [taken](AsyncLazy::take), or if the initialization was abandoned (see [`get_mut`](AsyncLazy::get_mut)).
+    #[inline(always)]
+    pub async fn into_inner (self) -> T {
+        let mut this = ManuallyDrop::new(self);
+        // nobody can be waiting on a cell we own, but `poll_get` may have left its caller's waker in it
+        unsafe { core::ptr::drop_in_place(&mut this.waiters) }
+
+        // `Acquire` pairs with the `Release` store that finished the initialization: whatever handed us
+        // ownership (a channel, a join, ...) should already synchronize with it, but we don't rely on that
+        let state = this.state.load(Ordering::Acquire);
+        this.track.write();
+
+        match state {
+            // uninit, or left partially polled by `poll_get` (init value)
+            UNINIT | PARKED => unsafe {
+                let f = core::ptr::read(this.f.get()).assume_init();
+                f.await
+            },
+
+            // handed back, partially polled, by a dropped `get` future (resume it)
+            #[cfg(feature = "alloc")]
+            SUSPENDED => unsafe { this.resume().await },
+
+            // nobody can be initializing a cell we own, so its initializer was abandoned (see `get_mut`)
+            INITIALIZING => abandoned(),
+
+            // poisoned (happens if initialization panics)
+            POISONED => poisoned(),
+
+            // cancelled (happens if the initializing `get` future is dropped, without `alloc`), or aborted
+            CANCELLED | ABORTING => cancelled(),
+
+            // taken
+            TAKEN => taken(),
+
+            // init
+            INIT => unsafe {
+                let value = core::ptr::read(this.value.get());
+                value.assume_init()
+            },
 
             #[cfg(debug_assertions)]
             _ => unreachable!(),
             #[cfg(not(debug_assertions))]
             _ => unsafe { unreachable_unchecked() }
         }
+    }
+}
 
-        unsafe { (&*self.value.get()).assume_init_ref() }
+impl<T, F: IntoFuture> AsyncLazy<T, F> {
+    /// Hands the initialization future of a [`get`](AsyncLazy::get) future dropped before it finished back to the cell,
+    /// so that the next ```get``` future resumes it. If the initialization was [aborted](AsyncLazy::abort) in the meantime,
+    /// the future is dropped, and the cell cancelled, instead
+    #[cfg(feature = "alloc")]
+    #[cold]
+    #[inline(never)]
+    fn suspend (&self, f: Pin<alloc::boxed::Box<F::IntoFuture>>, guard: AsyncPoisonGuard<'_>) {
+        self.track.write();
+        unsafe { *self.suspended.get() = Some(Suspended::new(f)) }
+
+        // `Release` publishes the future to whoever resumes it
+        match self.state.compare_exchange(INITIALIZING, SUSPENDED, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => {
+                guard.disarm();
+                DefaultStrategy::notify(&self.state);
+                self.waiters.wake_all();
+            },
+
+            Err(_aborting) => unsafe {
+                debug_assert_eq!(_aborting, ABORTING);
+                self.drop_suspended();
+                drop(guard)
+            }
+        }
     }
 
-    /// Returns a mutable reference to the inner value, initializing or waiting for it of necesary
+    /// Takes the initialization future handed back by a dropped [`get`](AsyncLazy::get) future. The state must have been
+    /// set to ```INITIALIZING``` from ```SUSPENDED``` by the caller
+    #[cfg(feature = "alloc")]
+    #[inline]
+    unsafe fn resume (&self) -> Pin<alloc::boxed::Box<F::IntoFuture>> {
+        self.track.write();
+        (*self.suspended.get()).take().unwrap_unchecked().into_box()
+    }
+}
+
+impl<T: 'static, F: 'static + Future<Output = T>> AsyncLazy<T, F> {
+    /// Returns a reference to the inner value of a ```static``` (or leaked) cell, initializing or waiting for it if necessary.
+    ///
+    /// Unlike [`get`](AsyncLazy::get), the initialization future is polled in place, pinned inside the cell, instead of
+    /// being moved into the returned future, so the latter stays small no matter how big the initialization future is.
+    /// Since the cell may be moved once its borrow ends (even with an initialization future leaked halfway through), this is
+    /// only possible while it's borrowed forever.
+    ///
+    /// # Cancellation
+    ///
+    /// Unlike with [`get`](AsyncLazy::get#cancellation), the initialization future is pinned inside the cell, where nothing
+    /// but another ```get_in_place``` could resume it. So if the future initializing the value is dropped before the
+    /// initialization finishes, the initialization future is dropped (in place), and the cell is cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`get`](AsyncLazy::get)
     #[inline(always)]
-    pub async fn get_mut (&mut self) -> &mut T {
-        match self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed) {
-            // uninitialized
-            Ok(UNINIT) => unsafe {
-                let f = core::mem::replace(&mut *self.f.get(), MaybeUninit::uninit());
-                (&mut *self.value.get()).write(f.assume_init().await);
+    pub fn get_in_place (&'static self) -> GetInPlace<T, F> {
+        GetInPlace::new(self)
+    }
+}
+
+impl<T, F: Future<Output = T> + Unpin> AsyncLazy<T, F> {
+    /// Polls the inner value, initializing (with `cx`) or waiting for it of necesary.
+    ///
+    /// Unlike [`get`](AsyncLazy::get), the initialization future is polled in place, and left in the cell between polls,
+    /// so that any later call to ```poll_get``` (or ```get``` future) can keep driving it. Since the cell may be moved between
+    /// polls, this requires the future to be ```Unpin``` (and an actual future, since converting an [`IntoFuture`] moves it out of the cell).
+    ///
+    /// While another task is initializing the value, only the last task to call ```poll_get``` is registered to be woken
+    /// (the ones before it are woken right away, so they poll again). Waiting via ```get``` futures has no such limitation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future panicked (the value is poisoned), or if it was cancelled
+    pub fn poll_get (&self, cx: &mut Context<'_>) -> Poll<&T> {
+        loop {
+            match self.state.load(Ordering::Acquire) {
+                // initialized
+                INIT => return unsafe {
+                    self.track.read();
+                    Poll::Ready((*self.value.get()).assume_init_ref())
+                },
+
+                // uninitialized, or left by the last call (or a dropped `get` future)
+                state @ (UNINIT | PARKED | SUSPENDED) => {
+                    if self.state.compare_exchange(state, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                        self.wait_for_clones();
+                        self.mark_started(state);
+                        return unsafe {
+                            self.unsuspend(state);
+                            self.poll_in_place(cx)
+                        }
+                    }
+                },
+
+                // currently initializing
+                INITIALIZING => {
+                    self.waiters.register_untracked(cx.waker());
+                    if self.state.load(Ordering::Acquire) == INITIALIZING {
+                        return Poll::Pending
+                    }
+                },
+
+                // initialization panicked
+                POISONED => poisoned(),
+
+                // initialization was cancelled (or aborted)
+                CANCELLED | ABORTING => cancelled(),
+
+                // value was taken
+                TAKEN => taken(),
 
                 #[cfg(debug_assertions)]
-                assert_eq!(self.state.swap(INIT, Ordering::Release), INITIALIZING);
+                _ => unreachable!(),
                 #[cfg(not(debug_assertions))]
-                self.state.store(INIT, Ordering::Release);
-                self.waker.wake();
+                _ => unsafe { unreachable_unchecked() }
+            }
+        }
+    }
+
+    /// Advances the initialization without waiting for it: if nobody else is running the initialization future, it's
+    /// polled once in place (like [`poll_get`](AsyncLazy::poll_get) does), and left in the cell for later polls if it's
+    /// still pending. Returns ```true``` if the value is initialized afterwards, ```false``` otherwise.
+    ///
+    /// Unlike ```poll_get```, this never waits on someone else's initialization, nor panics if the value is poisoned or cancelled
+    /// (it just returns ```false```), but it does if the future panics while it's polled
+    pub fn force_now (&self, cx: &mut Context<'_>) -> bool {
+        match self.state.load(Ordering::Acquire) {
+            INIT => true,
+            state @ (UNINIT | PARKED | SUSPENDED) => match self.state.compare_exchange(state, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                Ok(_) => unsafe {
+                    self.wait_for_clones();
+                    self.mark_started(state);
+                    self.unsuspend(state);
+                    self.poll_in_place(cx).is_ready()
+                },
+                Err(state) => state == INIT
             },
+            _ => false
+        }
+    }
 
-            // currently initializing
-            Err(INITIALIZING) => AwaitInit::new(INIT, &self.state, &self.waker).await,
+    /// Moves the initialization future handed back by a dropped [`get`](AsyncLazy::get) future into the cell (it's ```Unpin```),
+    /// if the state was set to ```INITIALIZING``` from ```SUSPENDED``` by the caller, so that it's polled in place from then on
+    #[inline(always)]
+    unsafe fn unsuspend (&self, prev: u8) {
+        #[cfg(feature = "alloc")]
+        if prev == SUSPENDED {
+            let f = Pin::into_inner(self.resume());
+            self.f.get().write(MaybeUninit::new(*f));
+        }
+        #[cfg(not(feature = "alloc"))]
+        let _ = prev;
+    }
 
-            // initialized
-            Err(INIT) => {},
+    /// Polls the initialization future inside of the cell. The state must have been set to ```INITIALIZING``` by the caller
+    unsafe fn poll_in_place (&self, cx: &mut Context<'_>) -> Poll<&T> {
+        self.track.write();
+        let guard = AsyncPoisonGuard::new(&self.state, &self.waiters);
+        let f = (*self.f.get()).as_mut_ptr();
 
-            #[cfg(debug_assertions)]
-            _ => unreachable!(),
-            #[cfg(not(debug_assertions))]
-            _ => unsafe { unreachable_unchecked() }
+        // declared after `guard`, so the future is dropped before the cell is poisoned
+        let unwinding = DropOnUnwind(f);
+        let poll = Pin::new(&mut *f).poll(cx);
+        core::mem::forget(unwinding);
+        guard.disarm();
+
+        match poll {
+            Poll::Ready(value) => {
+                core::ptr::drop_in_place(f);
+                self.value.get().write(MaybeUninit::new(value));
+
+                if self.complete().is_err() {
+                    cancelled()
+                }
+                Poll::Ready((*self.value.get()).assume_init_ref())
+            },
+
+            Poll::Pending => {
+                // leave the future for whoever polls next (us, once it wakes us up), and let the `get` futures
+                // waiting on the cell take over, in case we never do. If they do, we're woken once they finish
+                if self.state.compare_exchange(INITIALIZING, PARKED, Ordering::Release, Ordering::Relaxed).is_err() {
+                    // aborted while we polled it
+                    let guard = AsyncPoisonGuard::new(&self.state, &self.waiters);
+                    core::ptr::drop_in_place(f);
+                    drop(guard);
+                    cancelled()
+                }
+
+                self.waiters.wake_queued();
+                self.waiters.register_untracked(cx.waker());
+                Poll::Pending
+            }
         }
+    }
+}
+
+/// Drops the future it points to if dropped (only happens while unwinding)
+struct DropOnUnwind<F>(*mut F);
 
-        unsafe { self.value.get_mut().assume_init_mut() }
+impl<F> Drop for DropOnUnwind<F> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(self.0) }
     }
+}
+
+/// Panics if the state the cell settled on after waiting for its initialization isn't ```INIT```
+#[inline(always)]
+fn settled (state: u8) {
+    match state {
+        POISONED => poisoned(),
+        CANCELLED | ABORTING => cancelled(),
+        TAKEN => taken(),
+        _ => {}
+    }
+}
+
+impl<T, C> AsyncLazy<T, FromFn<C>> {
+    loom_const! {
+        /// Builds a new ```AsyncLazy``` value whose initialization future is built by `f` when it's first needed.
+        ///
+        /// Unlike [`new`](AsyncLazy::new), this allows for ```static``` values (as long as the type of `f` can be named)
+        ///
+        /// ```rust
+        /// use laizy::{AsyncLazy, FromFn};
+        /// use futures::future::BoxFuture;
+        ///
+        /// static VALUE: AsyncLazy<u8, FromFn<fn() -> BoxFuture<'static, u8>>> = AsyncLazy::from_fn(|| Box::pin(async { 2 }));
+        /// assert_eq!(*futures::executor::block_on(VALUE.get()), 2);
+        /// ```
+        #[inline(always)]
+        pub const fn from_fn (f: C) -> Self {
+            Self::new(FromFn { f })
+        }
+    }
+}
+
+/// Initializer of an [`AsyncLazy`] that builds its future when the value is first needed. Built by [`AsyncLazy::from_fn`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FromFn<C> {
+    f: C
+}
+
+impl<C: FnOnce() -> Fut, Fut: IntoFuture> IntoFuture for FromFn<C> {
+    type Output = Fut::Output;
+    type IntoFuture = Fut::IntoFuture;
 
-    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
     #[inline(always)]
-    pub fn try_get (&self) -> Option<&T> {
-        match self.state.load(Ordering::Acquire) {
-            INIT => unsafe { Some((&*self.value.get()).assume_init_ref()) }
-            _ => None
+    fn into_future(self) -> Self::IntoFuture {
+        (self.f)().into_future()
+    }
+}
+
+/// An [`AsyncLazy`] whose initialization future is built from an argument stored in the cell (see [`AsyncLazy::with_arg`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub type AsyncLazyWith<T, A, C> = AsyncLazy<T, With<A, C>>;
+
+impl<T, A, C> AsyncLazy<T, With<A, C>> {
+    loom_const! {
+        /// Builds a new ```AsyncLazy``` value whose initialization future is built by calling `f` with `arg` when it's first needed
+        ///
+        /// ```rust
+        /// use laizy::AsyncLazy;
+        ///
+        /// let cell = AsyncLazy::with_arg(String::from("config"), |config: String| async move { config.len() });
+        /// assert_eq!(*futures::executor::block_on(cell.get()), 6);
+        /// ```
+        #[inline(always)]
+        pub const fn with_arg (arg: A, f: C) -> Self {
+            Self::new(With { arg, f })
         }
     }
 
-    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    /// Returns the argument and the function building the initialization future if it hasn't been built yet, or the
+    /// cell otherwise
+    #[inline]
+    pub fn into_parts (mut self) -> Result<(A, C), Self> {
+        match self.take_initializer() {
+            Some(With { arg, f }) => Ok((arg, f)),
+            None => Err(self)
+        }
+    }
+}
+
+/// Initializer of an [`AsyncLazy`] that builds its future from a stored argument when the value is first needed.
+/// Built by [`AsyncLazy::with_arg`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct With<A, C> {
+    arg: A,
+    f: C
+}
+
+impl<A, C: FnOnce(A) -> Fut, Fut: Future> IntoFuture for With<A, C> {
+    type Output = Fut::Output;
+    type IntoFuture = Fut;
+
     #[inline(always)]
-    pub fn try_get_mut (&mut self) -> Option<&mut T> {
-        match self.state.load(Ordering::Acquire) {
-            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) }
-            _ => None
+    fn into_future(self) -> Self::IntoFuture {
+        (self.f)(self.arg)
+    }
+}
+
+/// Awaiting a reference to the cell is the same as awaiting [`get`](AsyncLazy::get)
+///
+/// ```rust
+/// use laizy::{AsyncLazy, FromFn};
+/// use futures::future::BoxFuture;
+///
+/// static CELL: AsyncLazy<u8, FromFn<fn() -> BoxFuture<'static, u8>>> = AsyncLazy::from_fn(|| Box::pin(async { 1 }));
+///
+/// #[tokio::main]
+/// async fn main () {
+///     assert_eq!(*(&CELL).await, 1);
+/// }
+/// ```
+///
+/// ```rust
+/// use laizy::AsyncLazy;
+///
+/// let cell = AsyncLazy::new(async { 2u8 });
+/// assert_eq!(*futures::executor::block_on(async { (&cell).await }), 2);
+/// ```
+impl<'a, T, F: IntoFuture<Output = T>> IntoFuture for &'a AsyncLazy<T, F> {
+    type Output = &'a T;
+    type IntoFuture = Get<'a, T, F>;
+
+    #[inline(always)]
+    fn into_future(self) -> Self::IntoFuture {
+        self.get()
+    }
+}
+
+/// Awaiting a mutable reference to the cell is the same as awaiting [`get_mut`](AsyncLazy::get_mut)
+///
+/// ```rust
+/// use laizy::AsyncLazy;
+///
+/// let mut cell = AsyncLazy::new(async { 2u8 });
+/// futures::executor::block_on(async {
+///     *(&mut cell).await += 1;
+///     assert_eq!(*(&cell).await, 3);
+/// });
+/// ```
+impl<'a, T, F: IntoFuture<Output = T>> IntoFuture for &'a mut AsyncLazy<T, F> {
+    type Output = &'a mut T;
+    type IntoFuture = GetMut<'a, T, F>;
+
+    #[inline(always)]
+    fn into_future(self) -> Self::IntoFuture {
+        self.get_mut()
+    }
+}
+
+/// Initializer of an [`AsyncLazy`] that initializes it with ```T::default()```.
+///
+/// ```AsyncLazy<T, DefaultInit<T>>``` implements [`Default`] by initializing the value right away, so that structs holding
+/// one can derive it without naming a future type
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct DefaultInit<T> {
+    _value: PhantomData<fn() -> T>
+}
+
+impl<T> DefaultInit<T> {
+    /// Builds a new initializer, to build a cell that's initialized with ```T::default()``` when first needed
+    // implementing `Default` would overlap the `Default` impls of `AsyncLazy`
+    #[allow(clippy::new_without_default)]
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { _value: PhantomData }
+    }
+}
+
+impl<T: Default> IntoFuture for DefaultInit<T> {
+    type Output = T;
+    type IntoFuture = core::future::Ready<T>;
+
+    #[inline(always)]
+    fn into_future(self) -> Self::IntoFuture {
+        core::future::ready(T::default())
+    }
+}
+
+impl<T> Clone for DefaultInit<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for DefaultInit<T> {}
+
+impl<T> core::fmt::Debug for DefaultInit<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("DefaultInit")
+    }
+}
+
+impl<T, F: Default> Default for AsyncLazy<T, F> {
+    /// Builds an uninitialized cell, with ```F::default()``` as its initializer
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(F::default())
+    }
+}
+
+// `DefaultInit` doesn't implement `Default`, so this doesn't overlap with the impl above
+impl<T: Default> Default for AsyncLazy<T, DefaultInit<T>> {
+    /// Builds a cell that's already initialized with ```T::default()```
+    #[inline(always)]
+    fn default() -> Self {
+        Self::init(T::default())
+    }
+}
+
+impl<T: Clone, F: Clone> Clone for AsyncLazy<T, F> {
+    /// Clones the value if the cell is initialized. Otherwise, if the initialization future is still stored in the cell
+    /// (see [`initializer`](AsyncLazy::initializer)), the clone gets its own copy of it (as is, even if it was left partially
+    /// polled by [`poll_get`](AsyncLazy::poll_get)), and initializes independently. Poisoned, cancelled and taken cells are cloned as such.
+    ///
+    /// Any number of threads can clone the same cell at once, and cloning it never counts as initializing it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell is being initialized, since the future has been moved out of the cell by then (even if a dropped
+    /// [`get`](AsyncLazy::get) future handed it back), and the value can't be waited for synchronously
+    fn clone(&self) -> Self {
+        loop {
+            match self.state.load(Ordering::Acquire) {
+                INIT => {
+                    self.track.read();
+                    return Self::init(unsafe { (*self.value.get()).assume_init_ref() }.clone())
+                },
+
+                // announce that we're reading the future, so that whoever takes it over afterwards (see `wait_for_clones`)
+                // waits for us to be done with it, and check it's still there once we have
+                UNINIT | PARKED => {
+                    self.clones.fetch_add(1, Ordering::Relaxed);
+                    let release = Release(&self.clones);
+                    crate::sync::fence(Ordering::SeqCst);
+                    if let UNINIT | PARKED = self.state.load(Ordering::Acquire) {
+                        self.track.read();
+                        return Self::new(unsafe { (*self.f.get()).assume_init_ref() }.clone())
+                    }
+                    // taken over in the meantime
+                    drop(release)
+                },
+
+                INITIALIZING | SUSPENDED => panic!("AsyncLazy cloned while initializing"),
+
+                // poisoned, cancelled (or aborted) or taken
+                state => return Self {
+                    state: AtomicU8::new(if state == ABORTING { CANCELLED } else { state }),
+                    waiters: Waiters::new(),
+                    clones: AtomicUsize::new(0),
+                    #[cfg(feature = "std")]
+                    started: AtomicU64::new(0),
+                    value: UnsafeCell::new(MaybeUninit::uninit()),
+                    f: UnsafeCell::new(MaybeUninit::uninit()),
+                    #[cfg(feature = "alloc")]
+                    suspended: UnsafeCell::new(None),
+                    track: Track::new()
+                }
+            }
         }
     }
+}
 
-    /// Returns the inner value, initializing it if necessary
+/// Stops counting a [`clone`](AsyncLazy::clone) as a reader of the initialization future (even if cloning it panicked)
+struct Release<'a>(&'a AtomicUsize);
+
+impl Drop for Release<'_> {
     #[inline(always)]
-    pub async fn into_inner (self) -> T {
-        let mut this = ManuallyDrop::new(self);
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+}
 
-        match this.state.load(Ordering::Relaxed) {
-            // uninit (init value)
-            UNINIT => unsafe { 
-                let f = core::mem::replace(this.f.get_mut(), MaybeUninit::uninit()).assume_init();
-                f.await
-            },
+/// Restores the state of a cell whose future was locked by an [`AsyncInitGuard`], waking
+/// anyone who started waiting on it in the meantime
+struct Unlock<'a, T, F> {
+    lazy: &'a AsyncLazy<T, F>,
+    prev: u8
+}
 
-            // currently initializing
-            INITIALIZING => unsafe {
-                AwaitInit::new(INIT, &this.state, &this.waker).await;
-                let value = core::mem::replace(this.value.get_mut(), MaybeUninit::uninit());
-                value.assume_init()
-            },
+impl<T, F> Drop for Unlock<'_, T, F> {
+    #[inline]
+    fn drop(&mut self) {
+        let lazy = self.lazy;
+        if lazy.state.compare_exchange(INITIALIZING, self.prev, Ordering::Release, Ordering::Relaxed).is_err() {
+            // aborted in the meantime
+            let guard = AsyncPoisonGuard::new(&lazy.state, &lazy.waiters);
+            lazy.track.write();
+            unsafe { core::ptr::drop_in_place((*lazy.f.get()).as_mut_ptr()) }
+            drop(guard);
+            return
+        }
 
-            // init
-            _ => unsafe {
-                let value = core::mem::replace(this.value.get_mut(), MaybeUninit::uninit());
-                value.assume_init()
+        DefaultStrategy::notify(&lazy.state);
+        lazy.waiters.wake_all();
+    }
+}
+
+impl<T: core::fmt::Debug, F> core::fmt::Debug for AsyncLazy<T, F> {
+    /// Prints the value if it's initialized, or the state of the cell otherwise (without ever polling the future)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("AsyncLazy");
+        match self.try_get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&self.state())
+        };
+        tuple.finish()
+    }
+}
+
+/// Placeholder formatted instead of the value of a cell that isn't initialized, matching its [`State`]
+#[inline]
+fn placeholder (state: State) -> &'static str {
+    match state {
+        State::Uninit => "<uninitialized>",
+        // it was still initializing when its value was looked for
+        State::Initializing | State::Initialized => "<initializing>",
+        State::Poisoned => "<poisoned>",
+        State::Cancelled => "<cancelled>",
+        State::Taken => "<taken>"
+    }
+}
+
+macro_rules! impl_fmt {
+    ($($fmt:ident),+) => {
+        $(
+            /// Formats the value if it's initialized, without ever polling the future. Otherwise, one of these fixed
+            /// placeholders is formatted instead (padded like a string), depending on the [`State`] of the cell:
+            /// ```<uninitialized>```, ```<initializing>```, ```<poisoned>```, ```<cancelled>``` or ```<taken>```
+            impl<T: core::fmt::$fmt, F> core::fmt::$fmt for AsyncLazy<T, F> {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    match self.try_get() {
+                        Some(value) => core::fmt::$fmt::fmt(value, f),
+                        None => f.pad(placeholder(self.state()))
+                    }
+                }
             }
+        )+
+    };
+}
+
+impl_fmt!(Display, LowerHex, UpperHex, Octal, Binary);
+
+/// Compares the values of both cells, without ever polling their futures: two cells are equal only if both are
+/// initialized, and their values are. An uninitialized cell isn't equal to any cell (itself included), which is why
+/// there's no [`Eq`] implementation
+impl<T: PartialEq, F, G> PartialEq<AsyncLazy<T, G>> for AsyncLazy<T, F> {
+    #[inline]
+    fn eq(&self, other: &AsyncLazy<T, G>) -> bool {
+        match (self.try_get(), other.try_get()) {
+            (Some(this), Some(other)) => this == other,
+            _ => false
         }
     }
 }
 
+/// Compares the value of the cell to `other`, without ever polling its future: an uninitialized cell isn't equal to any value
+impl<T: PartialEq, F> PartialEq<T> for AsyncLazy<T, F> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.try_get().is_some_and(|this| this == other)
+    }
+}
+
 impl<T, F> From<T> for AsyncLazy<T, F> {
     #[inline(always)]
     fn from(x: T) -> Self {
@@ -182,36 +1433,52 @@ impl<T, F> From<T> for AsyncLazy<T, F> {
 impl<T, F> Drop for AsyncLazy<T, F> {
     #[inline(always)]
     fn drop(&mut self) {
-        match self.state.load(Ordering::Relaxed) {
-            // uninit (drop future)
-            UNINIT => return unsafe { self.f.get_mut().assume_init_drop() },
-
-            // currently initializing
-            INITIALIZING => while self.state.load(Ordering::Acquire) == INITIALIZING { core::hint::spin_loop() },
+        // `&mut self` means no `get` future can borrow the cell anymore, and whatever gave us exclusive access
+        // already synchronizes with the end of the initialization, so the state can be read non-atomically
+        self.track.write();
+        match *self.state.get_mut() {
+            // uninit, or left partially polled by `poll_get` (drop future)
+            UNINIT | PARKED => unsafe { self.f.get_mut().assume_init_drop() },
 
             // init (drop value)
+            INIT => unsafe { self.value.get_mut().assume_init_drop() },
+
+            // handed back by a dropped `get` future (dropped along with `suspended`),
+            // taken (nothing to drop, the value was moved out),
+            // poisoned or cancelled (nothing to drop, the future was dropped by the `get` future polling it), or still initializing
+            // (the `get` future that owned the initializer was leaked instead of dropped, taking it along)
             _ => {}
         }
-
-        unsafe { self.value.get_mut().assume_init_drop() }
     }
 }
 
-unsafe impl<T: Send, F: Send> Send for AsyncLazy<T, F> {}
-unsafe impl<T: Sync, F: Sync> Sync for AsyncLazy<T, F> {}
+// The cell never polls `F` in place unless it's `Unpin` (`poll_get`) or the cell is borrowed forever (`get_in_place`):
+// `get`, `get_mut` and `into_inner` move it out of the cell
+// first, and poll it inside of their own (pinned) future, which is only ever handed back to the cell boxed. So moving the
+// cell around, even after `F` started running, can't move a pinned `F`
+impl<T, F> Unpin for AsyncLazy<T, F> {}
+
+// Sharing an `AsyncLazy` allows any task to move `F` out of the cell and poll it (possibly hopping threads with
+// the `get` future), and to hand out `&T`, so `F` must be `Send` and `T` both `Send` and `Sync`. A dropped `get` future
+// may also hand its (converted) future back to the cell, for another task (or the new owner of the cell) to resume
+// it, so that future must be `Send` as well
+unsafe impl<T: Send, F: IntoFuture + Send> Send for AsyncLazy<T, F> where F::IntoFuture: Send {}
+unsafe impl<T: Send + Sync, F: IntoFuture + Send> Sync for AsyncLazy<T, F> where F::IntoFuture: Send {}
 
-/// Creates a new ```AsyncLazy``` without having to specify the future's return type
+/// Creates a new ```AsyncLazy``` without having to specify the future's return type. `f` is only converted into a
+/// future once the value is first needed
 #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
 #[cfg(feature = "nightly")]
 #[inline(always)]
-pub const fn async_lazy<F: Future> (f: F) -> AsyncLazy<F::Output, F> {
+pub const fn async_lazy<F: IntoFuture> (f: F) -> AsyncLazy<F::Output, F> {
     AsyncLazy::new(f)
 }
 
-/// Creates a new ```AsyncLazy``` without having to specify the future's return type
+/// Creates a new ```AsyncLazy``` without having to specify the future's return type. `f` is only converted into a
+/// future once the value is first needed
 #[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
 #[cfg(not(feature = "nightly"))]
 #[inline(always)]
-pub fn async_lazy<F: Future> (f: F) -> AsyncLazy<F::Output, F> {
+pub fn async_lazy<F: IntoFuture> (f: F) -> AsyncLazy<F::Output, F> {
     AsyncLazy::new(f)
 }
@@ -0,0 +1,62 @@
+use core::{future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}};
+use crate::cancelled;
+use super::AsyncLazy;
+
+/// Initializer of an [`AsyncLazy`] that runs a blocking closure on tokio's blocking thread pool (see [`AsyncLazy::new_blocking`])
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Blocking<G> {
+    g: G
+}
+
+/// Future of a [`Blocking`] initializer
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct SpawnBlocking<T> {
+    handle: tokio::task::JoinHandle<T>
+}
+
+impl<T, G> AsyncLazy<T, Blocking<G>> {
+    loom_const! {
+        /// Builds a new ```AsyncLazy``` value initialized by the blocking closure `g`, which runs on tokio's blocking thread
+        /// pool (see [```spawn_blocking```](tokio::task::spawn_blocking)), so that awaiting the value doesn't block the runtime.
+        ///
+        /// If `g` panics, the panic is resumed by the task running the initialization, so the cell is poisoned. If the
+        /// blocking task is cancelled instead (because the runtime is shutting down), so is the cell
+        ///
+        /// # Panics
+        ///
+        /// The initialization panics if it's started outside of a tokio runtime
+        #[inline(always)]
+        pub const fn new_blocking (g: G) -> Self {
+            Self::new(Blocking { g })
+        }
+    }
+}
+
+impl<T: 'static + Send, G: 'static + Send + FnOnce() -> T> IntoFuture for Blocking<G> {
+    type Output = T;
+    type IntoFuture = SpawnBlocking<T>;
+
+    #[inline(always)]
+    fn into_future(self) -> Self::IntoFuture {
+        SpawnBlocking { handle: tokio::task::spawn_blocking(self.g) }
+    }
+}
+
+impl<T> Future for SpawnBlocking<T> {
+    type Output = T;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Ready(Ok(value)) => Poll::Ready(value),
+            Poll::Ready(Err(e)) => match e.try_into_panic() {
+                Ok(payload) => std::panic::resume_unwind(payload),
+                Err(_) => cancelled()
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
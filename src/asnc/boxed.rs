@@ -0,0 +1,65 @@
+//! Async lazy values with type-erased initialization futures
+
+use core::{future::Future, pin::Pin};
+use alloc::boxed::Box;
+use super::{AsyncLazy, FromFn};
+
+/// An [`AsyncLazy`] whose initialization future is boxed (the same type as ```futures::future::BoxFuture<'static, T>```),
+/// so that its type can be written down (e.g. in a struct field) even when it's an ```async``` block. Built by
+/// [`AsyncLazy::boxed`]
+///
+/// Boxing isn't ```const```, so statics use [`BoxedAsyncLazyFn`] instead, which boxes the future once it's first needed
+#[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+pub type BoxedAsyncLazy<T> = AsyncLazy<T, Pin<Box<dyn Future<Output = T> + Send>>>;
+
+/// Like [`BoxedAsyncLazy`], but its initialization future needn't be ```Send``` (the same type as
+/// ```futures::future::LocalBoxFuture<'static, T>```), so it's neither ```Send``` nor ```Sync```. Built by
+/// [`AsyncLazy::boxed_local`]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+pub type LocalBoxedAsyncLazy<T> = AsyncLazy<T, Pin<Box<dyn Future<Output = T>>>>;
+
+/// A [`BoxedAsyncLazy`] that can be built in ```const``` contexts (and so, in statics, on stable), by boxing its future
+/// once it's first needed
+///
+/// ```rust
+/// use laizy::{AsyncLazy, BoxedAsyncLazyFn};
+///
+/// static PORT: BoxedAsyncLazyFn<u16> = AsyncLazy::from_fn(|| Box::pin(async { 8080 }));
+/// # futures::executor::block_on(async {
+/// assert_eq!(PORT.get().await, &8080);
+/// # })
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+pub type BoxedAsyncLazyFn<T> = AsyncLazy<T, FromFn<fn() -> Pin<Box<dyn Future<Output = T> + Send>>>>;
+
+impl<T> AsyncLazy<T, Pin<Box<dyn Future<Output = T> + Send>>> {
+    /// Builds a new ```BoxedAsyncLazy``` value
+    ///
+    /// ```rust
+    /// use laizy::{AsyncLazy, BoxedAsyncLazy};
+    ///
+    /// struct Client {
+    ///     token: BoxedAsyncLazy<String>
+    /// }
+    ///
+    /// let user = String::from("laizy");
+    /// let client = Client { token: AsyncLazy::boxed(async move { format!("token-{user}") }) };
+    /// # futures::executor::block_on(async {
+    /// assert_eq!(client.token.get().await, "token-laizy");
+    /// # })
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+    #[inline(always)]
+    pub fn boxed (fut: impl Future<Output = T> + Send + 'static) -> Self {
+        Self::new(Box::pin(fut))
+    }
+}
+
+impl<T> AsyncLazy<T, Pin<Box<dyn Future<Output = T>>>> {
+    /// Builds a new ```LocalBoxedAsyncLazy``` value
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+    #[inline(always)]
+    pub fn boxed_local (fut: impl Future<Output = T> + 'static) -> Self {
+        Self::new(Box::pin(fut))
+    }
+}
@@ -0,0 +1,198 @@
+use core::{cell::UnsafeCell, future::IntoFuture, mem::MaybeUninit, sync::atomic::Ordering, task::Poll};
+use crate::sync::{AtomicU8, AtomicUsize};
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, STALE, poisoned, utils::{AsyncPoisonGuard, AwaitInit, Waiters}};
+
+/// An async value that's computed once, and computed again every time it's invalidated.
+///
+/// Every valid value is computed exactly once: tasks asking for it while it's being computed wait for that computation
+/// (instead of starting their own), and a computation that was invalidated before it finished is started over. Since
+/// the value may be replaced through a shared reference, it's handed out by cloning it (wrap it in an ```Arc``` to share it instead)
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct AsyncCached<T, C> {
+    state: AtomicU8,
+    /// Bumped by every invalidation
+    generation: AtomicUsize,
+    /// Tasks currently cloning the value
+    readers: AtomicUsize,
+    waiters: Waiters,
+    value: UnsafeCell<MaybeUninit<T>>,
+    make: UnsafeCell<C>
+}
+
+impl<T, C> AsyncCached<T, C> {
+    loom_const! {
+        /// Builds a new ```AsyncCached``` value, whose value is computed by the futures built by `make`
+        #[inline(always)]
+        pub const fn new (make: C) -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                generation: AtomicUsize::new(0),
+                readers: AtomicUsize::new(0),
+                waiters: Waiters::new(),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                make: UnsafeCell::new(make)
+            }
+        }
+    }
+
+    /// Returns ```true``` if the value is computed, and hasn't been invalidated since, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_valid (&self) -> bool {
+        self.state.load(Ordering::Acquire) == INIT
+    }
+
+    /// Returns ```true``` if the last computation panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state.load(Ordering::Acquire) == POISONED
+    }
+
+    /// Returns a clone of the value if it's valid, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<T> where T: Clone {
+        self.try_get_or_expire(|_| false)
+    }
+
+    /// Returns a clone of the value if it's valid, or invalidates it (without restarting a computation in flight) if it's `expired`
+    pub(crate) fn try_get_or_expire (&self, expired: impl FnOnce(&T) -> bool) -> Option<T> where T: Clone {
+        // readers that come after the value was invalidated don't hold back whoever replaces it
+        if self.state.load(Ordering::Acquire) != INIT {
+            return None
+        }
+
+        // `SeqCst` (here and in `compute`) orders the increment before the state is read back, so that whoever
+        // replaces the value afterwards either sees us and waits for us to finish with it, or is seen by us
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let value = match self.state.load(Ordering::SeqCst) {
+            INIT => unsafe {
+                let value = (*self.value.get()).assume_init_ref();
+                match expired(value) {
+                    true => {
+                        let _ = self.state.compare_exchange(INIT, STALE, Ordering::SeqCst, Ordering::Relaxed);
+                        None
+                    },
+                    false => Some(value.clone())
+                }
+            },
+            _ => None
+        };
+        self.readers.fetch_sub(1, Ordering::Release);
+        value
+    }
+
+    /// Invalidates the value, dropping it, so that the next access computes it again. Also clears the poisoning of a
+    /// computation that panicked
+    pub fn invalidate (&mut self) {
+        let prev = core::mem::replace(&mut *self.state.get_mut(), UNINIT);
+        *self.generation.get_mut() += 1;
+
+        if let INIT | STALE = prev {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+        self.waiters.clear();
+    }
+
+    /// Invalidates the value through a shared reference, so that the next access computes it again. The old value is
+    /// dropped once that computation starts. If the value is being computed, the computation is started over once it finishes
+    #[inline]
+    pub fn invalidate_shared (&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        let _ = self.state.compare_exchange(INIT, STALE, Ordering::SeqCst, Ordering::Relaxed);
+    }
+}
+
+impl<T: Clone, C: FnMut() -> F, F: IntoFuture<Output = T>> AsyncCached<T, C> {
+    /// Returns a clone of the value, computing it (or waiting for it to be computed) if it isn't valid
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computation panics (or panicked before), poisoning the value until it's [invalidated](AsyncCached::invalidate)
+    #[inline(always)]
+    pub async fn get (&self) -> T {
+        self.get_with(|make| make()).await
+    }
+}
+
+impl<T: Clone, C> AsyncCached<T, C> {
+    /// Like [`get`](AsyncCached::get), but builds the futures computing the value with `make`, given the stored closure
+    pub(crate) async fn get_with<F: IntoFuture<Output = T>> (&self, mut make: impl FnMut(&mut C) -> F) -> T {
+        loop {
+            if let Some(value) = self.try_get() {
+                return value
+            }
+
+            match self.state.load(Ordering::Acquire) {
+                state @ (UNINIT | STALE) if self.state.compare_exchange(state, INITIALIZING, Ordering::SeqCst, Ordering::Relaxed).is_ok() => {
+                    unsafe { self.compute(state == STALE, &mut make).await }
+                },
+
+                // wait for it, and check again
+                INITIALIZING => { AwaitInit::new(&self.state, &self.waiters).await; },
+                POISONED => poisoned(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Computes the value. The state must have been set to ```INITIALIZING``` by the caller (from ```STALE``` if `stale`)
+    async unsafe fn compute<F: IntoFuture<Output = T>> (&self, stale: bool, make: &mut impl FnMut(&mut C) -> F) {
+        if stale {
+            // if cancelled while waiting, the stale value is handed back to whoever computes it next
+            struct Restale<'a>(&'a AtomicU8, &'a Waiters);
+            impl Drop for Restale<'_> {
+                #[inline(always)]
+                fn drop(&mut self) {
+                    self.0.store(STALE, Ordering::Release);
+                    self.1.wake_all();
+                }
+            }
+
+            // nobody else can make the value valid again, so new readers won't touch it (nor count themselves), and
+            // the ones still cloning it are done shortly. Yields to the executor meanwhile, instead of blocking its thread
+            let restale = Restale(&self.state, &self.waiters);
+            core::future::poll_fn(|cx| match self.readers.load(Ordering::SeqCst) {
+                0 => Poll::Ready(()),
+                _ => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }).await;
+            core::mem::forget(restale);
+            (*self.value.get()).assume_init_drop();
+        }
+
+        // if cancelled, someone else can compute it. If panicking, the value is poisoned
+        let guard = AsyncPoisonGuard::retrying(&self.state, &self.waiters);
+        let value = loop {
+            let generation = self.generation.load(Ordering::Acquire);
+            let value = make(&mut *self.make.get()).await;
+            if self.generation.load(Ordering::Acquire) == generation {
+                break value
+            }
+        };
+
+        guard.disarm();
+        self.value.get().write(MaybeUninit::new(value));
+        self.state.store(INIT, Ordering::Release);
+        self.waiters.wake_all();
+    }
+}
+
+impl<T: core::fmt::Debug, C> core::fmt::Debug for AsyncCached<T, C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncCached").field("valid", &self.is_valid()).finish_non_exhaustive()
+    }
+}
+
+impl<T, C> Drop for AsyncCached<T, C> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if let INIT | STALE = *self.state.get_mut() {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+// Sharing it lets any task call `make` and poll its futures (so they must be `Send`), and clone the value
+unsafe impl<T: Send, C: Send> Send for AsyncCached<T, C> {}
+unsafe impl<T: Send + Sync, C: Send> Sync for AsyncCached<T, C> {}
@@ -0,0 +1,382 @@
+use core::{future::{Future, IntoFuture}, marker::PhantomData, mem::MaybeUninit, pin::Pin, sync::atomic::Ordering};
+use core::task::{Context, Poll};
+use futures_core::FusedFuture;
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, CANCELLED, PARKED, TAKEN, ABORTING, SUSPENDED, poisoned, cancelled, taken, abandoned, utils::{AwaitInit, AsyncPoisonGuard}};
+use super::{AsyncLazy, settled};
+
+#[cfg(feature = "alloc")]
+use {core::ptr::NonNull, alloc::boxed::Box};
+#[cfg(not(debug_assertions))]
+use core::hint::unreachable_unchecked;
+
+/// Future returned by [`AsyncLazy::get`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Get<'a, T, F: IntoFuture> {
+    lazy: &'a AsyncLazy<T, F>,
+    step: Step<'a, F::IntoFuture>,
+    // declared after `step`, so that the initialization future is dropped before the cell is poisoned or cancelled
+    guard: Option<AsyncPoisonGuard<'a>>
+}
+
+/// Future returned by [`AsyncLazy::get_mut`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GetMut<'a, T, F: IntoFuture> {
+    inner: Get<'a, T, F>,
+    _mut: PhantomData<&'a mut AsyncLazy<T, F>>
+}
+
+enum Step<'a, Fut> {
+    /// Not polled yet
+    Start,
+    /// Polling the initialization future
+    Initializing(Driven<Fut>),
+    /// Waiting for another task's initialization
+    Waiting(AwaitInit<'a>),
+    /// Resolved
+    Done
+}
+
+/// Initialization future, as polled by a [`Get`]. With ```alloc```, it's boxed, so that it can be handed back to the cell
+/// (without moving the future itself) if the [`Get`] is dropped before it finishes
+#[cfg(feature = "alloc")]
+type Driven<Fut> = Pin<Box<Fut>>;
+#[cfg(not(feature = "alloc"))]
+type Driven<Fut> = Fut;
+
+#[inline(always)]
+fn drive<Fut> (f: Fut) -> Driven<Fut> {
+    #[cfg(feature = "alloc")]
+    return Box::pin(f);
+    #[cfg(not(feature = "alloc"))]
+    return f;
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> Get<'a, T, F> {
+    #[inline(always)]
+    pub(super) const fn new (lazy: &'a AsyncLazy<T, F>) -> Self {
+        Self { lazy, step: Step::Start, guard: None }
+    }
+
+    /// Drives the initialization, resolving once the value is initialized
+    #[inline]
+    fn poll_init (self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // `step` is never moved out of while pinned, only dropped in place by assignment
+        let this = unsafe { self.get_unchecked_mut() };
+        let lazy = this.lazy;
+
+        loop {
+            match &mut this.step {
+                Step::Start => match lazy.state.load(Ordering::Acquire) {
+                    INIT => break,
+                    _ => match this.start() {
+                        Some(step) => this.step = step,
+                        None => break
+                    }
+                },
+
+                Step::Initializing(f) => unsafe {
+                    if lazy.state.load(Ordering::Relaxed) == ABORTING {
+                        this.abort()
+                    }
+
+                    // if `f` panics, poison the cell right away, since a caught panic may drop this future much later
+                    let unwinding = Unwinding(&mut this.guard);
+                    let poll = Pin::new_unchecked(f).poll(cx);
+                    core::mem::forget(unwinding);
+
+                    let value = match poll {
+                        Poll::Ready(value) => value,
+                        Poll::Pending => {
+                            // so that `abort` can wake us up, in case `f` never does
+                            lazy.waiters.register_driver(cx.waker());
+                            match lazy.state.load(Ordering::Relaxed) {
+                                ABORTING => this.abort(),
+                                _ => return Poll::Pending
+                            }
+                        }
+                    };
+
+                    this.step = Step::Done;
+                    lazy.track.write();
+                    lazy.value.get().write(MaybeUninit::new(value));
+                    if let Some(guard) = this.guard.take() {
+                        guard.disarm()
+                    }
+
+                    if let Err(value) = lazy.complete() {
+                        drop(value);
+                        cancelled()
+                    }
+                    return Poll::Ready(())
+                },
+
+                Step::Waiting(waiting) => match unsafe { Pin::new_unchecked(waiting) }.poll(cx) {
+                    // the task initializing it left it for someone else to drive (through `poll_get`, or by dropping
+                    // its `get` future), or it was just locked to be cloned
+                    Poll::Ready(UNINIT | PARKED | SUSPENDED) => this.step = Step::Start,
+                    Poll::Ready(state) => {
+                        this.step = Step::Done;
+                        settled(state);
+                        return Poll::Ready(())
+                    },
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Done => panic!("`Get` polled after completion")
+            }
+        }
+
+        this.step = Step::Done;
+        Poll::Ready(())
+    }
+
+    /// Slow path of the first poll: starts the initialization, or waits for it to be initialized.
+    /// Returns ```None``` if it's already initialized
+    #[cold]
+    #[inline(never)]
+    fn start (&mut self) -> Option<Step<'a, F::IntoFuture>> {
+        let lazy = self.lazy;
+        let mut current = UNINIT;
+
+        loop {
+            match lazy.state.compare_exchange(current, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                // handed back, partially polled, by a dropped `get` future
+                #[cfg(feature = "alloc")]
+                Ok(SUSPENDED) => unsafe {
+                    self.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                    return Some(Step::Initializing(lazy.resume()))
+                },
+
+                // uninitialized, or left partially polled by `poll_get`. In the latter case, the future is `Unpin`, so it
+                // can be moved out of the cell, and converting it into a future does nothing, so it's resumed
+                Ok(_) => unsafe {
+                    lazy.wait_for_clones();
+                    lazy.mark_started(current);
+                    self.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                    lazy.track.write();
+                    let f = core::ptr::read(lazy.f.get()).assume_init();
+                    return Some(Step::Initializing(drive(f.into_future())))
+                },
+
+                // we tried to take over from the wrong state
+                Err(state @ (UNINIT | PARKED | SUSPENDED)) => current = state,
+
+                // currently initializing
+                Err(INITIALIZING) => return Some(Step::Waiting(AwaitInit::new(&lazy.state, &lazy.waiters))),
+
+                // initialized
+                Err(INIT) => return None,
+
+                // initialization panicked
+                Err(POISONED) => poisoned(),
+
+                // initialization was cancelled (or aborted)
+                Err(CANCELLED | ABORTING) => cancelled(),
+
+                // value was taken
+                Err(TAKEN) => taken(),
+
+                #[cfg(debug_assertions)]
+                _ => unreachable!(),
+                #[cfg(not(debug_assertions))]
+                _ => unsafe { unreachable_unchecked() }
+            }
+        }
+    }
+
+    /// Drops the initialization future and cancels the cell, after it was aborted
+    #[cold]
+    #[inline(never)]
+    fn abort (&mut self) -> ! {
+        self.step = Step::Done;
+        drop(self.guard.take());
+        cancelled()
+    }
+
+    /// Like [`start`](Get::start), for a cell that's borrowed mutably: nobody else can be initializing it, so the state
+    /// only needs ```Relaxed``` accesses, and there's never anyone to wait for. The cell must not be initialized yet.
+    ///
+    /// # Safety
+    /// The cell must be borrowed mutably for as long as this future lives
+    #[cold]
+    #[inline(never)]
+    unsafe fn start_exclusive (&mut self) -> Step<'a, F::IntoFuture> {
+        let lazy = self.lazy;
+
+        match lazy.state.load(Ordering::Relaxed) {
+            // uninitialized, or left partially polled by `poll_get` (see `start`)
+            UNINIT | PARKED => {
+                lazy.state.store(INITIALIZING, Ordering::Relaxed);
+                self.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                let f = core::ptr::read(lazy.f.get()).assume_init();
+                Step::Initializing(drive(f.into_future()))
+            },
+
+            // handed back by a dropped `get` future
+            #[cfg(feature = "alloc")]
+            SUSPENDED => {
+                lazy.state.store(INITIALIZING, Ordering::Relaxed);
+                self.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                Step::Initializing(lazy.resume())
+            },
+
+            // nothing will ever finish the initialization (the initializer was taken, or its `get` future leaked)
+            INITIALIZING => abandoned(),
+            POISONED => poisoned(),
+            CANCELLED | ABORTING => cancelled(),
+            TAKEN => taken(),
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unreachable_unchecked()
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F: IntoFuture> Drop for Get<'_, T, F> {
+    #[inline]
+    fn drop(&mut self) {
+        // dropped mid-initialization (without panicking): hand the future back to the cell, instead of cancelling it
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            return
+        }
+
+        if let (Step::Initializing(_), Some(guard)) = (&self.step, self.guard.take()) {
+            if let Step::Initializing(f) = core::mem::replace(&mut self.step, Step::Done) {
+                self.lazy.suspend(f, guard)
+            }
+        }
+    }
+}
+
+/// Initialization future handed back to an [`AsyncLazy`] by a [`Get`] dropped before it finished, boxed and type-erased (the
+/// cell may not know the type of the future its initializer converts into)
+#[cfg(feature = "alloc")]
+pub(super) struct Suspended {
+    f: NonNull<()>,
+    drop: unsafe fn(NonNull<()>)
+}
+
+#[cfg(feature = "alloc")]
+impl Suspended {
+    #[inline]
+    pub(super) fn new<Fut> (f: Pin<Box<Fut>>) -> Self {
+        unsafe fn drop_boxed<Fut> (f: NonNull<()>) {
+            drop(Box::from_raw(f.as_ptr().cast::<Fut>()))
+        }
+
+        // the future is never moved out of the box, only dropped (or polled) in it
+        let f = Box::into_raw(unsafe { Pin::into_inner_unchecked(f) });
+        Self { f: unsafe { NonNull::new_unchecked(f) }.cast(), drop: drop_boxed::<Fut> }
+    }
+
+    /// # Safety
+    /// `Fut` must be the type of the future it was built from
+    #[inline]
+    pub(super) unsafe fn into_box<Fut> (self) -> Pin<Box<Fut>> {
+        let this = core::mem::ManuallyDrop::new(self);
+        Pin::new_unchecked(Box::from_raw(this.f.as_ptr().cast::<Fut>()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Drop for Suspended {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.f) }
+    }
+}
+
+/// Drops the poison guard it borrows if dropped (only happens while unwinding)
+pub(super) struct Unwinding<'b, G>(pub(super) &'b mut Option<G>);
+
+impl<G> Drop for Unwinding<'_, G> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        drop(self.0.take())
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> Future for Get<'a, T, F> {
+    type Output = &'a T;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let lazy = self.lazy;
+        match self.poll_init(cx) {
+            Poll::Ready(()) => unsafe {
+                lazy.track.read();
+                Poll::Ready((*lazy.value.get()).assume_init_ref())
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> FusedFuture for Get<'a, T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> GetMut<'a, T, F> {
+    #[inline(always)]
+    pub(super) fn new (lazy: &'a mut AsyncLazy<T, F>) -> Self {
+        Self { inner: Get::new(lazy), _mut: PhantomData }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> Future for GetMut<'a, T, F> {
+    type Output = &'a mut T;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `inner` is structurally pinned
+        let mut inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+        let lazy = inner.lazy;
+
+        if let Step::Start = inner.step {
+            // `step` isn't pinned until it holds the initialization future.
+            // the cell was borrowed mutably to build this future, so nobody else can access it
+            let this = unsafe { inner.as_mut().get_unchecked_mut() };
+            match lazy.state.load(Ordering::Relaxed) {
+                INIT => {
+                    this.step = Step::Done;
+                    return unsafe { Poll::Ready((*lazy.value.get()).assume_init_mut()) }
+                },
+                _ => this.step = unsafe { this.start_exclusive() }
+            }
+        }
+
+        match inner.poll_init(cx) {
+            // the cell was borrowed mutably to build this future, so nobody else can access the value
+            Poll::Ready(()) => unsafe { Poll::Ready((*lazy.value.get()).assume_init_mut()) },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> FusedFuture for GetMut<'a, T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for Get<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Get").finish_non_exhaustive()
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for GetMut<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GetMut").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,76 @@
+use core::{mem::{ManuallyDrop, MaybeUninit}, sync::atomic::Ordering};
+use crate::{UNINIT, INITIALIZING, PARKED, cancelled, utils::AsyncPoisonGuard};
+use super::{AsyncLazy, Unlock};
+
+impl<T, F> AsyncLazy<T, F> {
+    /// Reserves the initialization of the cell, so that it can be initialized by some external workflow instead of its
+    /// initialization future. Returns ```None``` if the cell is already initialized, or being initialized.
+    ///
+    /// While the guard is alive, the cell is initializing: every [`get`](AsyncLazy::get) (or [`wait`](AsyncLazy::wait))
+    /// future waits for it to [`finish`](AsyncInitGuard::finish). If it's [`abort`](AsyncInitGuard::abort)ed (or dropped)
+    /// instead, the cell goes back to how it was, initialization future included, and the waiting `get` futures resume
+    #[inline]
+    pub fn begin_init (&self) -> Option<AsyncInitGuard<'_, T, F>> {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            match current {
+                UNINIT | PARKED => match self.state.compare_exchange_weak(current, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                    Ok(_) => {
+                        self.wait_for_clones();
+                        self.mark_started(current);
+                        return Some(AsyncInitGuard { unlock: Unlock { lazy: self, prev: current } })
+                    },
+                    Err(state) => current = state
+                },
+                _ => return None
+            }
+        }
+    }
+}
+
+/// Reservation of the initialization of an [`AsyncLazy`] (see [`AsyncLazy::begin_init`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "the initialization is aborted as soon as the guard is dropped"]
+pub struct AsyncInitGuard<'a, T, F> {
+    // restores the cell if the guard is dropped
+    unlock: Unlock<'a, T, F>
+}
+
+impl<'a, T, F> AsyncInitGuard<'a, T, F> {
+    /// Initializes the cell with `value`, dropping its initialization future without polling it, and waking every
+    /// task waiting for it
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization was [aborted](AsyncLazy::abort) through the cell in the meantime (the value is dropped,
+    /// and the cell cancelled)
+    pub fn finish (self, value: T) -> &'a T {
+        let lazy = ManuallyDrop::new(self).unlock.lazy;
+
+        unsafe {
+            // if dropping the future panics, the cell is poisoned
+            let guard = AsyncPoisonGuard::new(&lazy.state, &lazy.waiters);
+            lazy.track.write();
+            core::ptr::drop_in_place((*lazy.f.get()).as_mut_ptr());
+            guard.disarm();
+
+            lazy.value.get().write(MaybeUninit::new(value));
+            if let Err(value) = lazy.complete() {
+                drop(value);
+                cancelled()
+            }
+            (*lazy.value.get()).assume_init_ref()
+        }
+    }
+
+    /// Gives up on the initialization, returning the cell to how it was before [`begin_init`](AsyncLazy::begin_init).
+    /// Same as dropping the guard
+    #[inline(always)]
+    pub fn abort (self) {}
+}
+
+impl<T, F> core::fmt::Debug for AsyncInitGuard<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncInitGuard").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,67 @@
+use core::{future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}};
+use super::AsyncLazy;
+
+/// Initializer of an [`AsyncLazy`] that runs a hook on its value (see [`AsyncLazy::new_with_hook`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Hooked<F, H> {
+    f: F,
+    hook: H
+}
+
+/// Future of a [`Hooked`] initializer
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct HookedFuture<Fut, H> {
+    f: Fut,
+    hook: Option<H>
+}
+
+impl<T, F, H> AsyncLazy<T, Hooked<F, H>> {
+    loom_const! {
+        /// Builds a new ```AsyncLazy``` value that calls `hook` with its value as soon as `f` resolves, before the value is
+        /// handed to (or any task waiting on it is woken by) whoever ran the initialization, be it through
+        /// [`get`](AsyncLazy::get), [`get_mut`](AsyncLazy::get_mut), [`into_inner`](AsyncLazy::into_inner) or any other way.
+        ///
+        /// The hook only runs if `f` does, so it won't if the cell is [`set`](AsyncLazy::set) (or its value
+        /// [`replace`](AsyncLazy::replace)d) before it starts initializing
+        #[inline(always)]
+        pub const fn new_with_hook (f: F, hook: H) -> Self {
+            Self::new(Hooked { f, hook })
+        }
+    }
+}
+
+impl<T, F: IntoFuture<Output = T>, H: FnOnce(&T)> IntoFuture for Hooked<F, H> {
+    type Output = T;
+    type IntoFuture = HookedFuture<F::IntoFuture, H>;
+
+    #[inline(always)]
+    fn into_future(self) -> Self::IntoFuture {
+        HookedFuture { f: self.f.into_future(), hook: Some(self.hook) }
+    }
+}
+
+impl<T, Fut: Future<Output = T>, H: FnOnce(&T)> Future for HookedFuture<Fut, H> {
+    type Output = T;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `f` is structurally pinned, `hook` isn't
+        let this = unsafe { self.get_unchecked_mut() };
+        match unsafe { Pin::new_unchecked(&mut this.f) }.poll(cx) {
+            Poll::Ready(value) => {
+                let hook = this.hook.take().expect("`HookedFuture` polled after completion");
+                hook(&value);
+                Poll::Ready(value)
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<Fut, H> core::fmt::Debug for HookedFuture<Fut, H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HookedFuture").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,239 @@
+use core::{future::{Future, IntoFuture}, mem::MaybeUninit, pin::Pin, sync::atomic::Ordering};
+use core::task::{Context, Poll};
+use futures_core::FusedFuture;
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, CANCELLED, PARKED, TAKEN, ABORTING, SUSPENDED, poisoned, cancelled, taken, utils::{AwaitInit, AsyncPoisonGuard}};
+use super::{AsyncLazy, settled};
+#[cfg(feature = "alloc")]
+use super::Get;
+
+#[cfg(not(debug_assertions))]
+use core::hint::unreachable_unchecked;
+
+/// Future returned by [`AsyncLazy::get_in_place`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GetInPlace<T: 'static, F: 'static + IntoFuture> {
+    lazy: &'static AsyncLazy<T, F>,
+    step: Step,
+    // declared after `step`, so that the initialization future is dropped before the cell is poisoned or cancelled
+    guard: Option<AsyncPoisonGuard<'static>>,
+    /// Resumes the initialization future handed back (boxed) by a dropped `get` future, which can't be moved into the cell
+    #[cfg(feature = "alloc")]
+    resuming: Option<Get<'static, T, F>>
+}
+
+enum Step {
+    /// Not polled yet
+    Start,
+    /// Polling the initialization future, pinned inside the cell
+    Initializing,
+    /// Waiting for another task's initialization
+    Waiting(AwaitInit<'static>),
+    /// Polling the ```get``` future in ```resuming```
+    #[cfg(feature = "alloc")]
+    Resuming,
+    /// Resolved
+    Done
+}
+
+impl<T, F: Future<Output = T>> GetInPlace<T, F> {
+    #[inline(always)]
+    pub(super) const fn new (lazy: &'static AsyncLazy<T, F>) -> Self {
+        Self {
+            lazy,
+            step: Step::Start,
+            guard: None,
+            #[cfg(feature = "alloc")]
+            resuming: None
+        }
+    }
+
+    /// Slow path of the first poll: starts the initialization, or waits for it to be initialized.
+    /// Returns ```None``` if it's already initialized
+    #[cold]
+    #[inline(never)]
+    fn start (&mut self) -> Option<Step> {
+        let lazy = self.lazy;
+        let mut current = UNINIT;
+
+        loop {
+            match lazy.state.compare_exchange(current, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+                // uninitialized, or left partially polled (in place) by `poll_get`
+                Ok(_) => {
+                    lazy.wait_for_clones();
+                    lazy.mark_started(current);
+                    self.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                    return Some(Step::Initializing)
+                },
+
+                // we tried to take over from the wrong state
+                Err(state @ (UNINIT | PARKED)) => current = state,
+
+                // handed back by a dropped `get` future, so it's resumed like one would
+                #[cfg(feature = "alloc")]
+                Err(SUSPENDED) => {
+                    self.resuming = Some(lazy.get());
+                    return Some(Step::Resuming)
+                },
+
+                // currently initializing
+                Err(INITIALIZING) => return Some(Step::Waiting(AwaitInit::new(&lazy.state, &lazy.waiters))),
+
+                // initialized
+                Err(INIT) => return None,
+
+                // initialization panicked
+                Err(POISONED) => poisoned(),
+
+                // initialization was cancelled (or aborted)
+                Err(CANCELLED | ABORTING) => cancelled(),
+
+                // value was taken
+                Err(TAKEN) => taken(),
+
+                #[cfg(debug_assertions)]
+                _ => unreachable!(),
+                #[cfg(not(debug_assertions))]
+                _ => unsafe { unreachable_unchecked() }
+            }
+        }
+    }
+}
+
+impl<T, F: IntoFuture> GetInPlace<T, F> {
+    /// Drops the initialization future (in place) and cancels the cell, after it was aborted
+    #[cold]
+    #[inline(never)]
+    fn abort (&mut self) -> ! {
+        self.step = Step::Done;
+        unsafe { core::ptr::drop_in_place((*self.lazy.f.get()).as_mut_ptr()) }
+        drop(self.guard.take());
+        cancelled()
+    }
+}
+
+/// Drops the initialization future in place, and then the poison guard, if dropped (only happens while unwinding)
+struct Unwinding<'b, F> {
+    f: *mut F,
+    step: &'b mut Step,
+    guard: &'b mut Option<AsyncPoisonGuard<'static>>
+}
+
+impl<F> Drop for Unwinding<'_, F> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        *self.step = Step::Done;
+        unsafe { core::ptr::drop_in_place(self.f) }
+        drop(self.guard.take())
+    }
+}
+
+impl<T, F: Future<Output = T>> Future for GetInPlace<T, F> {
+    type Output = &'static T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `step` is never moved out of while pinned, only dropped in place by assignment
+        let this = unsafe { self.get_unchecked_mut() };
+        let lazy = this.lazy;
+
+        loop {
+            match &mut this.step {
+                Step::Start => match lazy.state.load(Ordering::Acquire) {
+                    INIT => break,
+                    _ => match this.start() {
+                        Some(step) => this.step = step,
+                        None => break
+                    }
+                },
+
+                // the cell is borrowed for ```'static```, so it can never be moved (nor mutably borrowed) again, and the
+                // future is only ever dropped in place
+                Step::Initializing => unsafe {
+                    let f = (*lazy.f.get()).as_mut_ptr();
+                    if lazy.state.load(Ordering::Relaxed) == ABORTING {
+                        this.abort()
+                    }
+
+                    // if `f` panics, drop it and poison the cell right away, since a caught panic may drop this future much later
+                    let unwinding = Unwinding { f, step: &mut this.step, guard: &mut this.guard };
+                    let poll = Pin::new_unchecked(&mut *f).poll(cx);
+                    core::mem::forget(unwinding);
+
+                    let value = match poll {
+                        Poll::Ready(value) => value,
+                        Poll::Pending => {
+                            // so that `abort` can wake us up, in case `f` never does
+                            lazy.waiters.register_driver(cx.waker());
+                            match lazy.state.load(Ordering::Relaxed) {
+                                ABORTING => this.abort(),
+                                _ => return Poll::Pending
+                            }
+                        }
+                    };
+
+                    this.step = Step::Done;
+                    core::ptr::drop_in_place(f);
+                    lazy.value.get().write(MaybeUninit::new(value));
+                    if let Some(guard) = this.guard.take() {
+                        guard.disarm()
+                    }
+
+                    if let Err(value) = lazy.complete() {
+                        drop(value);
+                        cancelled()
+                    }
+                    return Poll::Ready((*lazy.value.get()).assume_init_ref())
+                },
+
+                Step::Waiting(waiting) => match unsafe { Pin::new_unchecked(waiting) }.poll(cx) {
+                    // the task initializing it through `poll_get` left it for someone else to drive (or it was
+                    // just locked to be cloned)
+                    Poll::Ready(UNINIT | PARKED | SUSPENDED) => this.step = Step::Start,
+                    Poll::Ready(state) => {
+                        settled(state);
+                        break
+                    },
+                    Poll::Pending => return Poll::Pending
+                },
+
+                // `resuming` is structurally pinned, and only set along with this step
+                #[cfg(feature = "alloc")]
+                Step::Resuming => match unsafe { Pin::new_unchecked(this.resuming.as_mut().unwrap_unchecked()) }.poll(cx) {
+                    Poll::Ready(value) => {
+                        this.step = Step::Done;
+                        return Poll::Ready(value)
+                    },
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Done => panic!("`GetInPlace` polled after completion")
+            }
+        }
+
+        this.step = Step::Done;
+        unsafe { Poll::Ready((*lazy.value.get()).assume_init_ref()) }
+    }
+}
+
+impl<T, F: Future<Output = T>> FusedFuture for GetInPlace<T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+}
+
+impl<T, F: IntoFuture> Drop for GetInPlace<T, F> {
+    #[inline]
+    fn drop(&mut self) {
+        // cancelled mid-initialization: the future is dropped in place, and then `guard` cancels the cell
+        if let Step::Initializing = self.step {
+            unsafe { core::ptr::drop_in_place((*self.lazy.f.get()).as_mut_ptr()) }
+        }
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for GetInPlace<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GetInPlace").finish_non_exhaustive()
+    }
+}
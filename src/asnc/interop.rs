@@ -0,0 +1,118 @@
+// Conversions from and to the once cells of other crates. Those can't store an initialization future, so converting
+// an uninitialized [`AsyncLazy`] into one drops its future without polling it
+
+use super::{AsyncLazy, AsyncOnce};
+
+impl<T, F> AsyncLazy<T, F> {
+    /// Converts the cell into a [`tokio::sync::OnceCell`], with the value if it's initialized. Otherwise, the new cell
+    /// is empty, and the initialization future (if it's still stored) is dropped without being polled
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub fn into_tokio_once_cell (mut self) -> tokio::sync::OnceCell<T> {
+        tokio::sync::OnceCell::new_with(self.take())
+    }
+
+    /// Converts the cell into an [`async_once_cell::OnceCell`], with the value if it's initialized. Otherwise, the new
+    /// cell is empty, and the initialization future (if it's still stored) is dropped without being polled
+    #[cfg_attr(docsrs, doc(cfg(feature = "async-once-cell")))]
+    #[cfg(feature = "async-once-cell")]
+    #[inline]
+    pub fn into_async_once_cell (mut self) -> async_once_cell::OnceCell<T> {
+        match self.take() {
+            Some(value) => async_once_cell::OnceCell::new_with(value),
+            None => async_once_cell::OnceCell::new()
+        }
+    }
+}
+
+/// Initializes the new cell with the value, if there's one
+#[inline]
+fn once_with<T> (value: Option<T>) -> AsyncOnce<T> {
+    let once = AsyncOnce::new();
+    if let Some(value) = value {
+        let _ = once.set(value);
+    }
+    once
+}
+
+#[cfg(feature = "tokio")]
+impl<T, F> From<AsyncLazy<T, F>> for tokio::sync::OnceCell<T> {
+    /// See [`AsyncLazy::into_tokio_once_cell`]
+    #[inline(always)]
+    fn from(lazy: AsyncLazy<T, F>) -> Self {
+        lazy.into_tokio_once_cell()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T, F> TryFrom<tokio::sync::OnceCell<T>> for AsyncLazy<T, F> {
+    type Error = tokio::sync::OnceCell<T>;
+
+    /// Builds an initialized cell with the value, or returns the cell back if it's empty (there's no future to initialize it with)
+    #[inline]
+    fn try_from(cell: tokio::sync::OnceCell<T>) -> Result<Self, Self::Error> {
+        match cell.initialized() {
+            true => Ok(Self::init(cell.into_inner().unwrap())),
+            false => Err(cell)
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> From<AsyncOnce<T>> for tokio::sync::OnceCell<T> {
+    #[inline(always)]
+    fn from(once: AsyncOnce<T>) -> Self {
+        Self::new_with(once.into_inner())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> From<tokio::sync::OnceCell<T>> for AsyncOnce<T> {
+    #[inline(always)]
+    fn from(cell: tokio::sync::OnceCell<T>) -> Self {
+        once_with(cell.into_inner())
+    }
+}
+
+#[cfg(feature = "async-once-cell")]
+impl<T, F> From<AsyncLazy<T, F>> for async_once_cell::OnceCell<T> {
+    /// See [`AsyncLazy::into_async_once_cell`]
+    #[inline(always)]
+    fn from(lazy: AsyncLazy<T, F>) -> Self {
+        lazy.into_async_once_cell()
+    }
+}
+
+#[cfg(feature = "async-once-cell")]
+impl<T, F> TryFrom<async_once_cell::OnceCell<T>> for AsyncLazy<T, F> {
+    type Error = async_once_cell::OnceCell<T>;
+
+    /// Builds an initialized cell with the value, or returns the cell back if it's empty (there's no future to initialize it with)
+    #[inline]
+    fn try_from(mut cell: async_once_cell::OnceCell<T>) -> Result<Self, Self::Error> {
+        match cell.take() {
+            Some(value) => Ok(Self::init(value)),
+            None => Err(cell)
+        }
+    }
+}
+
+#[cfg(feature = "async-once-cell")]
+impl<T> From<AsyncOnce<T>> for async_once_cell::OnceCell<T> {
+    #[inline]
+    fn from(once: AsyncOnce<T>) -> Self {
+        match once.into_inner() {
+            Some(value) => Self::new_with(value),
+            None => Self::new()
+        }
+    }
+}
+
+#[cfg(feature = "async-once-cell")]
+impl<T> From<async_once_cell::OnceCell<T>> for AsyncOnce<T> {
+    #[inline(always)]
+    fn from(cell: async_once_cell::OnceCell<T>) -> Self {
+        once_with(cell.into_inner())
+    }
+}
@@ -0,0 +1,94 @@
+// Support for the `join!` and `try_join!` macros, which nest the `get` futures of every cell in `Join`s (ending
+// with `()`), and then flatten their output into a tuple
+
+/// Joins the initialization of every cell, concurrently (through their [`get`](crate::AsyncLazy::get) futures), and
+/// resolves to a tuple with references to their values. Must be awaited inside of an async context, and accepts paths
+/// to ```static``` cells as well as expressions evaluating to (references to) cells
+///
+/// ```rust
+/// use laizy::{AsyncLazy, FromFn};
+/// use futures::future::BoxFuture;
+///
+/// static A: AsyncLazy<u8, FromFn<fn() -> BoxFuture<'static, u8>>> = AsyncLazy::from_fn(|| Box::pin(async { 1 }));
+/// let b = laizy::async_lazy(async { "b" });
+///
+/// futures::executor::block_on(async {
+///     let (a, b) = laizy::join!(A, &b);
+///     assert_eq!((*a, *b), (1, "b"));
+/// })
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[macro_export]
+macro_rules! join {
+    ($($lazy:expr),+ $(,)?) => {
+        $crate::__private::Flatten::flatten($crate::__join_nest!($($lazy),+).await)
+    };
+}
+
+/// Like [`join!`], but for [`AsyncTryLazy`](crate::AsyncTryLazy) cells sharing their error type. Resolves to ```Ok```
+/// with a tuple of references to their values if every cell initialized successfully, or to the error of the first
+/// cell (in the order they're given) that failed otherwise. Every initialization runs to completion either way
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[macro_export]
+macro_rules! try_join {
+    ($($lazy:expr),+ $(,)?) => {
+        $crate::__private::TryTuple::transpose($crate::__private::Flatten::flatten($crate::__join_nest!($($lazy),+).await))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __join_nest {
+    () => { ::core::future::ready(()) };
+    ($head:expr $(, $tail:expr)*) => {
+        $crate::__private::Join::new(($head).get(), $crate::__join_nest!($($tail),*))
+    };
+}
+
+/// Nested pairs, ending with `()`, that flatten into a tuple
+pub trait Flatten {
+    type Output;
+    fn flatten (self) -> Self::Output;
+}
+
+/// Tuple of results that turns into a result of a tuple
+pub trait TryTuple {
+    type Output;
+    fn transpose (self) -> Self::Output;
+}
+
+macro_rules! nested {
+    () => { () };
+    ($head:ident $($tail:ident)*) => { ($head, nested!($($tail)*)) };
+}
+
+macro_rules! impl_tuples {
+    () => {};
+    ($head:ident $($tail:ident)*) => {
+        impl<$head, $($tail),*> Flatten for nested!($head $($tail)*) {
+            type Output = ($head, $($tail,)*);
+
+            #[allow(non_snake_case)]
+            #[inline(always)]
+            fn flatten (self) -> Self::Output {
+                let nested!($head $($tail)*) = self;
+                ($head, $($tail,)*)
+            }
+        }
+
+        impl<Err, $head, $($tail),*> TryTuple for (Result<$head, Err>, $(Result<$tail, Err>,)*) {
+            type Output = Result<($head, $($tail,)*), Err>;
+
+            #[allow(non_snake_case)]
+            #[inline(always)]
+            fn transpose (self) -> Self::Output {
+                let ($head, $($tail,)*) = self;
+                Ok(($head?, $($tail?,)*))
+            }
+        }
+
+        impl_tuples!($($tail)*);
+    };
+}
+
+impl_tuples!(A B C D E F G H I J K L);
@@ -0,0 +1,268 @@
+//! Async lazy values for single-threaded executors (e.g. ```LocalSet```s, or embedded runtimes)
+
+use core::{cell::{Cell, UnsafeCell}, future::{Future, IntoFuture}, marker::PhantomData, mem::{MaybeUninit, ManuallyDrop}};
+use core::{pin::Pin, task::{Context, Poll}};
+use futures_core::FusedFuture;
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, CANCELLED, State, poisoned, cancelled, abandoned};
+use crate::utils::{AsyncPoisonGuard, AwaitInit, LocalWaiters, WaitList};
+use super::{FromFn, settled, get::Unwinding};
+
+#[cfg(not(debug_assertions))]
+use core::hint::unreachable_unchecked;
+
+/// A lazy value that initializes via future, for single-threaded executors.
+///
+/// Unlike [`AsyncLazy`](crate::AsyncLazy), it uses no atomics, and it's neither ```Send``` nor ```Sync```, so it can be
+/// initialized by futures that aren't ```Send``` either (e.g. ones holding an ```Rc```). It's also simpler: the
+/// initialization future is always polled by the [`get`](AsyncLazy::get) future that started it, so dropping that
+/// future before it finishes cancels the cell (it's never handed back to the cell to be resumed, even with ```alloc```)
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct AsyncLazy<T, F> {
+    state: Cell<u8>,
+    waiters: LocalWaiters,
+    value: UnsafeCell<MaybeUninit<T>>,
+    f: UnsafeCell<MaybeUninit<F>>,
+    // neither `Send` nor `Sync`
+    _local: PhantomData<*const ()>
+}
+
+impl<T, F> AsyncLazy<T, F> {
+    /// Builds a new ```AsyncLazy``` value
+    #[inline(always)]
+    pub const fn new (f: F) -> Self {
+        Self {
+            state: Cell::new(UNINIT),
+            waiters: LocalWaiters::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            f: UnsafeCell::new(MaybeUninit::new(f)),
+            _local: PhantomData
+        }
+    }
+
+    /// Builds an ```AsyncLazy``` value that's already initialized
+    #[inline(always)]
+    pub const fn init (value: T) -> Self {
+        Self {
+            state: Cell::new(INIT),
+            waiters: LocalWaiters::new(),
+            value: UnsafeCell::new(MaybeUninit::new(value)),
+            f: UnsafeCell::new(MaybeUninit::uninit()),
+            _local: PhantomData
+        }
+    }
+
+    /// Returns the current state of the cell
+    #[inline(always)]
+    pub fn state (&self) -> State {
+        State::from_raw(self.state.get())
+    }
+
+    /// Returns ```true``` if the value has already initialized, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initialized (&self) -> bool {
+        self.state.get() == INIT
+    }
+
+    /// Returns ```true``` if the initialization future panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state.get() == POISONED
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&T> {
+        match self.state.get() {
+            INIT => unsafe { Some((*self.value.get()).assume_init_ref()) },
+            _ => None
+        }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get_mut (&mut self) -> Option<&mut T> {
+        match self.state.get() {
+            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) },
+            _ => None
+        }
+    }
+}
+
+impl<T, C> AsyncLazy<T, FromFn<C>> {
+    /// Builds a new ```AsyncLazy``` value whose initialization future is built by `f` when it's first needed
+    /// (see [`AsyncLazy::from_fn`](crate::AsyncLazy::from_fn))
+    #[inline(always)]
+    pub const fn from_fn (f: C) -> Self {
+        Self::new(FromFn { f })
+    }
+}
+
+impl<T, F: IntoFuture<Output = T>> AsyncLazy<T, F> {
+    /// Returns a reference to the inner value, initializing or waiting for it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future panicked (the value is poisoned), or if it was cancelled (the ```get``` future
+    /// running it was dropped before it finished)
+    #[inline(always)]
+    pub fn get (&self) -> Get<'_, T, F> {
+        Get { lazy: self, step: Step::Start, guard: None }
+    }
+
+    /// Returns the inner value, initializing it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization future ran, but panicked (the value is poisoned), or was cancelled, or if the
+    /// initialization was abandoned (its ```get``` future was leaked)
+    pub async fn into_inner (self) -> T {
+        let this = ManuallyDrop::new(self);
+
+        match this.state.get() {
+            UNINIT => unsafe {
+                let f = core::ptr::read(this.f.get()).assume_init();
+                f.await
+            },
+
+            // nobody can be initializing a cell we own, so its `get` future was leaked
+            INITIALIZING => abandoned(),
+            POISONED => poisoned(),
+            CANCELLED => cancelled(),
+            INIT => unsafe { core::ptr::read(this.value.get()).assume_init() },
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unsafe { unreachable_unchecked() }
+        }
+    }
+}
+
+impl<T: core::fmt::Debug, F> core::fmt::Debug for AsyncLazy<T, F> {
+    /// Prints the value if it's initialized, or the state of the cell otherwise (without ever polling the future)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("AsyncLazy");
+        match self.try_get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&self.state())
+        };
+        tuple.finish()
+    }
+}
+
+impl<T, F> Drop for AsyncLazy<T, F> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        match self.state.get() {
+            UNINIT => unsafe { self.f.get_mut().assume_init_drop() },
+            INIT => unsafe { self.value.get_mut().assume_init_drop() },
+            // poisoned or cancelled (the future was dropped by its `get` future), or still initializing (its `get` future was leaked)
+            _ => {}
+        }
+    }
+}
+
+// the future is moved out of the cell before it's polled (see `AsyncLazy`)
+impl<T, F> Unpin for AsyncLazy<T, F> {}
+
+/// Future returned by [`AsyncLazy::get`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Get<'a, T, F: IntoFuture> {
+    lazy: &'a AsyncLazy<T, F>,
+    step: Step<'a, F::IntoFuture>,
+    // declared after `step`, so that the initialization future is dropped before the cell is poisoned or cancelled
+    guard: Option<AsyncPoisonGuard<'a, Cell<u8>, LocalWaiters>>
+}
+
+enum Step<'a, Fut> {
+    /// Not polled yet
+    Start,
+    /// Polling the initialization future
+    Initializing(Fut),
+    /// Waiting for another task's initialization
+    Waiting(AwaitInit<'a, Cell<u8>, LocalWaiters>),
+    /// Resolved
+    Done
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> Future for Get<'a, T, F> {
+    type Output = &'a T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `step` is never moved out of while pinned, only dropped in place by assignment
+        let this = unsafe { self.get_unchecked_mut() };
+        let lazy = this.lazy;
+
+        loop {
+            match &mut this.step {
+                Step::Start => match lazy.state.get() {
+                    INIT => break,
+                    UNINIT => unsafe {
+                        lazy.state.set(INITIALIZING);
+                        this.guard = Some(AsyncPoisonGuard::new(&lazy.state, &lazy.waiters));
+                        let f = core::ptr::read(lazy.f.get()).assume_init();
+                        this.step = Step::Initializing(f.into_future())
+                    },
+                    INITIALIZING => this.step = Step::Waiting(AwaitInit::new(&lazy.state, &lazy.waiters)),
+                    POISONED => poisoned(),
+                    CANCELLED => cancelled(),
+
+                    #[cfg(debug_assertions)]
+                    _ => unreachable!(),
+                    #[cfg(not(debug_assertions))]
+                    _ => unsafe { unreachable_unchecked() }
+                },
+
+                Step::Initializing(f) => unsafe {
+                    // if `f` panics, poison the cell right away, since a caught panic may drop this future much later
+                    let unwinding = Unwinding(&mut this.guard);
+                    let poll = Pin::new_unchecked(f).poll(cx);
+                    core::mem::forget(unwinding);
+
+                    match poll {
+                        Poll::Ready(value) => {
+                            this.step = Step::Done;
+                            lazy.value.get().write(MaybeUninit::new(value));
+                            if let Some(guard) = this.guard.take() {
+                                guard.disarm()
+                            }
+
+                            lazy.state.set(INIT);
+                            lazy.waiters.wake_all();
+                            return Poll::Ready((*lazy.value.get()).assume_init_ref())
+                        },
+                        Poll::Pending => return Poll::Pending
+                    }
+                },
+
+                Step::Waiting(waiting) => match unsafe { Pin::new_unchecked(waiting) }.poll(cx) {
+                    Poll::Ready(state) => {
+                        this.step = Step::Done;
+                        settled(state);
+                        break
+                    },
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Done => panic!("`Get` polled after completion")
+            }
+        }
+
+        this.step = Step::Done;
+        unsafe { Poll::Ready((*lazy.value.get()).assume_init_ref()) }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> FusedFuture for Get<'a, T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        matches!(self.step, Step::Done)
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for Get<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Get").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,92 @@
+use core::{borrow::Borrow, future::IntoFuture, hash::Hash};
+use std::{collections::HashMap, sync::{Arc, Mutex, MutexGuard, PoisonError}};
+use super::AsyncOnce;
+
+/// A map of values that are initialized asynchronously, at most once per key.
+///
+/// The first caller asking for a key runs its initialization future, while concurrent callers asking for the same key
+/// wait for it (each key has its own [`AsyncOnce`]), and later callers get the cached value. The map is only locked to
+/// look the key up, never while a value is being initialized, so keys don't wait on each other. Since values may be
+/// [removed](AsyncLazyMap::remove) through a shared reference, they're handed out as an [`Arc`]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "std"))))]
+pub struct AsyncLazyMap<K, V> {
+    entries: Mutex<HashMap<K, Arc<AsyncOnce<Arc<V>>>>>
+}
+
+impl<K, V> AsyncLazyMap<K, V> {
+    /// Builds a new, empty ```AsyncLazyMap```
+    #[inline(always)]
+    pub fn new () -> Self {
+        Self { entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the number of keys in the map, including those whose value is still being initialized
+    #[inline]
+    pub fn len (&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns ```true``` if the map has no keys, ```false``` otherwise
+    #[inline]
+    pub fn is_empty (&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes every key from the map, so that they're initialized again on their next access.
+    /// Initializations in flight still complete for the callers waiting on them, but their values aren't cached
+    #[inline]
+    pub fn clear (&self) {
+        self.lock().clear()
+    }
+
+    /// Locks the map, even if a thread panicked while holding the lock (the map itself is never left half-updated)
+    #[inline(always)]
+    fn lock (&self) -> MutexGuard<'_, HashMap<K, Arc<AsyncOnce<Arc<V>>>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: Eq + Hash, V> AsyncLazyMap<K, V> {
+    /// Returns the value of `key` if it's already initialized, ```None``` otherwise
+    #[inline]
+    pub fn get<Q: ?Sized + Eq + Hash> (&self, key: &Q) -> Option<Arc<V>> where K: Borrow<Q> {
+        self.lock().get(key)?.get().cloned()
+    }
+
+    /// Removes `key` from the map, so that it's initialized again on its next access. Returns its value if it was initialized.
+    /// An initialization in flight still completes for the callers waiting on it, but its value isn't cached
+    #[inline]
+    pub fn remove<Q: ?Sized + Eq + Hash> (&self, key: &Q) -> Option<Arc<V>> where K: Borrow<Q> {
+        self.lock().remove(key)?.get().cloned()
+    }
+
+    /// Returns the value of `key`, initializing it with the future built by `f` if nobody else is, or waiting for whoever
+    /// is otherwise (see [`AsyncOnce::get_or_init`])
+    ///
+    /// # Panics
+    ///
+    /// Panics if the future that initialized the value panicked, until the key is [removed](AsyncLazyMap::remove)
+    pub async fn get_or_init<F: IntoFuture<Output = V>> (&self, key: K, f: impl FnOnce() -> F) -> Arc<V> {
+        let once = self.lock().entry(key).or_default().clone();
+        if let Some(value) = once.get() {
+            return value.clone()
+        }
+
+        let f = f().into_future();
+        once.get_or_init(async move { Arc::new(f.await) }).await.clone()
+    }
+}
+
+impl<K, V> Default for AsyncLazyMap<K, V> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: core::fmt::Debug, V: core::fmt::Debug> core::fmt::Debug for AsyncLazyMap<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let entries = self.lock();
+        f.debug_map().entries(entries.iter().map(|(key, once)| (key, once.get()))).finish()
+    }
+}
@@ -0,0 +1,342 @@
+use core::{cell::UnsafeCell, convert::Infallible, mem::{ManuallyDrop, MaybeUninit}, pin::Pin};
+use core::{future::{Future, IntoFuture}, sync::atomic::Ordering, task::{Context, Poll}};
+use crate::sync::AtomicU8;
+use futures_core::FusedFuture;
+use crate::{UNINIT, INITIALIZING, INIT, POISONED, poisoned, utils::{AwaitInit, AsyncPoisonGuard, Waiters}};
+use super::get::Unwinding;
+
+#[cfg(not(debug_assertions))]
+use core::hint::unreachable_unchecked;
+
+/// A cell that's initialized asynchronously at most once, by a future supplied at each call site.
+///
+/// Unlike [`AsyncLazy`](crate::AsyncLazy), the cell doesn't store its initialization future: every call to
+/// [`get_or_init`](AsyncOnce::get_or_init) brings its own, and only the first one to be polled runs it, while the
+/// others wait for its value.
+///
+/// If the running future is cancelled (or fails, through [`get_or_try_init`](AsyncOnce::get_or_try_init)), the cell
+/// is left uninitialized, and one of the waiting callers runs its own future instead. If it panics, the cell is poisoned
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct AsyncOnce<T> {
+    state: AtomicU8,
+    waiters: Waiters,
+    value: UnsafeCell<MaybeUninit<T>>
+}
+
+impl<T> AsyncOnce<T> {
+    loom_const! {
+        /// Builds a new, uninitialized ```AsyncOnce```
+        #[inline(always)]
+        pub const fn new () -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                waiters: Waiters::new(),
+                value: UnsafeCell::new(MaybeUninit::uninit())
+            }
+        }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe { Some((*self.value.get()).assume_init_ref()) },
+            _ => None
+        }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> Option<&mut T> {
+        match *self.state.get_mut() {
+            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) },
+            _ => None
+        }
+    }
+
+    /// Initializes the cell with `value`, waking every task waiting on it.
+    ///
+    /// Returns `value` back if the cell is already initialized, or some future is currently initializing it
+    pub fn set (&self, value: T) -> Result<(), T> {
+        if self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(value)
+        }
+
+        unsafe { self.value.get().write(MaybeUninit::new(value)) }
+        self.complete();
+        Ok(())
+    }
+
+    /// Returns a reference to the value, initializing it with `f` if nobody else is, or waiting for whoever is otherwise.
+    /// `f` is dropped without being polled if the value is initialized by someone else
+    ///
+    /// # Panics
+    ///
+    /// Panics if the future that initialized the value panicked (the cell is poisoned)
+    #[inline(always)]
+    pub fn get_or_init<F: IntoFuture<Output = T>> (&self, f: F) -> GetOrInit<'_, T, F> {
+        GetOrInit { inner: Init::new(self, f) }
+    }
+
+    /// Like [`get_or_init`](AsyncOnce::get_or_init), but with a fallible future. If `f` fails, its error is returned and the
+    /// cell is left uninitialized, so that the next caller (or someone already waiting) can try again
+    ///
+    /// # Panics
+    ///
+    /// Panics if the future that initialized the value panicked (the cell is poisoned)
+    #[inline(always)]
+    pub fn get_or_try_init<E, F: IntoFuture<Output = Result<T, E>>> (&self, f: F) -> GetOrTryInit<'_, T, F> {
+        GetOrTryInit { inner: Init::new(self, f) }
+    }
+
+    /// Returns ```true``` if the future initializing the value panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state.load(Ordering::Acquire) == POISONED
+    }
+
+    /// Returns the inner value if it's initialized, ```None``` otherwise
+    #[inline]
+    pub fn into_inner (self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        let state = *this.state.get_mut();
+        match state {
+            INIT => unsafe { Some(this.value.get_mut().assume_init_read()) },
+            _ => None
+        }
+    }
+
+    /// Marks the cell as initialized (after the value has been written), waking every task waiting for it
+    #[inline]
+    fn complete (&self) {
+        #[cfg(debug_assertions)]
+        assert_eq!(self.state.swap(INIT, Ordering::Release), INITIALIZING);
+        #[cfg(not(debug_assertions))]
+        self.state.store(INIT, Ordering::Release);
+        self.waiters.wake_all();
+    }
+}
+
+/// Future returned by [`AsyncOnce::get_or_init`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GetOrInit<'a, T, F: IntoFuture> {
+    inner: Init<'a, T, F>
+}
+
+/// Future returned by [`AsyncOnce::get_or_try_init`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GetOrTryInit<'a, T, F: IntoFuture> {
+    inner: Init<'a, T, F>
+}
+
+/// Drives (or waits for) the initialization of an [`AsyncOnce`] with the caller's future
+struct Init<'a, T, F: IntoFuture> {
+    once: &'a AsyncOnce<T>,
+    /// The caller's future, until it's started (it's never polled in here, so it isn't pinned)
+    f: Option<F>,
+    step: Step<'a, F::IntoFuture>,
+    // declared after `step`, so that the initialization future is dropped before the cell is reset or poisoned
+    guard: Option<AsyncPoisonGuard<'a>>
+}
+
+enum Step<'a, Fut> {
+    /// Not polled yet, or the previous initialization was cancelled (or failed)
+    Start,
+    /// Polling the initialization future
+    Initializing(Fut),
+    /// Waiting for another task's initialization
+    Waiting(AwaitInit<'a>),
+    /// Resolved
+    Done
+}
+
+impl<'a, T, F: IntoFuture> Init<'a, T, F> {
+    #[inline(always)]
+    const fn new (once: &'a AsyncOnce<T>, f: F) -> Self {
+        Self { once, f: Some(f), step: Step::Start, guard: None }
+    }
+
+    /// Drives the initialization, splitting the output of the caller's future into the value or its error with `split`
+    fn poll_with<E> (self: Pin<&mut Self>, cx: &mut Context<'_>, split: impl FnOnce(F::Output) -> Result<T, E>) -> Poll<Result<&'a T, E>> {
+        // `step` is never moved out of while pinned, only dropped in place by assignment
+        let this = unsafe { self.get_unchecked_mut() };
+        let once = this.once;
+
+        loop {
+            match &mut this.step {
+                Step::Start => match once.state.load(Ordering::Acquire) {
+                    INIT => break,
+                    _ => match this.start() {
+                        Some(step) => this.step = step,
+                        None => break
+                    }
+                },
+
+                Step::Initializing(f) => unsafe {
+                    // if `f` panics, poison the cell right away, since a caught panic may drop this future much later
+                    let unwinding = Unwinding(&mut this.guard);
+                    let poll = Pin::new_unchecked(f).poll(cx);
+                    core::mem::forget(unwinding);
+
+                    let output = match poll {
+                        Poll::Ready(output) => output,
+                        Poll::Pending => return Poll::Pending
+                    };
+
+                    this.step = Step::Done;
+                    match split(output) {
+                        Ok(value) => {
+                            once.value.get().write(MaybeUninit::new(value));
+                            if let Some(guard) = this.guard.take() {
+                                guard.disarm()
+                            }
+                            once.complete();
+                            break
+                        },
+
+                        // makes the cell uninitialized again, waking up the waiters so that one of them can retry
+                        Err(e) => {
+                            drop(this.guard.take());
+                            return Poll::Ready(Err(e))
+                        }
+                    }
+                },
+
+                Step::Waiting(waiting) => match unsafe { Pin::new_unchecked(waiting) }.poll(cx) {
+                    // the initialization was cancelled (or failed), so we get to try
+                    Poll::Ready(UNINIT) => this.step = Step::Start,
+                    Poll::Ready(INIT) => break,
+                    Poll::Ready(POISONED) => {
+                        this.step = Step::Done;
+                        poisoned()
+                    },
+                    #[cfg(debug_assertions)]
+                    Poll::Ready(_) => unreachable!(),
+                    #[cfg(not(debug_assertions))]
+                    Poll::Ready(_) => unsafe { unreachable_unchecked() },
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Done => panic!("`AsyncOnce` future polled after completion")
+            }
+        }
+
+        this.step = Step::Done;
+        unsafe { Poll::Ready(Ok((*once.value.get()).assume_init_ref())) }
+    }
+
+    /// Slow path of the first poll: starts the initialization, or waits for it to be initialized.
+    /// Returns ```None``` if it's already initialized
+    #[cold]
+    #[inline(never)]
+    fn start (&mut self) -> Option<Step<'a, F::IntoFuture>> {
+        let once = self.once;
+        match once.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                self.guard = Some(AsyncPoisonGuard::retrying(&once.state, &once.waiters));
+                // only taken once: after that, this future either resolves or keeps polling it
+                let f = unsafe { self.f.take().unwrap_unchecked() };
+                Some(Step::Initializing(f.into_future()))
+            },
+
+            // currently initializing
+            Err(INITIALIZING) => Some(Step::Waiting(AwaitInit::new(&once.state, &once.waiters))),
+
+            // initialized
+            Err(INIT) => None,
+
+            // initialization panicked
+            Err(POISONED) => poisoned(),
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unsafe { unreachable_unchecked() }
+        }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> Future for GetOrInit<'a, T, F> {
+    type Output = &'a T;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `inner` is structurally pinned
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+        match inner.poll_with(cx, Ok::<T, Infallible>) {
+            Poll::Ready(Ok(value)) => Poll::Ready(value),
+            Poll::Ready(Err(e)) => match e {},
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<'a, T, E, F: IntoFuture<Output = Result<T, E>>> Future for GetOrTryInit<'a, T, F> {
+    type Output = Result<&'a T, E>;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `inner` is structurally pinned
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+        inner.poll_with(cx, core::convert::identity)
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>> FusedFuture for GetOrInit<'a, T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        matches!(self.inner.step, Step::Done)
+    }
+}
+
+impl<'a, T, E, F: IntoFuture<Output = Result<T, E>>> FusedFuture for GetOrTryInit<'a, T, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        matches!(self.inner.step, Step::Done)
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for GetOrInit<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GetOrInit").finish_non_exhaustive()
+    }
+}
+
+impl<T, F: IntoFuture> core::fmt::Debug for GetOrTryInit<'_, T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GetOrTryInit").finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for AsyncOnce<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for AsyncOnce<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("AsyncOnce");
+        match self.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&crate::State::from_raw(self.state.load(Ordering::Acquire)))
+        };
+        tuple.finish()
+    }
+}
+
+impl<T> Drop for AsyncOnce<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        // nobody can be initializing it while it's borrowed mutably (a leaked future leaves it initializing, with no value)
+        if *self.state.get_mut() == INIT {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for AsyncOnce<T> {}
+unsafe impl<T: Send + Sync> Sync for AsyncOnce<T> {}
@@ -0,0 +1,94 @@
+use core::{cell::UnsafeCell, mem::MaybeUninit, ops::Deref, sync::atomic::Ordering};
+use crate::sync::AtomicU8;
+use crate::{INIT, State};
+use super::AsyncLazy;
+
+impl<T, F> AsyncLazy<T, F> {
+    /// Returns a reference to the inner value, which must have been initialized already (e.g. by awaiting it at startup).
+    /// The initialization future is never polled
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value isn't initialized, naming its type and the state of the cell
+    #[track_caller]
+    #[inline]
+    pub fn get_ready (&self) -> &T {
+        match self.try_get() {
+            Some(value) => value,
+            None => not_ready::<T>(self.state.load(Ordering::Acquire))
+        }
+    }
+
+    /// Returns a view of the cell that dereferences to its value, for code that only runs once it's initialized.
+    /// Like [`get_ready`](AsyncLazy::get_ready), the view never polls the initialization future, and panics if it's
+    /// dereferenced before the value is initialized. Building it never panics
+    #[inline(always)]
+    pub fn as_ready (&self) -> SyncView<'_, T> {
+        SyncView { state: &self.state, value: &self.value }
+    }
+}
+
+/// View of an [`AsyncLazy`] that dereferences to its value, once it's initialized (see [`AsyncLazy::as_ready`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct SyncView<'a, T> {
+    state: &'a AtomicU8,
+    value: &'a UnsafeCell<MaybeUninit<T>>
+}
+
+impl<'a, T> SyncView<'a, T> {
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&'a T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe { Some((*self.value.get()).assume_init_ref()) },
+            _ => None
+        }
+    }
+}
+
+impl<T> Deref for SyncView<'_, T> {
+    type Target = T;
+
+    /// # Panics
+    ///
+    /// Panics if the value isn't initialized (see [`AsyncLazy::get_ready`])
+    #[track_caller]
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self.try_get() {
+            Some(value) => value,
+            None => not_ready::<T>(self.state.load(Ordering::Acquire))
+        }
+    }
+}
+
+impl<T> Clone for SyncView<'_, T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SyncView<'_, T> {}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for SyncView<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("SyncView");
+        match self.try_get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&State::from_raw(self.state.load(Ordering::Acquire)))
+        };
+        tuple.finish()
+    }
+}
+
+// the view only hands out `&T`, like a reference to the value would
+unsafe impl<T: Sync> Send for SyncView<'_, T> {}
+unsafe impl<T: Sync> Sync for SyncView<'_, T> {}
+
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn not_ready<T> (state: u8) -> ! {
+    panic!("AsyncLazy<{}> accessed before it was initialized (it's {:?})", core::any::type_name::<T>(), State::from_raw(state))
+}
@@ -0,0 +1,122 @@
+use core::{future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}, time::Duration};
+use super::AsyncTryLazy;
+
+/// How many times (and how often) a [`Retry`] initializer attempts the initialization
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. The initialization is always attempted at least once
+    pub max_attempts: u32,
+    /// Time to wait before the next attempt, given how many have failed so far (starting at 1)
+    pub backoff: fn(u32) -> Duration
+}
+
+/// Initializer of an [`AsyncTryLazy`] that retries failed attempts (see [`AsyncTryLazy::retrying`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Retry<M, S> {
+    policy: RetryPolicy,
+    make: M,
+    sleep: S
+}
+
+/// Future of a [`Retry`] initializer
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Retrying<M, S, A, Z> {
+    retry: Retry<M, S>,
+    failed: u32,
+    step: Step<A, Z>
+}
+
+enum Step<A, Z> {
+    /// Running an attempt
+    Attempt(A),
+    /// Waiting for the next attempt
+    Sleeping(Z),
+    /// Resolved
+    Done
+}
+
+impl<T, E, M, S> AsyncTryLazy<T, E, Retry<M, S>> {
+    loom_const! {
+        /// Builds a new ```AsyncTryLazy``` value that initializes with the futures built by `make`, attempting the
+        /// initialization again whenever it fails, as long as the `policy` allows it. Only the last failure is cached.
+        ///
+        /// Between attempts it waits for the future built by `sleep`, given the policy's backoff, so any runtime's timer can
+        /// be used (e.g. ```tokio::time::sleep```). Every attempt runs as part of the cell's single initialization, so tasks
+        /// waiting on it never trigger extra attempts
+        #[inline(always)]
+        pub const fn retrying (policy: RetryPolicy, make: M, sleep: S) -> Self {
+            Self::new(Retry { policy, make, sleep })
+        }
+    }
+}
+
+impl<T, E, M, Fut, S, Z> IntoFuture for Retry<M, S> where
+    M: FnMut() -> Fut,
+    Fut: IntoFuture<Output = Result<T, E>>,
+    S: FnMut(Duration) -> Z,
+    Z: Future
+{
+    type Output = Result<T, E>;
+    type IntoFuture = Retrying<M, S, Fut::IntoFuture, Z>;
+
+    #[inline]
+    fn into_future(mut self) -> Self::IntoFuture {
+        let first = (self.make)().into_future();
+        Retrying { retry: self, failed: 0, step: Step::Attempt(first) }
+    }
+}
+
+impl<T, E, M, Fut, S, A, Z> Future for Retrying<M, S, A, Z> where
+    M: FnMut() -> Fut,
+    Fut: IntoFuture<Output = Result<T, E>, IntoFuture = A>,
+    A: Future<Output = Result<T, E>>,
+    S: FnMut(Duration) -> Z,
+    Z: Future
+{
+    type Output = Result<T, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `step` is never moved out of while pinned, only dropped in place by assignment. `retry` isn't pinned
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            match &mut this.step {
+                Step::Attempt(attempt) => match unsafe { Pin::new_unchecked(attempt) }.poll(cx) {
+                    Poll::Ready(Err(e)) => {
+                        this.failed += 1;
+                        if this.failed >= this.retry.policy.max_attempts {
+                            this.step = Step::Done;
+                            return Poll::Ready(Err(e))
+                        }
+
+                        let backoff = (this.retry.policy.backoff)(this.failed);
+                        this.step = Step::Sleeping((this.retry.sleep)(backoff));
+                    },
+
+                    Poll::Ready(Ok(value)) => {
+                        this.step = Step::Done;
+                        return Poll::Ready(Ok(value))
+                    },
+
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Sleeping(sleep) => match unsafe { Pin::new_unchecked(sleep) }.poll(cx) {
+                    Poll::Ready(_) => this.step = Step::Attempt((this.retry.make)().into_future()),
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Step::Done => panic!("`Retrying` polled after completion")
+            }
+        }
+    }
+}
+
+impl<M, S, A, Z> core::fmt::Debug for Retrying<M, S, A, Z> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Retrying").field("failed", &self.failed).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,91 @@
+use core::{future::Future, pin::Pin, task::{Context, Poll}};
+use super::AsyncLazy;
+
+/// Value of one of two alternatives (see [`select`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    /// The first alternative
+    Left(L),
+    /// The second alternative
+    Right(R)
+}
+
+/// Drives the initialization of both cells concurrently, resolving with the value of the first one to be initialized.
+///
+/// Both initialization futures are polled in place (see [`AsyncLazy::poll_get`]), so the loser's isn't cancelled once
+/// the returned future resolves (or if it's dropped before), but left in its cell for any task waiting on it (or the next
+/// one to access it) to keep driving. If both are ready, `a` wins
+///
+/// # Panics
+///
+/// Panics if polling either cell panics (see [`AsyncLazy::poll_get`])
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[inline(always)]
+pub fn select<'a, T, U, A, B> (a: &'a AsyncLazy<T, A>, b: &'a AsyncLazy<U, B>) -> Select<'a, T, U, A, B> where
+    A: Future<Output = T> + Unpin,
+    B: Future<Output = U> + Unpin
+{
+    Select { a, b }
+}
+
+/// Like [`select`], for two cells of the same type, resolving to the value of the first one
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[inline(always)]
+pub fn race<'a, T, A, B> (a: &'a AsyncLazy<T, A>, b: &'a AsyncLazy<T, B>) -> Race<'a, T, A, B> where
+    A: Future<Output = T> + Unpin,
+    B: Future<Output = T> + Unpin
+{
+    Race { inner: select(a, b) }
+}
+
+/// Future returned by [`select`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Select<'a, T, U, A, B> {
+    a: &'a AsyncLazy<T, A>,
+    b: &'a AsyncLazy<U, B>
+}
+
+/// Future returned by [`race`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Race<'a, T, A, B> {
+    inner: Select<'a, T, T, A, B>
+}
+
+impl<'a, T, U, A: Future<Output = T> + Unpin, B: Future<Output = U> + Unpin> Future for Select<'a, T, U, A, B> {
+    type Output = Either<&'a T, &'a U>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(value) = self.a.poll_get(cx) {
+            return Poll::Ready(Either::Left(value))
+        }
+
+        self.b.poll_get(cx).map(Either::Right)
+    }
+}
+
+impl<'a, T, A: Future<Output = T> + Unpin, B: Future<Output = T> + Unpin> Future for Race<'a, T, A, B> {
+    type Output = &'a T;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner).poll(cx).map(|value| match value {
+            Either::Left(value) | Either::Right(value) => value
+        })
+    }
+}
+
+impl<T, U, A, B> core::fmt::Debug for Select<'_, T, U, A, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Select").finish_non_exhaustive()
+    }
+}
+
+impl<T, A, B> core::fmt::Debug for Race<'_, T, A, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Race").finish_non_exhaustive()
+    }
+}
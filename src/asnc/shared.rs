@@ -0,0 +1,79 @@
+use core::future::{Future, IntoFuture};
+use alloc::sync::Arc;
+use crate::State;
+use super::AsyncLazy;
+
+/// A clonable, reference counted [`AsyncLazy`]. Every clone shares the same cell, so the value is only initialized once
+/// across all of them, but each clone can hand out futures that own their own reference to it, and can be spawned
+#[cfg_attr(docsrs, doc(cfg(all(feature = "futures", feature = "alloc"))))]
+pub struct SharedAsyncLazy<T, F> {
+    inner: Arc<AsyncLazy<T, F>>
+}
+
+impl<T, F> SharedAsyncLazy<T, F> {
+    /// Builds a new ```SharedAsyncLazy``` value
+    #[inline(always)]
+    pub fn new (f: F) -> Self {
+        Self::from(AsyncLazy::new(f))
+    }
+
+    /// Returns the current state of the cell
+    #[inline(always)]
+    pub fn state (&self) -> State {
+        self.inner.state()
+    }
+
+    /// Returns ```Some(ref value)``` if the value is initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&T> {
+        self.inner.try_get()
+    }
+
+    /// Returns a reference to the shared cell
+    #[inline(always)]
+    pub fn as_lazy (&self) -> &AsyncLazy<T, F> {
+        &self.inner
+    }
+}
+
+impl<T, F: IntoFuture<Output = T>> SharedAsyncLazy<T, F> {
+    /// Returns a clone of the inner value, initializing or waiting for it if necessary.
+    ///
+    /// The returned future holds its own reference to the cell, so it's ```'static``` (and ```Send```, as long as the
+    /// cell can be shared between threads), and can be spawned. Wrap the value in an [`Arc`] to share it instead of cloning it
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`AsyncLazy::get`]
+    #[inline]
+    pub fn get (&self) -> impl Future<Output = T> + Send + 'static where
+        T: 'static + Clone + Send + Sync,
+        F: 'static + Send,
+        F::IntoFuture: Send
+    {
+        let inner = self.inner.clone();
+        async move { inner.get().await.clone() }
+    }
+}
+
+impl<T, F> Clone for SharedAsyncLazy<T, F> {
+    /// Returns a new handle to the same cell
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<T, F> From<AsyncLazy<T, F>> for SharedAsyncLazy<T, F> {
+    #[inline(always)]
+    fn from(lazy: AsyncLazy<T, F>) -> Self {
+        Self { inner: Arc::new(lazy) }
+    }
+}
+
+impl<T: core::fmt::Debug, F> core::fmt::Debug for SharedAsyncLazy<T, F> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SharedAsyncLazy").field(&self.inner).finish()
+    }
+}
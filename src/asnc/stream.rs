@@ -0,0 +1,170 @@
+use core::{future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}};
+use futures_core::{Stream, FusedStream};
+use crate::poisoned;
+
+/// A stream that isn't constructed until it's first polled.
+///
+/// The first call to ```poll_next``` builds the stream with `f`, stores it (pinned in place), and every call from then on
+/// is forwarded to it. See [`AsyncLazyStream`] for streams built by a future
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "streams do nothing unless polled"]
+pub struct LazyStream<S, F> {
+    inner: Inner<S, F, ()>
+}
+
+/// A stream that isn't constructed until it's first polled, by a future.
+///
+/// The first call to ```poll_next``` starts polling the future returned by `f`. Once it resolves, the stream it
+/// returns is stored (pinned in place), and every call from then on is forwarded to it
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "streams do nothing unless polled"]
+pub struct AsyncLazyStream<S, F: IntoFuture> {
+    inner: Inner<S, F, F::IntoFuture>
+}
+
+enum Inner<S, F, Fut> {
+    /// Not polled yet
+    Uninit(F),
+    /// Polling the future building the stream
+    Constructing(Fut),
+    /// Forwarding to the stream
+    Init(S),
+    /// The constructor panicked
+    Poisoned
+}
+
+impl<S, F, Fut> Inner<S, F, Fut> {
+    /// Moves the constructor out, leaving the stream poisoned until it's replaced
+    #[inline(always)]
+    fn take (&mut self) -> F {
+        match core::mem::replace(self, Self::Poisoned) {
+            Self::Uninit(f) => f,
+            _ => unreachable!()
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint (&self) -> (usize, Option<usize>) where S: Stream {
+        match self {
+            Self::Init(stream) => stream.size_hint(),
+            _ => (0, None)
+        }
+    }
+
+    #[inline(always)]
+    fn is_terminated (&self) -> bool where S: FusedStream {
+        match self {
+            Self::Init(stream) => stream.is_terminated(),
+            _ => false
+        }
+    }
+}
+
+impl<S, F> LazyStream<S, F> {
+    /// Builds a new ```LazyStream``` value
+    #[inline(always)]
+    pub const fn new (f: F) -> Self {
+        Self { inner: Inner::Uninit(f) }
+    }
+
+    /// Returns ```true``` if the stream has been constructed, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initialized (&self) -> bool {
+        matches!(self.inner, Inner::Init(_))
+    }
+}
+
+impl<S, F: IntoFuture> AsyncLazyStream<S, F> {
+    /// Builds a new ```AsyncLazyStream``` value
+    #[inline(always)]
+    pub const fn new (f: F) -> Self {
+        Self { inner: Inner::Uninit(f) }
+    }
+
+    /// Returns ```true``` if the stream has been constructed, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initialized (&self) -> bool {
+        matches!(self.inner, Inner::Init(_))
+    }
+}
+
+impl<S: Stream, F: FnOnce() -> S> Stream for LazyStream<S, F> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // only the stream is structurally pinned, and it's never moved once stored
+        let inner = unsafe { &mut self.get_unchecked_mut().inner };
+
+        if let Inner::Uninit(_) = inner {
+            let f = inner.take();
+            *inner = Inner::Init(f());
+        }
+
+        match inner {
+            Inner::Init(stream) => unsafe { Pin::new_unchecked(stream) }.poll_next(cx),
+            _ => poisoned()
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S: Stream, F: IntoFuture<Output = S>> Stream for AsyncLazyStream<S, F> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // the future and the stream are structurally pinned, and never moved once stored
+        let inner = unsafe { &mut self.get_unchecked_mut().inner };
+
+        loop {
+            match inner {
+                Inner::Uninit(_) => {
+                    let f = inner.take();
+                    *inner = Inner::Constructing(f.into_future());
+                },
+
+                Inner::Constructing(fut) => match unsafe { Pin::new_unchecked(fut) }.poll(cx) {
+                    Poll::Ready(stream) => *inner = Inner::Init(stream),
+                    Poll::Pending => return Poll::Pending
+                },
+
+                Inner::Init(stream) => return unsafe { Pin::new_unchecked(stream) }.poll_next(cx),
+                Inner::Poisoned => poisoned()
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S: FusedStream, F: FnOnce() -> S> FusedStream for LazyStream<S, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+impl<S: FusedStream, F: IntoFuture<Output = S>> FusedStream for AsyncLazyStream<S, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+impl<S, F> core::fmt::Debug for LazyStream<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LazyStream").field("initialized", &self.is_initialized()).finish_non_exhaustive()
+    }
+}
+
+impl<S, F: IntoFuture> core::fmt::Debug for AsyncLazyStream<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncLazyStream").field("initialized", &self.is_initialized()).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,116 @@
+use core::{future::IntoFuture, time::Duration};
+use super::AsyncCached;
+
+/// Source of time for an [`AsyncTimedLazy`], as the time elapsed since an arbitrary, fixed epoch.
+/// It must never go backwards. Closures returning a [`Duration`] are clocks too, so any tick counter can be used
+pub trait Clock {
+    /// Returns the time elapsed since the clock's epoch
+    fn now (&self) -> Duration;
+}
+
+impl<G: ?Sized + Fn() -> Duration> Clock for G {
+    #[inline(always)]
+    fn now (&self) -> Duration {
+        self()
+    }
+}
+
+/// [`Clock`] backed by [`std::time::Instant`], whose epoch is the first time any ```StdClock``` is read
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdClock;
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    #[inline]
+    fn now (&self) -> Duration {
+        #[cfg(not(loom))]
+        static EPOCH: crate::Lazy<std::time::Instant> = crate::Lazy::new(std::time::Instant::now);
+        // loom's atomics only live for one of its executions, so they can't be in a `static`
+        #[cfg(loom)]
+        static EPOCH: std::sync::LazyLock<std::time::Instant> = std::sync::LazyLock::new(std::time::Instant::now);
+        EPOCH.elapsed()
+    }
+}
+
+/// An async value that expires after some time, and is computed again once it's accessed after that.
+///
+/// Like with [`AsyncCached`], concurrent accesses to an expired value share a single recomputation, and values are
+/// handed out by cloning them
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct AsyncTimedLazy<T, C, K> {
+    /// The value and the time it was computed at
+    cached: AsyncCached<(T, Duration), C>,
+    ttl: Duration,
+    clock: K
+}
+
+impl<T, C, K: Clock> AsyncTimedLazy<T, C, K> {
+    loom_const! {
+        /// Builds a new ```AsyncTimedLazy``` value, whose value is computed by the futures built by `make`, and expires
+        /// once it's older than `ttl`, as told by `clock`
+        #[inline(always)]
+        pub const fn new (ttl: Duration, clock: K, make: C) -> Self {
+            Self { cached: AsyncCached::new(make), ttl, clock }
+        }
+    }
+
+    /// Returns how long values last before expiring
+    #[inline(always)]
+    pub fn ttl (&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns a clone of the value if it's computed and hasn't expired, ```None``` otherwise
+    #[inline]
+    pub fn try_get (&self) -> Option<T> where T: Clone {
+        self.try_fresh().map(|(value, _)| value)
+    }
+
+    /// Expires the value now, dropping it, so that the next access computes it again (see [`AsyncCached::invalidate`])
+    #[inline(always)]
+    pub fn invalidate (&mut self) {
+        self.cached.invalidate()
+    }
+
+    /// Expires the value now, through a shared reference (see [`AsyncCached::invalidate_shared`])
+    #[inline(always)]
+    pub fn invalidate_shared (&self) {
+        self.cached.invalidate_shared()
+    }
+
+    /// Returns the value if it hasn't expired, marking it as expired otherwise
+    #[inline]
+    fn try_fresh (&self) -> Option<(T, Duration)> where T: Clone {
+        let now = self.clock.now();
+        self.cached.try_get_or_expire(|(_, at)| now.saturating_sub(*at) >= self.ttl)
+    }
+}
+
+impl<T: Clone, C: FnMut() -> F, F: IntoFuture<Output = T>, K: Clock> AsyncTimedLazy<T, C, K> {
+    /// Returns a clone of the value, computing it (or waiting for it to be computed) if it isn't computed or has expired.
+    /// The age of the value is counted from the moment its computation finishes
+    ///
+    /// # Panics
+    ///
+    /// Panics if the computation panics (or panicked before), poisoning the value until it's [invalidated](AsyncTimedLazy::invalidate)
+    pub async fn get (&self) -> T {
+        if let Some((value, _)) = self.try_fresh() {
+            return value
+        }
+
+        let clock = &self.clock;
+        let (value, _) = self.cached.get_with(|make| {
+            let value = make().into_future();
+            async move { (value.await, clock.now()) }
+        }).await;
+        value
+    }
+}
+
+impl<T: core::fmt::Debug, C, K> core::fmt::Debug for AsyncTimedLazy<T, C, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncTimedLazy").field("valid", &self.cached.is_valid()).field("ttl", &self.ttl).finish_non_exhaustive()
+    }
+}
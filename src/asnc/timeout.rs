@@ -0,0 +1,75 @@
+use core::{fmt, future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}};
+use futures_core::FusedFuture;
+use super::{AsyncLazy, Get};
+
+/// Error returned by [`GetTimeout`] when its deadline finishes before the value is initialized
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline elapsed before the value was initialized")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Elapsed {}
+
+/// Future returned by [`AsyncLazy::get_timeout`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct GetTimeout<'a, T, F: IntoFuture, D> {
+    /// ```None``` once resolved
+    get: Option<Get<'a, T, F>>,
+    deadline: D
+}
+
+impl<'a, T, F: IntoFuture<Output = T>, D: Future> GetTimeout<'a, T, F, D> {
+    #[inline(always)]
+    pub(super) fn new (lazy: &'a AsyncLazy<T, F>, deadline: D) -> Self {
+        Self { get: Some(lazy.get()), deadline }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>, D: Future> Future for GetTimeout<'a, T, F, D> {
+    type Output = Result<&'a T, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // both fields are structurally pinned, and `get` is only ever dropped in place
+        let this = unsafe { self.get_unchecked_mut() };
+        let get = match &mut this.get {
+            Some(get) => unsafe { Pin::new_unchecked(get) },
+            None => panic!("`GetTimeout` polled after completion")
+        };
+
+        if let Poll::Ready(value) = get.poll(cx) {
+            this.get = None;
+            return Poll::Ready(Ok(value))
+        }
+
+        match unsafe { Pin::new_unchecked(&mut this.deadline) }.poll(cx) {
+            Poll::Ready(_) => {
+                // drop the `get` future right away, so that, if it was initializing, it hands the initialization future
+                // back to the cell (for someone else to resume) now, instead of whenever this future is dropped
+                this.get = None;
+                Poll::Ready(Err(Elapsed))
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<'a, T, F: IntoFuture<Output = T>, D: Future> FusedFuture for GetTimeout<'a, T, F, D> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.get.is_none()
+    }
+}
+
+impl<T, F: IntoFuture, D> fmt::Debug for GetTimeout<'_, T, F, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GetTimeout").finish_non_exhaustive()
+    }
+}
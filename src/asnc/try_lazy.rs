@@ -0,0 +1,126 @@
+use core::{future::{Future, IntoFuture}, pin::Pin, task::{Context, Poll}};
+use futures_core::FusedFuture;
+use super::{AsyncLazy, Get};
+
+/// A lazy value that initializes via a fallible future.
+///
+/// Whatever the future resolves to, success or failure, is cached: every caller waiting on the initialization (and
+/// every later one) sees the same value or error, without running the future again. To retry after a failure,
+/// [`reset`](AsyncTryLazy::reset) the cell with a new future
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct AsyncTryLazy<T, E, F> {
+    inner: AsyncLazy<Result<T, E>, F>
+}
+
+impl<T, E, F> AsyncTryLazy<T, E, F> {
+    loom_const! {
+        /// Builds a new ```AsyncTryLazy``` value
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self { inner: AsyncLazy::new(f) }
+        }
+    }
+
+    /// Returns ```Some(Ok(ref value))``` or ```Some(Err(ref error))``` if the initialization has already finished,
+    /// ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<Result<&T, &E>> {
+        self.inner.try_get().map(Result::as_ref)
+    }
+
+    /// Returns ```true``` if the initialization finished successfully, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_ok (&self) -> bool {
+        matches!(self.try_get(), Some(Ok(_)))
+    }
+
+    /// Returns ```true``` if the initialization finished with an error, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_err (&self) -> bool {
+        matches!(self.try_get(), Some(Err(_)))
+    }
+
+    /// Returns ```true``` if the initialization future panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    /// Drops the value, error (or initialization future) of the cell, and makes it uninitialized again, with `f` as
+    /// its new initialization future, so that the next access retries
+    #[inline(always)]
+    pub fn reset (&mut self, f: F) {
+        self.inner.reset(f)
+    }
+}
+
+impl<T, E, F: IntoFuture<Output = Result<T, E>>> AsyncTryLazy<T, E, F> {
+    /// Returns a reference to the inner value or error, initializing or waiting for it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`AsyncLazy::get`]
+    #[inline(always)]
+    pub fn get (&self) -> TryGet<'_, T, E, F> {
+        TryGet { inner: self.inner.get() }
+    }
+
+    /// Like [`get`](AsyncTryLazy::get), but returns a clone of the error
+    #[inline]
+    pub async fn get_cloned_err (&self) -> Result<&T, E> where E: Clone {
+        self.get().await.map_err(E::clone)
+    }
+
+    /// Returns the inner value or error, initializing it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`AsyncLazy::into_inner`]
+    #[inline(always)]
+    pub async fn into_inner (self) -> Result<T, E> {
+        self.inner.into_inner().await
+    }
+}
+
+/// Future returned by [`AsyncTryLazy::get`]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TryGet<'a, T, E, F: IntoFuture> {
+    inner: Get<'a, Result<T, E>, F>
+}
+
+impl<'a, T, E, F: IntoFuture<Output = Result<T, E>>> Future for TryGet<'a, T, E, F> {
+    type Output = Result<&'a T, &'a E>;
+
+    #[inline(always)]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `inner` is structurally pinned
+        let inner = unsafe { self.map_unchecked_mut(|this| &mut this.inner) };
+        inner.poll(cx).map(Result::as_ref)
+    }
+}
+
+impl<'a, T, E, F: IntoFuture<Output = Result<T, E>>> FusedFuture for TryGet<'a, T, E, F> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+impl<T, E, F: IntoFuture> core::fmt::Debug for TryGet<'_, T, E, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TryGet").finish_non_exhaustive()
+    }
+}
+
+impl<T: core::fmt::Debug, E: core::fmt::Debug, F> core::fmt::Debug for AsyncTryLazy<T, E, F> {
+    /// Prints the value or error if the initialization finished, or the state of the cell otherwise
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("AsyncTryLazy");
+        match self.try_get() {
+            Some(result) => tuple.field(&result),
+            None => tuple.field(&self.inner.state())
+        };
+        tuple.finish()
+    }
+}
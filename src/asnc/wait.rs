@@ -0,0 +1,64 @@
+use core::{cell::UnsafeCell, fmt, future::Future, mem::MaybeUninit, pin::Pin, task::{Context, Poll}};
+use futures_core::FusedFuture;
+use crate::{INIT, utils::AwaitInit};
+use super::settled;
+
+/// Future returned by [`AsyncLazy::wait`](super::AsyncLazy::wait)
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Wait<'a, T> {
+    value: &'a UnsafeCell<MaybeUninit<T>>,
+    waiting: AwaitInit<'a>,
+    done: bool
+}
+
+impl<'a, T> Wait<'a, T> {
+    #[inline(always)]
+    pub(super) const fn new (value: &'a UnsafeCell<MaybeUninit<T>>, waiting: AwaitInit<'a>) -> Self {
+        Self { value, waiting, done: false }
+    }
+}
+
+impl<'a, T> Future for Wait<'a, T> {
+    type Output = &'a T;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // `waiting` is structurally pinned
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.done {
+            panic!("`Wait` polled after completion")
+        }
+
+        match unsafe { Pin::new_unchecked(&mut this.waiting) }.poll(cx) {
+            Poll::Ready(state) => {
+                this.done = true;
+                match state {
+                    INIT => unsafe { Poll::Ready((*this.value.get()).assume_init_ref()) },
+                    state => {
+                        settled(state);
+                        unreachable!()
+                    }
+                }
+            },
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<T> FusedFuture for Wait<'_, T> {
+    #[inline(always)]
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<T> fmt::Debug for Wait<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Wait").finish_non_exhaustive()
+    }
+}
+
+// only hands out `&T`, like `&AsyncLazy` (which also requires `T: Send` to be shared)
+unsafe impl<T: Send + Sync> Send for Wait<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for Wait<'_, T> {}
@@ -0,0 +1,44 @@
+//! Lazy values with type-erased initializers
+
+use alloc::boxed::Box;
+use crate::Lazy;
+
+/// A [`Lazy`] whose initializer is boxed, so that its type can be written down (e.g. in a struct field) even when it's
+/// a capturing closure. Built by [`Lazy::boxed`]
+///
+/// ```rust
+/// use laizy::{Lazy, BoxedLazy};
+///
+/// struct Report {
+///     summary: BoxedLazy<String>
+/// }
+///
+/// let rows = vec![3, 1, 2];
+/// let report = Report { summary: Lazy::boxed(move || format!("{} rows", rows.len())) };
+/// assert_eq!(report.summary.get(), "3 rows");
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub type BoxedLazy<T> = Lazy<T, Box<dyn FnOnce() -> T + Send>>;
+
+/// Like [`BoxedLazy`], but its initializer needn't be ```Send```, so it's neither ```Send``` nor ```Sync```. Built by
+/// [`Lazy::boxed_local`]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub type LocalBoxedLazy<T> = Lazy<T, Box<dyn FnOnce() -> T>>;
+
+impl<T> Lazy<T, Box<dyn FnOnce() -> T + Send>> {
+    /// Builds a new ```BoxedLazy``` value
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[inline(always)]
+    pub fn boxed (f: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self::new(Box::new(f))
+    }
+}
+
+impl<T> Lazy<T, Box<dyn FnOnce() -> T>> {
+    /// Builds a new ```LocalBoxedLazy``` value
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[inline(always)]
+    pub fn boxed_local (f: impl FnOnce() -> T + 'static) -> Self {
+        Self::new(Box::new(f))
+    }
+}
@@ -0,0 +1,79 @@
+//! Lazy values without any drop glue
+
+use core::{mem::ManuallyDrop, ops::{Deref, DerefMut}};
+use crate::{Lazy, policy::Repanic, strategy::{WaitStrategy, DefaultStrategy}};
+
+/// A [`Lazy`] whose value and initializer are both ```Copy```, and so have nothing to drop.
+///
+/// Unlike ```Lazy```, it doesn't implement ```Drop```, so it can be dropped in ```const``` contexts, and it may hold references
+/// that dangle by the time it goes out of scope. The only thing it may leak is the panic message of a poisoned value (under ```std```).
+///
+/// Besides construction, it's used exactly like the inner cell, which it dereferences to
+#[derive(Debug)]
+pub struct CopyLazy<T: Copy, F: Copy = fn() -> T, P = Repanic, S: WaitStrategy = DefaultStrategy> {
+    inner: ManuallyDrop<Lazy<T, F, P, S>>
+}
+
+impl<T: Copy, F: Copy> CopyLazy<T, F> {
+    loom_const! {
+        /// Builds a new ```CopyLazy``` value
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self { inner: ManuallyDrop::new(Lazy::new(f)) }
+        }
+    }
+}
+
+impl<T: Copy, F: Copy, P, S: WaitStrategy> CopyLazy<T, F, P, S> {
+    loom_const! {
+        /// Builds a new ```CopyLazy``` value with a custom [`PoisonPolicy`](crate::policy::PoisonPolicy) and/or [`WaitStrategy`]
+        #[inline(always)]
+        pub const fn with_policy (f: F) -> Self {
+            Self { inner: ManuallyDrop::new(Lazy::with_policy(f)) }
+        }
+    }
+
+    loom_const! {
+        /// Builds a ```CopyLazy``` value that's already initialized
+        #[inline(always)]
+        pub const fn init (value: T) -> Self {
+            Self { inner: ManuallyDrop::new(Lazy::init(value)) }
+        }
+    }
+
+    /// Returns the inner cell
+    #[inline(always)]
+    pub fn into_lazy (self) -> Lazy<T, F, P, S> {
+        ManuallyDrop::into_inner(self.inner)
+    }
+}
+
+impl<T: Copy, F: Copy, P, S: WaitStrategy> Deref for CopyLazy<T, F, P, S> {
+    type Target = Lazy<T, F, P, S>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: Copy, F: Copy, P, S: WaitStrategy> DerefMut for CopyLazy<T, F, P, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<T: Copy + Default> Default for CopyLazy<T, fn() -> T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(Default::default)
+    }
+}
+
+impl<T: Copy, F: Copy, P, S: WaitStrategy> From<T> for CopyLazy<T, F, P, S> {
+    #[inline(always)]
+    fn from(x: T) -> Self {
+        Self::init(x)
+    }
+}
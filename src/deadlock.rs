@@ -0,0 +1,118 @@
+//! Detection of initializations that wait on each other (only with ```std``` and debug assertions)
+
+use core::fmt::Write;
+use crate::sync::AtomicU8;
+use std::{string::String, sync::{Mutex, PoisonError}, thread::{self, ThreadId}, vec::Vec};
+
+/// Wait-graph of the running initializations
+static GRAPH: Mutex<Graph> = Mutex::new(Graph { owners: Vec::new(), waiters: Vec::new() });
+
+struct Graph {
+    /// Cells currently initializing, the name of their type, and the thread initializing them
+    owners: Vec<(usize, &'static str, ThreadId)>,
+    /// Threads currently waiting on a cell
+    waiters: Vec<(ThreadId, usize)>
+}
+
+impl Graph {
+    #[inline]
+    fn owner (&self, key: usize) -> Option<(&'static str, ThreadId)> {
+        self.owners.iter().find(|(k, ..)| *k == key).map(|&(_, name, thread)| (name, thread))
+    }
+
+    #[inline]
+    fn waiting_on (&self, thread: ThreadId) -> Option<usize> {
+        self.waiters.iter().find(|(t, _)| *t == thread).map(|&(_, key)| key)
+    }
+}
+
+#[inline(always)]
+fn key (state: &AtomicU8) -> usize {
+    state as *const AtomicU8 as usize
+}
+
+/// Marks the current thread as the one initializing a cell while it's alive
+pub(crate) struct Owner {
+    key: usize
+}
+
+impl Owner {
+    #[inline]
+    pub fn start (state: &AtomicU8, name: &'static str) -> Self {
+        let key = key(state);
+        GRAPH.lock().unwrap_or_else(PoisonError::into_inner).owners.push((key, name, thread::current().id()));
+        Self { key }
+    }
+}
+
+impl Drop for Owner {
+    #[inline]
+    fn drop(&mut self) {
+        let mut graph = GRAPH.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(idx) = graph.owners.iter().position(|(k, ..)| *k == self.key) {
+            graph.owners.swap_remove(idx);
+        }
+    }
+}
+
+/// Marks the current thread as waiting on a cell while it's alive
+pub(crate) struct Waiter {
+    thread: ThreadId
+}
+
+impl Waiter {
+    /// # Panics
+    ///
+    /// Panics if waiting on the cell would deadlock (i.e. the cell is being initialized by a thread that's, maybe transitively,
+    /// waiting for a cell this thread is initializing)
+    #[inline]
+    pub fn start (state: &AtomicU8, name: &'static str) -> Self {
+        let current = thread::current().id();
+        let waited = key(state);
+        let mut graph = GRAPH.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // follow the chain of owners and the cells they wait on, looking for ourselves
+        let mut cycle: Vec<(&'static str, ThreadId)> = Vec::new();
+        let (mut name, mut key) = (name, waited);
+        while let Some((_, owner)) = graph.owner(key) {
+            cycle.push((name, owner));
+            if owner == current {
+                drop(graph);
+                deadlocked(current, &cycle)
+            }
+
+            match graph.waiting_on(owner).and_then(|next| Some((next, graph.owner(next)?.0))) {
+                Some((next, next_name)) if cycle.len() <= graph.owners.len() => (key, name) = (next, next_name),
+                _ => break
+            }
+        }
+
+        graph.waiters.push((current, waited));
+        Self { thread: current }
+    }
+}
+
+impl Drop for Waiter {
+    #[inline]
+    fn drop(&mut self) {
+        let mut graph = GRAPH.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(idx) = graph.waiters.iter().position(|(t, _)| *t == self.thread) {
+            graph.waiters.swap_remove(idx);
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn deadlocked (current: ThreadId, cycle: &[(&'static str, ThreadId)]) -> ! {
+    let mut msg = String::new();
+    let _ = write!(msg, "{current:?} waits on `{}`", cycle[0].0);
+    for (i, &(_, owner)) in cycle.iter().enumerate() {
+        let _ = match cycle.get(i + 1) {
+            Some(&(next, _)) => write!(msg, ", initialized by {owner:?}, which waits on `{next}`"),
+            None => write!(msg, ", initialized by {owner:?} (this thread)")
+        };
+    }
+
+    panic!("Lazy initialization deadlock: {msg}")
+}
@@ -0,0 +1,42 @@
+//! Formatting of lazy values with ```defmt```, for embedded logging
+
+use defmt::{Format, Formatter, write};
+use crate::{Lazy, strategy::WaitStrategy};
+
+/// Formats the state of the cell (```Uninit```, ```Initializing```, ```Poisoned```), or ```Init``` alongside the value
+/// if it's initialized, without ever initializing it
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+impl<T: Format, F, P, S: WaitStrategy> Format for Lazy<T, F, P, S> {
+    fn format(&self, f: Formatter<'_>) {
+        // the value is only read once its initialization has been observed, with `Acquire` ordering
+        match self.try_get() {
+            Some(value) => write!(f, "Init({})", value),
+            None if self.is_initializing() => write!(f, "Initializing"),
+            None if self.is_poisoned() => write!(f, "Poisoned"),
+            None => write!(f, "Uninit")
+        }
+    }
+}
+
+/// Formats the state of the cell (```Uninit```, ```Initializing```, ```Poisoned```, ```Cancelled```, ```Taken```), or
+/// ```Init``` alongside the value if it's initialized, without ever polling the future
+#[cfg_attr(docsrs, doc(cfg(all(feature = "defmt", feature = "futures"))))]
+#[cfg(feature = "futures")]
+impl<T: Format, F> Format for crate::AsyncLazy<T, F> {
+    fn format(&self, f: Formatter<'_>) {
+        use crate::State;
+
+        match self.try_get() {
+            Some(value) => write!(f, "Init({})", value),
+            None => match self.state() {
+                State::Uninit => write!(f, "Uninit"),
+                State::Initializing => write!(f, "Initializing"),
+                State::Poisoned => write!(f, "Poisoned"),
+                State::Cancelled => write!(f, "Cancelled"),
+                State::Taken => write!(f, "Taken"),
+                // it finished initializing after `try_get` (and stays initialized while borrowed), so this time `try_get` finds it
+                State::Initialized => self.format(f)
+            }
+        }
+    }
+}
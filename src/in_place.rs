@@ -0,0 +1,111 @@
+//! Lazy values initialized directly inside of their cell
+
+use core::{mem::MaybeUninit, ops::{Deref, DerefMut}, ptr::addr_of_mut};
+use crate::{Lazy, Slot, Initializer, initialize_erased, INIT, policy::{PoisonPolicy, Repanic}, strategy::WaitStrategy};
+
+#[cfg(feature = "std")]
+use crate::utils::catch_payload;
+
+/// Initializer of a [`Lazy`] that writes the value directly into the cell's storage, instead of returning it.
+/// Built by [`Lazy::new_in_place`]
+#[derive(Debug, Clone, Copy)]
+pub struct InPlace<G> {
+    f: G
+}
+
+impl<T, G> Lazy<T, InPlace<G>> {
+    loom_const! {
+        /// Builds a new ```Lazy``` value whose initialization function writes the value in place, so that it's never
+        /// moved after being built (useful for values too large for the stack).
+        ///
+        /// If the initialization function panics midway, the value is poisoned, and whatever it wrote is leaked.
+        ///
+        /// # Safety
+        ///
+        /// `f` must fully initialize the value it's handed whenever it returns
+        #[inline(always)]
+        pub const unsafe fn new_in_place (f: G) -> Self {
+            Self::with_policy(InPlace { f })
+        }
+    }
+}
+
+impl<T, G: FnOnce(&mut MaybeUninit<T>), S: WaitStrategy> Lazy<T, InPlace<G>, Repanic, S> {
+    /// Returns a reference to the inner value, initializing or waiting for it of necesary
+    #[inline(always)]
+    pub fn get (&self) -> &T {
+        match self.state.load(core::sync::atomic::Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                (*self.value()).assume_init_ref()
+            },
+            _ => self.initialize_slow()
+        }
+    }
+
+    /// Returns a mutable reference to the inner value, initializing or waiting for it of necesary
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> &mut T {
+        match self.state.load(core::sync::atomic::Ordering::Acquire) {
+            INIT => unsafe { (*self.value()).assume_init_mut() },
+            _ => self.initialize_slow_mut()
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow (&self) -> &T {
+        unsafe {
+            initialize_erased(&self.state, &self.track, self.slot.get().cast(), core::ptr::null_mut(), Self::INITIALIZER);
+            (*self.value()).assume_init_ref()
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow_mut (&mut self) -> &mut T {
+        unsafe {
+            initialize_erased(&self.state, &self.track, self.slot.get().cast(), core::ptr::null_mut(), Self::INITIALIZER);
+            (*self.value()).assume_init_mut()
+        }
+    }
+
+    const INITIALIZER: Initializer = Initializer {
+        init: init_in_place::<T, G>,
+        poison: <Repanic as PoisonPolicy<InPlace<G>>>::poison,
+        wait: S::wait,
+        notify: S::notify,
+        #[cfg(feature = "std")]
+        name: core::any::type_name::<T>
+    };
+}
+
+impl<T, G: FnOnce(&mut MaybeUninit<T>), S: WaitStrategy> Deref for Lazy<T, InPlace<G>, Repanic, S> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T, G: FnOnce(&mut MaybeUninit<T>), S: WaitStrategy> DerefMut for Lazy<T, InPlace<G>, Repanic, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.get_mut()
+    }
+}
+
+/// Moves the in-place initializer out of `slot` (a ```Slot<T, InPlace<G>>```), and runs it on the storage it leaves behind
+unsafe fn init_in_place<T, G: FnOnce(&mut MaybeUninit<T>)> (slot: *mut u8, _: *mut u8) -> u8 {
+    let slot = slot.cast::<Slot<T, InPlace<G>>>();
+    let InPlace { f } = core::ptr::read(addr_of_mut!((*slot).f).cast::<MaybeUninit<InPlace<G>>>()).assume_init();
+    let value = &mut *addr_of_mut!((*slot).value).cast::<MaybeUninit<T>>();
+
+    // if `f` panics, the payload (if any) overwrites the partially written value, which is never read again
+    #[cfg(feature = "std")]
+    catch_payload(|| f(value), addr_of_mut!((*slot).payload).cast());
+    #[cfg(not(feature = "std"))]
+    f(value);
+    INIT
+}
@@ -0,0 +1,67 @@
+//! Conversions into lazy values, from either a value or an initializer
+
+use crate::Lazy;
+
+/// Conversion into a [`Lazy`], so that generic constructors can accept either a value or a function that initializes it.
+///
+/// A blanket implementation for every ```FnOnce() -> T``` would overlap with one for every value (a closure is a value
+/// too), so each form is wrapped instead: [`ByValue`] for values that are already built, and [`ByFn`] for initializers.
+/// A ```Lazy``` converts into itself
+///
+/// ```rust
+/// use laizy::{IntoLazy, ByValue, ByFn};
+///
+/// fn greeting (name: impl IntoLazy<Item = String>) -> String {
+///     format!("hello, {}", name.into_lazy().get())
+/// }
+///
+/// assert_eq!(greeting(ByValue(String::from("laizy"))), "hello, laizy");
+/// assert_eq!(greeting(ByFn(|| String::from("world"))), "hello, world");
+/// ```
+pub trait IntoLazy {
+    /// Type of the lazy value
+    type Item;
+    /// Initializer of the lazy value
+    type Init: FnOnce() -> Self::Item;
+
+    /// Converts itself into a ```Lazy``` value
+    fn into_lazy (self) -> Lazy<Self::Item, Self::Init>;
+}
+
+/// A value that's already built, converted into an initialized [`Lazy`] by [`IntoLazy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ByValue<T>(pub T);
+
+/// A function that initializes a value, converted into an uninitialized [`Lazy`] by [`IntoLazy`]
+#[derive(Debug, Clone, Copy)]
+pub struct ByFn<F>(pub F);
+
+impl<T> IntoLazy for ByValue<T> {
+    type Item = T;
+    type Init = fn() -> T;
+
+    #[inline(always)]
+    fn into_lazy (self) -> Lazy<T> {
+        Lazy::init(self.0)
+    }
+}
+
+impl<T, F: FnOnce() -> T> IntoLazy for ByFn<F> {
+    type Item = T;
+    type Init = F;
+
+    #[inline(always)]
+    fn into_lazy (self) -> Lazy<T, F> {
+        Lazy::from_fn(self.0)
+    }
+}
+
+impl<T, F: FnOnce() -> T> IntoLazy for Lazy<T, F> {
+    type Item = T;
+    type Init = F;
+
+    #[inline(always)]
+    fn into_lazy (self) -> Lazy<T, F> {
+        self
+    }
+}
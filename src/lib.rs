@@ -2,12 +2,65 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[macro_use]
+mod sync;
 mod utils;
-use core::{sync::atomic::{Ordering, AtomicU8}, mem::{MaybeUninit, ManuallyDrop}, cell::{UnsafeCell}, ops::{Deref, DerefMut}};
+mod state;
+pub mod policy;
+pub mod strategy;
+pub mod padded;
+pub mod unsync;
+pub mod race;
+mod in_place;
+mod copy;
+mod try_lazy;
+mod once;
+mod into_lazy;
+#[cfg(feature = "defmt")]
+mod defmt_fmt;
+#[cfg(feature = "alloc")]
+mod arc;
+#[cfg(feature = "alloc")]
+mod boxed;
+pub use in_place::InPlace;
+pub use copy::CopyLazy;
+pub use try_lazy::TryLazy;
+pub use once::{OnceCell, Once, OnceState};
+pub use into_lazy::{IntoLazy, ByValue, ByFn};
+#[cfg(feature = "alloc")]
+pub use arc::{LazyArc, WeakLazy};
+#[cfg(feature = "alloc")]
+pub use boxed::{BoxedLazy, LocalBoxedLazy};
+use utils::PoisonGuard;
+use state::{UNINIT, INITIALIZING, INIT, POISONED, FAILED};
+#[cfg(feature = "futures")]
+use state::{CANCELLED, PARKED, TAKEN, ABORTING, SUSPENDED, STALE};
+#[cfg(feature = "futures")]
+pub use state::State;
+use policy::{PoisonPolicy, Repanic};
+use strategy::{WaitStrategy, DefaultStrategy};
+use sync::{AtomicU8, Track};
+use core::{sync::atomic::Ordering, mem::{MaybeUninit, ManuallyDrop}, cell::{UnsafeCell}, ops::{Deref, DerefMut}, marker::PhantomData, ptr::addr_of_mut};
+use core::{future::Future, pin::Pin, task::{Context, Poll}};
 
 #[cfg(not(debug_assertions))]
 use core::hint::unreachable_unchecked;
 
+#[cfg(feature = "std")]
+mod watchdog;
+#[cfg(all(feature = "std", debug_assertions))]
+mod deadlock;
+#[cfg(feature = "std")]
+pub use watchdog::{set_slow_init_hook, clear_slow_init_hook};
+
+#[cfg(feature = "std")]
+use {core::any::Any, std::boxed::Box, utils::{Payload, catch_payload, panic_message}};
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "futures")] {
         mod asnc;
@@ -17,37 +70,78 @@ cfg_if::cfg_if! {
 
 /// The lazy type.
 /// Lazy values aren't initialized until requested by some part of the program. 
-/// When requested, ```Lazy``` will initialize the value and return a reference to it
+/// When requested, ```Lazy``` will initialize the value and return a reference to it.
+///
+/// What happens when the initialization function panics is decided by the [`PoisonPolicy`] ```P```,
+/// and how threads wait for someone else's initialization by the [`WaitStrategy`] ```S```
+///
+/// # Variance
+///
+/// ```Lazy``` is invariant over both ```T``` and ```F```, since they are stored (and written through ```&self```) inside an ```UnsafeCell```,
+/// so a ```Lazy<&'static str>``` can't be used where a ```Lazy<&'a str>``` is expected. Read the value out instead (```&'a str``` from ```*lazy.get()```)
 #[derive(Debug)]
-pub struct Lazy<T, F = fn() -> T> {
+pub struct Lazy<T, F = fn() -> T, P = Repanic, S: WaitStrategy = DefaultStrategy> {
     state: AtomicU8,
-    value: UnsafeCell<MaybeUninit<T>>,
-    f: UnsafeCell<MaybeUninit<F>>
+    slot: UnsafeCell<Slot<T, F>>,
+    track: Track,
+    policy: PhantomData<(P, S)>
 }
 
-// Values that `Lazy::state` can be
-const UNINIT: u8 = 0;
-const INITIALIZING: u8 = 1;
-const INIT: u8 = 2;
+/// Storage of a ```Lazy```. At most one of its fields is alive at a time, as indicated by the state:
+/// the initializer while ```UNINIT```, the value once ```INIT```, and (under ```std```) the panic message once ```POISONED```.
+///
+/// ```repr(C)``` places every field at the start of the slot, so the payload can be found without knowing ```T``` or ```F```
+#[repr(C)]
+union Slot<T, F> {
+    value: ManuallyDrop<MaybeUninit<T>>,
+    f: ManuallyDrop<MaybeUninit<F>>,
+    #[cfg(feature = "std")]
+    payload: ManuallyDrop<Payload>
+}
 
 impl<T, F> Lazy<T, F> {
-    /// Builds a new ```Lazy``` value
-    #[inline(always)]
-    pub const fn new (f: F) -> Self {
-        Self {
-            state: AtomicU8::new(UNINIT),
-            value: UnsafeCell::new(MaybeUninit::uninit()),
-            f: UnsafeCell::new(MaybeUninit::new(f))
+    loom_const! {
+        /// Builds a new ```Lazy``` value
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self::with_policy(f)
         }
     }
 
-    /// Builds a ```Lazy``` value that's already initialized
-    #[inline(always)]
-    pub const fn init (value: T) -> Self {
-        Self {
-            state: AtomicU8::new(INIT),
-            value: UnsafeCell::new(MaybeUninit::new(value)),
-            f: UnsafeCell::new(MaybeUninit::uninit())
+    loom_const! {
+        /// Builds a new ```Lazy``` value from its initializer. The same as [`new`](Lazy::new), as a counterpart of
+        /// ```Lazy::from(value)``` (there's no ```From<F>```, since it would overlap with it). See [`IntoLazy`] to accept either
+        #[inline(always)]
+        pub const fn from_fn (f: F) -> Self {
+            Self::new(f)
+        }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Lazy<T, F, P, S> {
+    loom_const! {
+        /// Builds a new ```Lazy``` value with a custom [`PoisonPolicy`] and/or [`WaitStrategy`]
+        #[inline(always)]
+        pub const fn with_policy (f: F) -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                slot: UnsafeCell::new(Slot { f: ManuallyDrop::new(MaybeUninit::new(f)) }),
+                track: Track::new(),
+                policy: PhantomData
+            }
+        }
+    }
+
+    loom_const! {
+        /// Builds a ```Lazy``` value that's already initialized
+        #[inline(always)]
+        pub const fn init (value: T) -> Self {
+            Self {
+                state: AtomicU8::new(INIT),
+                slot: UnsafeCell::new(Slot { value: ManuallyDrop::new(MaybeUninit::new(value)) }),
+                track: Track::new(),
+                policy: PhantomData
+            }
         }
     }
 
@@ -59,120 +153,195 @@ impl<T, F> Lazy<T, F> {
     
     /// Returns ```true``` if the value is currently initializing, ```false``` otherwise
     #[inline(always)]
-    pub fn is_init (&self) -> bool {
+    pub fn is_initializing (&self) -> bool {
         self.state.load(Ordering::Acquire) == INITIALIZING
     }
     
     /// Returns ```true``` if the value has already initialized, ```false``` otherwise
     #[inline(always)]
-    pub fn has_init (&self) -> bool {
+    pub fn is_initialized (&self) -> bool {
         self.state.load(Ordering::Acquire) == INIT
     }
-}
 
-impl<T, F: FnOnce() -> T> Lazy<T, F> {
-    /// Returns a reference to the inner value, initializing or waiting for it of necesary
+    /// Returns ```true``` if the value is currently initializing, ```false``` otherwise
+    #[deprecated(note = "easily mistaken for `is_initialized`, use `is_initializing` instead")]
     #[inline(always)]
-    pub fn get (&self) -> &T {
-        match self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed) {
-            // uninitialized
-            Ok(UNINIT) => unsafe {
-                let f = core::mem::replace(&mut *self.f.get(), MaybeUninit::uninit());
-                (&mut *self.value.get()).write((f.assume_init())());
-
-                #[cfg(debug_assertions)]
-                assert_eq!(self.state.swap(INIT, Ordering::Release), INITIALIZING);
-                #[cfg(not(debug_assertions))]
-                self.state.store(INIT, Ordering::Release);
-            },
-
-            // currently initializing
-            Err(INITIALIZING) => while self.state.load(Ordering::Acquire) == INITIALIZING { core::hint::spin_loop() },
+    pub fn is_init (&self) -> bool {
+        self.is_initializing()
+    }
+    
+    /// Returns ```true``` if the value has already initialized, ```false``` otherwise
+    #[deprecated(note = "use `is_initialized` instead")]
+    #[inline(always)]
+    pub fn has_init (&self) -> bool {
+        self.is_initialized()
+    }
 
-            // initialized
-            Err(INIT) => {},
+    /// Returns ```true``` if the initialization function panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state.load(Ordering::Acquire) == POISONED
+    }
 
-            #[cfg(debug_assertions)]
-            _ => unreachable!(),
-            #[cfg(not(debug_assertions))]
-            _ => unsafe { unreachable_unchecked() }
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                Some((*self.value()).assume_init_ref())
+            },
+            _ => None
         }
-
-        unsafe { (&*self.value.get()).assume_init_ref() }
     }
 
-    /// Returns a mutable reference to the inner value, initializing or waiting for it of necesary
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
     #[inline(always)]
-    pub fn get_mut (&mut self) -> &mut T {
-        match self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed) {
-            // uninitialized
-            Ok(UNINIT) => unsafe {
-                let f = core::mem::replace(&mut *self.f.get(), MaybeUninit::uninit());
-                self.value.get_mut().write((f.assume_init())());
+    pub fn try_get_mut (&mut self) -> Option<&mut T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe { Some((*self.value()).assume_init_mut()) }
+            _ => None
+        }
+    }
 
-                #[cfg(debug_assertions)]
-                assert_eq!(self.state.swap(INIT, Ordering::Release), INITIALIZING);
-                #[cfg(not(debug_assertions))]
-                self.state.store(INIT, Ordering::Release);
-            },
+    /// Returns the message of the initialization function's panic if the value is poisoned, ```None``` otherwise.
+    ///
+    /// The cell keeps a copy of panic messages (```&'static str``` and ```String``` payloads, as produced by ```panic!```),
+    /// so other payloads only reach the caller that ran the initialization function, whose panic is resumed as is
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    pub fn poison_payload (&self) -> Option<&(dyn Any + Send + Sync)> {
+        if !self.is_poisoned() { return None }
+
+        self.track.read();
+        let payload = unsafe { (*self.payload()).as_deref()? };
+        if let Some(msg) = payload.downcast_ref::<&'static str>() {
+            return Some(msg)
+        }
+        payload.downcast_ref::<std::string::String>().map(|msg| msg as _)
+    }
 
-            // currently initializing
-            Err(INITIALIZING) => while self.state.load(Ordering::Acquire) == INITIALIZING { core::hint::spin_loop() },
+    /// Removes and returns the message of the initialization function's panic if the value is poisoned, ```None``` otherwise
+    /// (see [`poison_payload`](Lazy::poison_payload))
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    pub fn take_poison_payload (&mut self) -> Option<Box<dyn Any + Send>> {
+        let state = *self.state.get_mut();
+        match state {
+            POISONED => unsafe { (*self.payload()).take() },
+            _ => None
+        }
+    }
 
-            // initialized
-            Err(INIT) => {},
+    /// Pointer to the value's storage
+    #[inline(always)]
+    fn value (&self) -> *mut MaybeUninit<T> {
+        unsafe { addr_of_mut!((*self.slot.get()).value).cast() }
+    }
 
-            #[cfg(debug_assertions)]
-            _ => unreachable!(),
-            #[cfg(not(debug_assertions))]
-            _ => unsafe { unreachable_unchecked() }
-        }
+    /// Pointer to the initializer's storage
+    #[inline(always)]
+    fn f (&self) -> *mut MaybeUninit<F> {
+        unsafe { addr_of_mut!((*self.slot.get()).f).cast() }
+    }
 
-        unsafe { self.value.get_mut().assume_init_mut() }
+    /// Pointer to the panic payload's storage, only initialized while ```POISONED```
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn payload (&self) -> *mut Payload {
+        unsafe { addr_of_mut!((*self.slot.get()).payload).cast() }
     }
+}
 
-    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+impl<T, F: FnOnce() -> T, P: PoisonPolicy<F>, S: WaitStrategy> Lazy<T, F, P, S> {
+    /// Returns a reference to the inner value, initializing or waiting for it of necesary
     #[inline(always)]
-    pub fn try_get (&self) -> Option<&T> {
+    pub fn get (&self) -> &T {
+        // fast path: the `Acquire` load synchronizes with the `Release` store that finished the initialization
         match self.state.load(Ordering::Acquire) {
-            INIT => unsafe { Some((&*self.value.get()).assume_init_ref()) }
-            _ => None
+            INIT => unsafe {
+                self.track.read();
+                (*self.value()).assume_init_ref()
+            },
+            _ => self.initialize_slow()
         }
     }
 
-    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    /// Returns a mutable reference to the inner value, initializing or waiting for it of necesary
     #[inline(always)]
-    pub fn try_get_mut (&mut self) -> Option<&mut T> {
+    pub fn get_mut (&mut self) -> &mut T {
         match self.state.load(Ordering::Acquire) {
-            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) }
-            _ => None
+            INIT => unsafe { (*self.value()).assume_init_mut() },
+            _ => self.initialize_slow_mut()
+        }
+    }
+
+    /// Slow path of [`get`](Lazy::get): initializes the value, or waits for it to be initialized
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow (&self) -> &T {
+        unsafe {
+            initialize_erased(&self.state, &self.track, self.slot.get().cast(), core::ptr::null_mut(), Self::INITIALIZER);
+            (*self.value()).assume_init_ref()
         }
     }
 
+    /// Slow path of [`get_mut`](Lazy::get_mut)
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow_mut (&mut self) -> &mut T {
+        unsafe {
+            initialize_erased(&self.state, &self.track, self.slot.get().cast(), core::ptr::null_mut(), Self::INITIALIZER);
+            (*self.value()).assume_init_mut()
+        }
+    }
+
+    const INITIALIZER: Initializer = Initializer {
+        init: init_slot::<T, F, P>,
+        poison: P::poison,
+        wait: S::wait,
+        notify: S::notify,
+        #[cfg(feature = "std")]
+        name: core::any::type_name::<T>
+    };
+
     /// Returns the inner value, initializing it if necessary
     ///
     /// # Panics
     ///
-    /// Panics if the initialization function ran, but panicked.
+    /// Panics if the initialization function ran, but panicked (the value is poisoned).
     #[inline(always)]
     pub fn into_inner (self) -> T {
-        let mut this = ManuallyDrop::new(self);
+        let this = ManuallyDrop::new(self);
 
-        match this.state.load(Ordering::Relaxed) {
+        // `Acquire` pairs with the `Release` store that finished the initialization: whatever handed us
+        // ownership (a channel, a join, ...) should already synchronize with it, but we don't rely on that
+        let state = this.state.load(Ordering::Acquire);
+        this.track.write();
+
+        match state {
             // uninit (init value)
             UNINIT => unsafe { 
-                let f = core::mem::replace(this.f.get_mut(), MaybeUninit::uninit()).assume_init();
+                let f = core::ptr::read(this.f()).assume_init();
                 f()
             },
 
-            // initializing (happens if initialization panics)
-            INITIALIZING => panic!("initialization panicked"),
+            // poisoned (happens if initialization panics)
+            #[cfg(feature = "std")]
+            POISONED => poisoned_with(unsafe { core::ptr::read(this.payload()) }.as_deref().and_then(panic_message)),
+            #[cfg(not(feature = "std"))]
+            POISONED => poisoned(),
 
             // init
-            _ => unsafe {
-                let value = core::mem::replace(this.value.get_mut(), MaybeUninit::uninit());
+            INIT => unsafe {
+                let value = core::ptr::read(this.value());
                 value.assume_init()
-            }
+            },
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unsafe { unreachable_unchecked() }
         }
     }
 
@@ -180,31 +349,44 @@ impl<T, F: FnOnce() -> T> Lazy<T, F> {
     ///
     /// # Panics
     ///
-    /// Panics if the initialization function ran, but panicked.
+    /// Panics if the initialization function ran, but panicked (the value is poisoned).
     #[inline(always)]
     pub fn try_into_inner (self) -> Result<T, F> {
-        let mut this = ManuallyDrop::new(self);
+        let this = ManuallyDrop::new(self);
 
-        match this.state.load(Ordering::Relaxed) {
+        // `Acquire` pairs with the `Release` store that finished the initialization: whatever handed us
+        // ownership (a channel, a join, ...) should already synchronize with it, but we don't rely on that
+        let state = this.state.load(Ordering::Acquire);
+        this.track.write();
+
+        match state {
             // uninit (get function)
             UNINIT => unsafe { 
-                let f = core::mem::replace(this.f.get_mut(), MaybeUninit::uninit());
+                let f = core::ptr::read(this.f());
                 Err(f.assume_init())
             },
 
-            // initializing (happens if initialization panics)
-            INITIALIZING => panic!("initialization panicked"),
+            // poisoned (happens if initialization panics)
+            #[cfg(feature = "std")]
+            POISONED => poisoned_with(unsafe { core::ptr::read(this.payload()) }.as_deref().and_then(panic_message)),
+            #[cfg(not(feature = "std"))]
+            POISONED => poisoned(),
 
             // init (get value)
-            _ => unsafe {
-                let value = core::mem::replace(this.value.get_mut(), MaybeUninit::uninit());
+            INIT => unsafe {
+                let value = core::ptr::read(this.value());
                 Ok(value.assume_init())
-            }
+            },
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unsafe { unreachable_unchecked() }
         }
     }
 }
 
-impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+impl<T, F: FnOnce() -> T, P: PoisonPolicy<F>, S: WaitStrategy> Deref for Lazy<T, F, P, S> {
     type Target = T;
 
     #[inline(always)]
@@ -213,13 +395,28 @@ impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
     }
 }
 
-impl<T, F: FnOnce() -> T> DerefMut for Lazy<T, F> {
+impl<T, F: FnOnce() -> T, P: PoisonPolicy<F>, S: WaitStrategy> DerefMut for Lazy<T, F, P, S> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.get_mut()
     }
 }
 
+/// A lazily-built future: the initialization function runs on the first poll, and the future it returns is polled from then on.
+///
+/// The future must be ```Unpin```, since it's reached through [`get_mut`](Lazy::get_mut), after which the cell is free to
+/// hand it out by value (```into_inner```) or by mutable reference. Box it with ```Box::pin``` otherwise
+impl<Fut: Future + Unpin, F: FnOnce() -> Fut, P: PoisonPolicy<F>, S: WaitStrategy> Future for Lazy<Fut, F, P, S> {
+    type Output = Fut::Output;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // nothing inside the cell is pinned: the initializer is moved out of it, and the future is `Unpin`
+        let this = unsafe { self.get_unchecked_mut() };
+        Pin::new(this.get_mut()).poll(cx)
+    }
+}
+
 impl<T: Default> Default for Lazy<T, fn() -> T> {
     #[inline(always)]
     fn default() -> Self {
@@ -227,30 +424,176 @@ impl<T: Default> Default for Lazy<T, fn() -> T> {
     }
 }
 
-impl<T, F> From<T> for Lazy<T, F> {
+impl<T, F, P, S: WaitStrategy> From<T> for Lazy<T, F, P, S> {
     #[inline(always)]
     fn from(x: T) -> Self {
         Self::init(x)
     }
 }
 
-impl<T, F> Drop for Lazy<T, F> {
+impl<T, F, P, S: WaitStrategy> Drop for Lazy<T, F, P, S> {
     #[inline(always)]
     fn drop(&mut self) {
-        match self.state.load(Ordering::Relaxed) {
+        // `&mut self` means no `get` can be running, and whatever gave us exclusive access (a join, a channel, ...)
+        // already synchronizes with the end of the initialization, so the state can be read non-atomically
+        let state = *self.state.get_mut();
+        debug_assert_ne!(state, INITIALIZING, "Lazy dropped while initializing");
+
+        // nothing to drop but (under `std`) the payload of a panic, which only a poisoned value holds (`P` can't be
+        // asked whether it poisons here, since `Drop` can't bound it)
+        if !core::mem::needs_drop::<T>() && !core::mem::needs_drop::<F>() && (!cfg!(feature = "std") || state != POISONED) {
+            return
+        }
+
+        self.track.write();
+
+        match state {
             // uninit (drop function)
-            UNINIT => return unsafe { self.f.get_mut().assume_init_drop() },
+            UNINIT => unsafe { (*self.f()).assume_init_drop() },
 
-            // currently initializing (wait for value)
-            INITIALIZING => while self.state.load(Ordering::Acquire) == INITIALIZING { core::hint::spin_loop() },
+            // poisoned (drop the panic payload, the initializer was consumed by the panic)
+            #[cfg(feature = "std")]
+            POISONED => unsafe { core::ptr::drop_in_place(self.payload()) },
+            #[cfg(not(feature = "std"))]
+            POISONED => {},
 
             // init (drop value)
-            _ => {},
+            INIT => unsafe { (*self.value()).assume_init_drop() },
+
+            // initializing (can't happen, and there would be nothing to drop anyway)
+            _ => {}
         }
+    }
+}
+
+/// The parts of a ```Lazy<T, F, P, S>``` its initialization depends on, so that [`initialize_erased`] is only compiled once
+#[derive(Clone, Copy)]
+struct Initializer {
+    /// Runs the initializer stored in the slot, and replaces it with its outcome, returning the state to finish in
+    /// (```INIT```, ```FAILED``` if the outcome is an error, or ```UNINIT``` if the error was written to the second
+    /// pointer instead, so that the initializer can be retried)
+    init: unsafe fn(*mut u8, *mut u8) -> u8,
+    poison: fn(&AtomicU8),
+    wait: fn(&AtomicU8, u8) -> u8,
+    notify: fn(&AtomicU8),
+    /// Name of the value's type, reported to the slow initialization hook
+    #[cfg(feature = "std")]
+    name: fn() -> &'static str
+}
+
+/// Runs the initializer in `slot` (a ```Slot<T, F>```), replacing it with its value
+unsafe fn init_slot<T, F: FnOnce() -> T, P: PoisonPolicy<F>> (slot: *mut u8, _: *mut u8) -> u8 {
+    let slot = slot.cast::<Slot<T, F>>();
+    let f_slot = addr_of_mut!((*slot).f).cast::<MaybeUninit<F>>();
+
+    let f = P::take(f_slot);
+    #[cfg(feature = "std")]
+    let value = if P::POISONS { catch_payload(f, addr_of_mut!((*slot).payload).cast()) } else { f() };
+    #[cfg(not(feature = "std"))]
+    let value = f();
+
+    // the value takes the initializer's storage, so whatever is left of the latter goes first
+    P::finish(f_slot);
+    addr_of_mut!((*slot).value).cast::<MaybeUninit<T>>().write(MaybeUninit::new(value));
+    INIT
+}
+
+/// Initializes the value in `slot`, or waits for it to be initialized. Once it returns, the value is initialized, or its
+/// initialization failed (as told by the returned state). If it failed without caching the error, it's written to `out`.
+/// Its accesses to `slot` are marked on `track`
+#[inline(never)]
+unsafe fn initialize_erased (state: &AtomicU8, track: &Track, slot: *mut u8, out: *mut u8, initializer: Initializer) -> u8 {
+    loop {
+        match state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Acquire) {
+            // uninitialized
+            Ok(UNINIT) => {
+                #[cfg(feature = "std")]
+                let _watch = watchdog::Watch::start(state);
+                #[cfg(all(feature = "std", debug_assertions))]
+                let _owner = deadlock::Owner::start(state, (initializer.name)());
+                let guard = PoisonGuard::new(state, initializer.poison, initializer.notify);
+                track.write();
+                let done = (initializer.init)(slot, out);
+                guard.disarm();
+
+                #[cfg(debug_assertions)]
+                assert_eq!(state.swap(done, Ordering::Release), INITIALIZING);
+                #[cfg(not(debug_assertions))]
+                state.store(done, Ordering::Release);
+                (initializer.notify)(state);
+                return done
+            },
+
+            // currently initializing (check again once it's done, since it may have to be retried)
+            Err(INITIALIZING) => {
+                #[cfg(all(feature = "std", debug_assertions))]
+                let _waiter = deadlock::Waiter::start(state, (initializer.name)());
+                #[cfg(feature = "std")]
+                watchdog::watch(state, (initializer.name)());
+                (initializer.wait)(state, INITIALIZING);
+            },
+
+            // initialized, or failed to
+            Err(state @ (INIT | FAILED)) => {
+                track.read();
+                return state
+            },
+
+            // initialization panicked
+            #[cfg(feature = "std")]
+            Err(POISONED) => {
+                track.read();
+                poisoned_with((*slot.cast::<Payload>()).as_deref().and_then(panic_message))
+            },
+            #[cfg(not(feature = "std"))]
+            Err(POISONED) => poisoned(),
+
+            #[cfg(debug_assertions)]
+            _ => unreachable!(),
+            #[cfg(not(debug_assertions))]
+            _ => unreachable_unchecked()
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn poisoned () -> ! {
+    panic!("Lazy instance poisoned")
+}
+
+#[cfg(feature = "futures")]
+#[cold]
+#[inline(never)]
+fn cancelled () -> ! {
+    panic!("AsyncLazy initialization cancelled")
+}
+
+#[cfg(feature = "futures")]
+#[cold]
+#[inline(never)]
+fn taken () -> ! {
+    panic!("AsyncLazy value taken")
+}
+
+#[cfg(feature = "futures")]
+#[cold]
+#[inline(never)]
+fn abandoned () -> ! {
+    panic!("AsyncLazy initialization abandoned")
+}
 
-        unsafe { self.value.get_mut().assume_init_drop() }
+#[cfg(feature = "std")]
+#[cold]
+#[inline(never)]
+fn poisoned_with (msg: Option<&str>) -> ! {
+    match msg {
+        Some(msg) => panic!("Lazy instance poisoned: {msg}"),
+        None => poisoned()
     }
 }
 
-unsafe impl<T: Send, F: Send> Send for Lazy<T, F> {}
-unsafe impl<T: Sync, F: Sync> Sync for Lazy<T, F> {}
+// Sharing a `Lazy` allows any thread to run `F` (moving it out of the cell) and to hand out `&T`,
+// so `F` must be `Send` and `T` both `Send` and `Sync`, just like `std::sync::LazyLock`
+unsafe impl<T: Send, F: Send, P, S: WaitStrategy> Send for Lazy<T, F, P, S> {}
+unsafe impl<T: Send + Sync, F: Send, P, S: WaitStrategy> Sync for Lazy<T, F, P, S> {}
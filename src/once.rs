@@ -0,0 +1,415 @@
+//! Cells (and one-off initializations) run at most once, by a function supplied at each call site
+
+use core::{cell::UnsafeCell, mem::{ManuallyDrop, MaybeUninit}, sync::atomic::Ordering};
+use crate::sync::{AtomicU8, Track};
+use crate::{Initializer, initialize_erased, UNINIT, INITIALIZING, INIT, POISONED};
+use crate::{utils::PoisonGuard, policy::{PoisonPolicy, Repanic}, strategy::{WaitStrategy, DefaultStrategy}};
+
+#[cfg(feature = "std")]
+use crate::utils::{Payload, catch_payload};
+
+/// A cell that's initialized at most once, by a function supplied at each call site.
+///
+/// Unlike [`Lazy`](crate::Lazy), the cell doesn't store its initializer: every call to [`get_or_init`](OnceCell::get_or_init)
+/// brings its own, and only the first one runs, while the threads calling it concurrently wait for its value (like
+/// [`Lazy::get`](crate::Lazy::get) does). If the running function panics (or fails, through
+/// [`get_or_try_init`](OnceCell::get_or_try_init)), the cell is left uninitialized, and the next caller runs its own
+///
+/// ```rust
+/// use laizy::OnceCell;
+///
+/// static CELL: OnceCell<String> = OnceCell::new();
+/// assert_eq!(CELL.get_or_init(|| String::from("config")), "config");
+/// assert_eq!(CELL.get_or_init(|| unreachable!()), "config");
+/// ```
+pub struct OnceCell<T> {
+    state: AtomicU8,
+    value: UnsafeCell<MaybeUninit<T>>,
+    track: Track
+}
+
+/// Initializer of a [`OnceCell`], and the error it failed with (if it was fallible)
+struct Attempt<F, E> {
+    f: Option<F>,
+    error: Option<E>
+}
+
+impl<T> OnceCell<T> {
+    loom_const! {
+        /// Builds a new, uninitialized ```OnceCell```
+        #[inline(always)]
+        pub const fn new () -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                track: Track::new()
+            }
+        }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                Some((*self.value.get()).assume_init_ref())
+            },
+            _ => None
+        }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> Option<&mut T> {
+        match *self.state.get_mut() {
+            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) },
+            _ => None
+        }
+    }
+
+    /// Initializes the cell with `value`, waking every thread waiting on it.
+    ///
+    /// Returns `value` back if the cell is already initialized, or some thread is currently initializing it
+    pub fn set (&self, value: T) -> Result<(), T> {
+        if self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(value)
+        }
+
+        self.track.write();
+        unsafe { self.value.get().write(MaybeUninit::new(value)) }
+        self.state.store(INIT, Ordering::Release);
+        DefaultStrategy::notify(&self.state);
+        Ok(())
+    }
+
+    /// Returns a reference to the value, initializing it with `f` if nobody else is, or waiting for whoever is otherwise.
+    /// `f` is dropped without being called if the value is initialized by someone else
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> T> (&self, f: F) -> &T {
+        match self.get() {
+            Some(value) => value,
+            None => self.initialize_slow(f)
+        }
+    }
+
+    /// Like [`get_or_init`](OnceCell::get_or_init), but with a fallible function. If `f` fails, its error is returned and
+    /// the cell is left uninitialized, so that the next caller (or some thread already waiting) can try again
+    #[inline]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<T, E>> (&self, f: F) -> Result<&T, E> {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => self.try_initialize_slow(f)
+        }
+    }
+
+    /// Blocks the current thread until the value is initialized (by someone else), and returns a reference to it
+    pub fn wait (&self) -> &T {
+        loop {
+            match self.state.load(Ordering::Acquire) {
+                INIT => return unsafe {
+                    self.track.read();
+                    (*self.value.get()).assume_init_ref()
+                },
+                state => { DefaultStrategy::wait(&self.state, state); }
+            }
+        }
+    }
+
+    /// Takes the value out of the cell if it's initialized, leaving it uninitialized
+    #[inline]
+    pub fn take (&mut self) -> Option<T> {
+        let state = *self.state.get_mut();
+        match state {
+            INIT => unsafe {
+                *self.state.get_mut() = UNINIT;
+                Some(self.value.get_mut().assume_init_read())
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the inner value if it's initialized, ```None``` otherwise
+    #[inline]
+    pub fn into_inner (self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        this.take()
+    }
+
+    /// Slow path of [`get_or_init`](OnceCell::get_or_init)
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow<F: FnOnce() -> T> (&self, f: F) -> &T {
+        let mut attempt = Attempt::<F, ()> { f: Some(f), error: None };
+        unsafe {
+            initialize_erased(&self.state, &self.track, self.value.get().cast(), (&mut attempt as *mut Attempt<F, ()>).cast(), Self::initializer(init_once::<T, F, ()>));
+            (*self.value.get()).assume_init_ref()
+        }
+    }
+
+    /// Slow path of [`get_or_try_init`](OnceCell::get_or_try_init)
+    #[cold]
+    #[inline(never)]
+    fn try_initialize_slow<E, F: FnOnce() -> Result<T, E>> (&self, f: F) -> Result<&T, E> {
+        let mut attempt = Attempt { f: Some(f), error: None };
+        unsafe {
+            match initialize_erased(&self.state, &self.track, self.value.get().cast(), (&mut attempt as *mut Attempt<F, E>).cast(), Self::initializer(init_try_once::<T, F, E>)) {
+                INIT => Ok((*self.value.get()).assume_init_ref()),
+                _ => Err(attempt.error.take().unwrap_unchecked())
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn initializer (init: unsafe fn(*mut u8, *mut u8) -> u8) -> Initializer {
+        Initializer {
+            init,
+            poison: unlock,
+            wait: DefaultStrategy::wait,
+            notify: DefaultStrategy::notify,
+            #[cfg(feature = "std")]
+            name: core::any::type_name::<T>
+        }
+    }
+}
+
+/// Leaves the cell uninitialized after its initializer panicked, so that the next caller runs its own
+fn unlock (state: &AtomicU8) {
+    state.store(UNINIT, Ordering::Release)
+}
+
+/// Runs the initializer of `attempt` (an ```Attempt<F, E>```), writing its value into `value` (a ```MaybeUninit<T>```)
+unsafe fn init_once<T, F: FnOnce() -> T, E> (value: *mut u8, attempt: *mut u8) -> u8 {
+    let f = (*attempt.cast::<Attempt<F, E>>()).f.take().unwrap_unchecked();
+    value.cast::<MaybeUninit<T>>().write(MaybeUninit::new(f()));
+    INIT
+}
+
+/// Like [`init_once`], but leaves the cell uninitialized (with the error in `attempt`) if the initializer fails
+unsafe fn init_try_once<T, F: FnOnce() -> Result<T, E>, E> (value: *mut u8, attempt: *mut u8) -> u8 {
+    let attempt = &mut *attempt.cast::<Attempt<F, E>>();
+    match (attempt.f.take().unwrap_unchecked())() {
+        Ok(x) => {
+            value.cast::<MaybeUninit<T>>().write(MaybeUninit::new(x));
+            INIT
+        },
+        Err(e) => {
+            attempt.error = Some(e);
+            UNINIT
+        }
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self { state: AtomicU8::new(INIT), value: UnsafeCell::new(MaybeUninit::new(value)), track: Track::new() }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("OnceCell");
+        match self.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninitialized>"))
+        };
+        tuple.finish()
+    }
+}
+
+impl<T> Drop for OnceCell<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if *self.state.get_mut() == INIT {
+            self.track.write();
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+// Sharing a `OnceCell` lets any thread initialize it, and hand out `&T`
+unsafe impl<T: Send> Send for OnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+/// A one-off initialization that has no value to store (like registering a logger, or installing a panic hook).
+///
+/// Threads calling [`call_once`](Once::call_once) while another one runs its function wait for it to finish (like
+/// [`Lazy::get`](crate::Lazy::get) does). If the function panics, the ```Once``` is poisoned, and every later call to
+/// [`call_once`](Once::call_once) panics as well, while [`call_once_force`](Once::call_once_force) runs its function again
+///
+/// ```rust
+/// use laizy::Once;
+///
+/// static SETUP: Once = Once::new();
+/// SETUP.call_once(|| println!("setting up"));
+/// SETUP.call_once(|| unreachable!());
+/// assert!(SETUP.is_completed());
+/// ```
+pub struct Once {
+    state: AtomicU8,
+    #[cfg(feature = "std")]
+    payload: UnsafeCell<Payload>,
+    track: Track
+}
+
+/// State of a [`Once`], handed to the function run by [`call_once_force`](Once::call_once_force)
+#[derive(Debug, Clone, Copy)]
+pub struct OnceState {
+    poisoned: bool
+}
+
+impl OnceState {
+    /// Returns ```true``` if a previous call panicked, poisoning the [`Once`]
+    #[inline(always)]
+    pub const fn is_poisoned (&self) -> bool {
+        self.poisoned
+    }
+}
+
+/// Function run by a [`Once`], and the state it's handed
+struct Call<F> {
+    f: Option<F>,
+    state: OnceState
+}
+
+impl Once {
+    loom_const! {
+        /// Builds a new ```Once``` that hasn't run yet
+        #[inline(always)]
+        pub const fn new () -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                #[cfg(feature = "std")]
+                payload: UnsafeCell::new(None),
+                track: Track::new()
+            }
+        }
+    }
+
+    /// Returns ```true``` once some function has run to completion
+    #[inline(always)]
+    pub fn is_completed (&self) -> bool {
+        self.state.load(Ordering::Acquire) == INIT
+    }
+
+    /// Runs `f` if no function has run yet, or waits for whichever is running. `f` is dropped without being called otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If the ```Once``` was poisoned by a previous function panicking (`f` panicking poisons it as well)
+    #[inline]
+    pub fn call_once<F: FnOnce()> (&self, f: F) {
+        if !self.is_completed() {
+            self.call_slow(|_: &OnceState| f())
+        }
+    }
+
+    /// Like [`call_once`](Once::call_once), but runs `f` even if the ```Once``` is poisoned, handing it the state it
+    /// found. If `f` completes, the ```Once``` is no longer poisoned
+    #[inline]
+    pub fn call_once_force<F: FnOnce(&OnceState)> (&self, f: F) {
+        if !self.is_completed() {
+            self.force_slow(f)
+        }
+    }
+
+    /// Slow path of [`call_once`](Once::call_once)
+    #[cold]
+    #[inline(never)]
+    fn call_slow<F: FnOnce(&OnceState)> (&self, f: F) {
+        let mut call = Call { f: Some(f), state: OnceState { poisoned: false } };
+        unsafe { initialize_erased(&self.state, &self.track, self.payload(), (&mut call as *mut Call<F>).cast(), Self::initializer::<F>()); }
+    }
+
+    /// Slow path of [`call_once_force`](Once::call_once_force)
+    #[cold]
+    #[inline(never)]
+    fn force_slow<F: FnOnce(&OnceState)> (&self, f: F) {
+        let mut call = Call { f: Some(f), state: OnceState { poisoned: false } };
+        loop {
+            match self.state.load(Ordering::Acquire) {
+                INIT => return,
+                INITIALIZING => { DefaultStrategy::wait(&self.state, INITIALIZING); },
+                current => if self.state.compare_exchange(current, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                    call.state.poisoned = current == POISONED;
+                    unsafe {
+                        let guard = PoisonGuard::new(&self.state, <Repanic as PoisonPolicy<F>>::poison, DefaultStrategy::notify);
+                        match call.state.poisoned {
+                            // threads that just found it poisoned may still be reading the payload of the first panic,
+                            // so it's kept for as long as the `Once` lives, and this call doesn't store its own
+                            true => (call.f.take().unwrap_unchecked())(&call.state),
+                            false => {
+                                self.track.write();
+                                call_with_state::<F>(self.payload(), (&mut call as *mut Call<F>).cast());
+                            }
+                        }
+                        guard.disarm();
+                    }
+
+                    self.state.store(INIT, Ordering::Release);
+                    DefaultStrategy::notify(&self.state);
+                    return
+                }
+            }
+        }
+    }
+
+    /// Type-erased pointer to the message of the panic that poisoned it (if any)
+    #[inline(always)]
+    fn payload (&self) -> *mut u8 {
+        #[cfg(feature = "std")]
+        return self.payload.get().cast();
+        #[cfg(not(feature = "std"))]
+        return core::ptr::null_mut();
+    }
+
+    #[inline(always)]
+    fn initializer<F: FnOnce(&OnceState)> () -> Initializer {
+        Initializer {
+            init: call_with_state::<F>,
+            poison: <Repanic as PoisonPolicy<F>>::poison,
+            wait: DefaultStrategy::wait,
+            notify: DefaultStrategy::notify,
+            #[cfg(feature = "std")]
+            name: core::any::type_name::<Self>
+        }
+    }
+}
+
+/// Runs the function of `call` (a ```Call<F>```), writing the message of its panic (if any) into `payload`
+unsafe fn call_with_state<F: FnOnce(&OnceState)> (payload: *mut u8, call: *mut u8) -> u8 {
+    let call = &mut *call.cast::<Call<F>>();
+    let f = call.f.take().unwrap_unchecked();
+    let state = call.state;
+
+    #[cfg(feature = "std")]
+    catch_payload(|| f(&state), payload.cast());
+    #[cfg(not(feature = "std"))]
+    { let _ = payload; f(&state); }
+    INIT
+}
+
+impl Default for Once {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Once {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Once").field("completed", &self.is_completed()).finish_non_exhaustive()
+    }
+}
+
+// The payload is only written once, by the thread whose function poisoned it, and only read (or dropped) afterwards
+unsafe impl Send for Once {}
+unsafe impl Sync for Once {}
@@ -0,0 +1,78 @@
+//! Cache-line padded lazy values, to avoid false sharing between hot, adjacent cells
+
+use core::ops::{Deref, DerefMut};
+use crate::{policy::Repanic, strategy::{WaitStrategy, DefaultStrategy}};
+
+/// A [`Lazy`](crate::Lazy) aligned (and padded) to the length of a cache line, so that no other value shares its line.
+///
+/// Besides construction, it's used exactly like the inner cell, which it dereferences to
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "powerpc64"), repr(align(128)))]
+#[cfg_attr(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "powerpc64")), repr(align(64)))]
+#[derive(Debug)]
+pub struct Lazy<T, F = fn() -> T, P = Repanic, S: WaitStrategy = DefaultStrategy> {
+    inner: crate::Lazy<T, F, P, S>
+}
+
+impl<T, F> Lazy<T, F> {
+    loom_const! {
+        /// Builds a new padded ```Lazy``` value
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self { inner: crate::Lazy::new(f) }
+        }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Lazy<T, F, P, S> {
+    loom_const! {
+        /// Builds a new padded ```Lazy``` value with a custom [`PoisonPolicy`](crate::policy::PoisonPolicy) and/or [`WaitStrategy`]
+        #[inline(always)]
+        pub const fn with_policy (f: F) -> Self {
+            Self { inner: crate::Lazy::with_policy(f) }
+        }
+    }
+
+    loom_const! {
+        /// Builds a padded ```Lazy``` value that's already initialized
+        #[inline(always)]
+        pub const fn init (value: T) -> Self {
+            Self { inner: crate::Lazy::init(value) }
+        }
+    }
+
+    /// Returns the inner, unpadded cell
+    #[inline(always)]
+    pub fn into_unpadded (self) -> crate::Lazy<T, F, P, S> {
+        self.inner
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> Deref for Lazy<T, F, P, S> {
+    type Target = crate::Lazy<T, F, P, S>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> DerefMut for Lazy<T, F, P, S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<T: Default> Default for Lazy<T, fn() -> T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self { inner: Default::default() }
+    }
+}
+
+impl<T, F, P, S: WaitStrategy> From<T> for Lazy<T, F, P, S> {
+    #[inline(always)]
+    fn from(x: T) -> Self {
+        Self::init(x)
+    }
+}
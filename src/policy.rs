@@ -0,0 +1,111 @@
+//! Policies that decide what happens to a [`Lazy`](crate::Lazy) after its initializer panics, and to a
+//! [`TryLazy`](crate::TryLazy) after its initializer fails
+
+use core::{mem::MaybeUninit, sync::atomic::Ordering};
+use crate::sync::AtomicU8;
+use crate::{UNINIT, POISONED};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Behaviour of a [`Lazy`](crate::Lazy) after its initialization function panicked.
+///
+/// This trait is sealed, and implemented by [`Repanic`], [`Retry`] and [`Abort`]
+pub trait PoisonPolicy<F>: sealed::Sealed {
+    /// Whether a panicking initializer leaves the value poisoned
+    #[doc(hidden)]
+    const POISONS: bool;
+
+    /// Returns the initializer that will be called
+    #[doc(hidden)]
+    unsafe fn take (f: *mut MaybeUninit<F>) -> F;
+
+    /// Cleans up the initializer slot after a successful initialization
+    #[doc(hidden)]
+    unsafe fn finish (f: *mut MaybeUninit<F>);
+
+    /// Updates the state after the initializer panicked
+    #[doc(hidden)]
+    fn poison (state: &AtomicU8);
+}
+
+/// The cell is poisoned, and every current and future access panics (default)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Repanic;
+
+/// The cell goes back to being uninitialized, and the next access runs a clone of the initializer again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Retry;
+
+/// The whole process is aborted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Abort;
+
+/// The error of a [`TryLazy`](crate::TryLazy)'s initializer is cached, and every current and future access returns
+/// a reference to it (default). For permanent failures, like a bad configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CacheErrors;
+
+/// The error of a [`TryLazy`](crate::TryLazy)'s initializer is returned to the caller that ran it, and the cell goes
+/// back to being uninitialized, so that the next access calls the initializer again. For transient failures, like a network error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RetryErrors;
+
+impl sealed::Sealed for Repanic {}
+impl sealed::Sealed for Retry {}
+impl sealed::Sealed for Abort {}
+
+impl<F> PoisonPolicy<F> for Repanic {
+    const POISONS: bool = true;
+
+    #[inline(always)]
+    unsafe fn take (f: *mut MaybeUninit<F>) -> F {
+        core::ptr::read(f).assume_init()
+    }
+
+    #[inline(always)]
+    unsafe fn finish (_: *mut MaybeUninit<F>) {}
+
+    #[inline(always)]
+    fn poison (state: &AtomicU8) {
+        state.store(POISONED, Ordering::Release);
+    }
+}
+
+impl<F: Clone> PoisonPolicy<F> for Retry {
+    const POISONS: bool = false;
+
+    #[inline(always)]
+    unsafe fn take (f: *mut MaybeUninit<F>) -> F {
+        (*f).assume_init_ref().clone()
+    }
+
+    #[inline(always)]
+    unsafe fn finish (f: *mut MaybeUninit<F>) {
+        core::ptr::drop_in_place(f.cast::<F>())
+    }
+
+    #[inline(always)]
+    fn poison (state: &AtomicU8) {
+        state.store(UNINIT, Ordering::Release);
+    }
+}
+
+impl<F> PoisonPolicy<F> for Abort {
+    const POISONS: bool = false;
+
+    #[inline(always)]
+    unsafe fn take (f: *mut MaybeUninit<F>) -> F {
+        core::ptr::read(f).assume_init()
+    }
+
+    #[inline(always)]
+    unsafe fn finish (_: *mut MaybeUninit<F>) {}
+
+    #[inline(always)]
+    fn poison (_: &AtomicU8) {
+        // we're already unwinding, so panicking again aborts the process (even on `no_std`)
+        panic!("Lazy initialization panicked, aborting")
+    }
+}
@@ -0,0 +1,512 @@
+//! Cells whose concurrent initializers all run, instead of waiting for each other, with a single one of them winning.
+//!
+//! Readers never block nor spin, and neither do initializers: every thread finding the cell empty computes its own
+//! value, and a single ```compare_exchange``` decides which one is stored, the others being dropped. This is preferable to
+//! [`Lazy`](crate::Lazy) when the value is cheap to compute (and computing it more than once is harmless), or when
+//! threads must never wait on each other (e.g. in signal handlers, or where priority inversion is a concern).
+//! Otherwise, [`Lazy`](crate::Lazy) computes the value exactly once.
+//!
+//! [`OnceNonZeroUsize`] and [`OnceBool`] store their value directly in an atomic integer, and [`OnceRef`] in an atomic
+//! pointer, so they need no allocator
+//!
+//! # Ordering
+//!
+//! Storing a value is a ```Release``` operation, and reading it is an ```Acquire``` one, so whatever the winning
+//! initializer wrote before storing its value (e.g. the entry of a table indexed by a cached id) is visible to every
+//! thread that reads that value back. Nothing is published by the losing initializers, so a value they computed must
+//! never be used once they lose: always use the one returned by ```get_or_init```
+
+use core::{marker::PhantomData, num::NonZeroUsize, ptr::null_mut, sync::atomic::{AtomicPtr, AtomicUsize, Ordering}};
+#[cfg(feature = "alloc")]
+use {core::ops::Deref, alloc::boxed::Box};
+
+/// A cell holding a ```NonZeroUsize``` stored directly in an ```AtomicUsize``` (with zero meaning it's empty), where
+/// concurrent initializers race to store their value
+///
+/// ```rust
+/// use core::num::NonZeroUsize;
+/// use laizy::race::OnceNonZeroUsize;
+///
+/// static PAGE_SIZE: OnceNonZeroUsize = OnceNonZeroUsize::new();
+/// let page_size = PAGE_SIZE.get_or_init(|| NonZeroUsize::new(4096).unwrap());
+/// assert_eq!(PAGE_SIZE.get(), Some(page_size));
+/// ```
+#[derive(Default)]
+pub struct OnceNonZeroUsize {
+    inner: AtomicUsize
+}
+
+impl OnceNonZeroUsize {
+    /// Builds a new, empty ```OnceNonZeroUsize```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { inner: AtomicUsize::new(0) }
+    }
+
+    /// Returns ```Some(value)``` if the value has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<NonZeroUsize> {
+        NonZeroUsize::new(self.inner.load(Ordering::Acquire))
+    }
+
+    /// Stores `value` if the cell is empty, returning it back otherwise
+    #[inline]
+    pub fn set (&self, value: NonZeroUsize) -> Result<(), NonZeroUsize> {
+        self.publish(value).map(|_| ()).map_err(|_| value)
+    }
+
+    /// Returns the value, computing it with `f` if it's empty. If some other thread stores its value first, the one
+    /// computed by `f` is discarded, and the winner is returned instead
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> NonZeroUsize> (&self, f: F) -> NonZeroUsize {
+        match self.get() {
+            Some(value) => value,
+            None => match self.publish(f()) {
+                Ok(value) | Err(value) => value
+            }
+        }
+    }
+
+    /// Like [`get_or_init`](OnceNonZeroUsize::get_or_init), but with a fallible function. If `f` fails, the cell is
+    /// left empty
+    #[inline]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<NonZeroUsize, E>> (&self, f: F) -> Result<NonZeroUsize, E> {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => match self.publish(f()?) {
+                Ok(value) | Err(value) => Ok(value)
+            }
+        }
+    }
+
+    /// Stores `value` if the cell is empty. Otherwise, returns the value that won
+    #[inline(always)]
+    fn publish (&self, value: NonZeroUsize) -> Result<NonZeroUsize, NonZeroUsize> {
+        // `Release` publishes what the winner wrote before, and `Acquire` (on failure) sees what the winner did
+        match self.inner.compare_exchange(0, value.get(), Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => Ok(value),
+            Err(winner) => unsafe { Err(NonZeroUsize::new_unchecked(winner)) }
+        }
+    }
+}
+
+impl core::fmt::Debug for OnceNonZeroUsize {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OnceNonZeroUsize").field(&self.get()).finish()
+    }
+}
+
+/// A cell holding a ```bool```, built on top of [`OnceNonZeroUsize`], where concurrent initializers race to store their value
+#[derive(Default)]
+pub struct OnceBool {
+    inner: OnceNonZeroUsize
+}
+
+impl OnceBool {
+    /// Builds a new, empty ```OnceBool```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { inner: OnceNonZeroUsize::new() }
+    }
+
+    /// Returns ```Some(value)``` if the value has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<bool> {
+        self.inner.get().map(Self::from_usize)
+    }
+
+    /// Stores `value` if the cell is empty, returning it back otherwise
+    #[inline(always)]
+    pub fn set (&self, value: bool) -> Result<(), bool> {
+        self.inner.set(Self::to_usize(value)).map_err(|_| value)
+    }
+
+    /// Returns the value, computing it with `f` if it's empty. If some other thread stores its value first, the one
+    /// computed by `f` is discarded, and the winner is returned instead
+    #[inline(always)]
+    pub fn get_or_init<F: FnOnce() -> bool> (&self, f: F) -> bool {
+        Self::from_usize(self.inner.get_or_init(|| Self::to_usize(f())))
+    }
+
+    /// Like [`get_or_init`](OnceBool::get_or_init), but with a fallible function. If `f` fails, the cell is left empty
+    #[inline(always)]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<bool, E>> (&self, f: F) -> Result<bool, E> {
+        self.inner.get_or_try_init(|| f().map(Self::to_usize)).map(Self::from_usize)
+    }
+
+    #[inline(always)]
+    const fn from_usize (value: NonZeroUsize) -> bool {
+        value.get() == 1
+    }
+
+    #[inline(always)]
+    const fn to_usize (value: bool) -> NonZeroUsize {
+        // `true` is stored as 1, and `false` as 2
+        unsafe { NonZeroUsize::new_unchecked(2 - value as usize) }
+    }
+}
+
+impl core::fmt::Debug for OnceBool {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OnceBool").field(&self.get()).finish()
+    }
+}
+
+/// A cell holding a ```&'a T``` stored directly in an ```AtomicPtr```, where concurrent initializers race to store their
+/// reference
+///
+/// ```rust
+/// use laizy::race::OnceRef;
+///
+/// static CONFIG: OnceRef<u16> = OnceRef::new();
+/// assert_eq!(CONFIG.get_or_init(|| &8080), &8080);
+/// ```
+pub struct OnceRef<'a, T> {
+    ptr: AtomicPtr<T>,
+    _ref: PhantomData<&'a T>
+}
+
+impl<'a, T> OnceRef<'a, T> {
+    /// Builds a new, empty ```OnceRef```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { ptr: AtomicPtr::new(null_mut()), _ref: PhantomData }
+    }
+
+    /// Returns ```Some(value)``` if the reference has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&'a T> {
+        // `Acquire` pairs with the `Release` of the winning `compare_exchange`, so whatever the winner wrote is visible
+        unsafe { self.ptr.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Stores `value` if the cell is empty, returning it back otherwise
+    #[inline]
+    pub fn set (&self, value: &'a T) -> Result<(), &'a T> {
+        self.publish(value).map(|_| ()).map_err(|_| value)
+    }
+
+    /// Returns the reference, computing it with `f` if it's empty. If some other thread stores its reference first,
+    /// the one computed by `f` is discarded, and the winner is returned instead
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> &'a T> (&self, f: F) -> &'a T {
+        match self.get() {
+            Some(value) => value,
+            None => match self.publish(f()) {
+                Ok(value) | Err(value) => value
+            }
+        }
+    }
+
+    /// Like [`get_or_init`](OnceRef::get_or_init), but with a fallible function. If `f` fails, the cell is left empty
+    #[inline]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<&'a T, E>> (&self, f: F) -> Result<&'a T, E> {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => match self.publish(f()?) {
+                Ok(value) | Err(value) => Ok(value)
+            }
+        }
+    }
+
+    /// Stores `value` if the cell is empty. Otherwise, returns the reference that won
+    #[inline(always)]
+    fn publish (&self, value: &'a T) -> Result<&'a T, &'a T> {
+        let ptr = value as *const T as *mut T;
+        match self.ptr.compare_exchange(null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => Ok(value),
+            Err(winner) => unsafe { Err(&*winner) }
+        }
+    }
+}
+
+impl<T> Default for OnceRef<'_, T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceRef<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OnceRef").field(&self.get()).finish()
+    }
+}
+
+// It only ever hands out the shared reference it stores
+unsafe impl<T: Sync> Send for OnceRef<'_, T> {}
+unsafe impl<T: Sync> Sync for OnceRef<'_, T> {}
+
+/// A cell holding a ```Box<T>``` stored behind an ```AtomicPtr```, where concurrent initializers race to store their box,
+/// the losers deallocating theirs
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub struct OnceBox<T> {
+    ptr: AtomicPtr<T>,
+    // owns a `Box<T>`
+    _value: PhantomData<Box<T>>
+}
+
+#[cfg(feature = "alloc")]
+impl<T> OnceBox<T> {
+    /// Builds a new, empty ```OnceBox```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { ptr: AtomicPtr::new(null_mut()), _value: PhantomData }
+    }
+
+    /// Returns ```Some(ref value)``` if the box has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&T> {
+        // `Acquire` pairs with the `Release` of the winning `compare_exchange`, so the value behind it is visible
+        unsafe { self.ptr.load(Ordering::Acquire).as_ref() }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the box has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> Option<&mut T> {
+        unsafe { self.ptr.get_mut().as_mut() }
+    }
+
+    /// Stores `value` if the cell is empty, returning it back otherwise
+    #[inline]
+    pub fn set (&self, value: Box<T>) -> Result<(), Box<T>> {
+        self.publish(value).map(|_| ()).map_err(|(_, value)| value)
+    }
+
+    /// Returns a reference to the value, computing its box with `f` if it's empty. If some other thread stores its box
+    /// first, the one computed by `f` is deallocated
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> Box<T>> (&self, f: F) -> &T {
+        match self.get() {
+            Some(value) => value,
+            None => match self.publish(f()) {
+                Ok(value) | Err((value, _)) => value
+            }
+        }
+    }
+
+    /// Like [`get_or_init`](OnceBox::get_or_init), but with a fallible function. If `f` fails, the cell is left empty
+    #[inline]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<Box<T>, E>> (&self, f: F) -> Result<&T, E> {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => match self.publish(f()?) {
+                Ok(value) | Err((value, _)) => Ok(value)
+            }
+        }
+    }
+
+    /// Takes the box out of the cell if it's been stored, leaving it empty
+    #[inline]
+    pub fn take (&mut self) -> Option<Box<T>> {
+        let ptr = core::mem::replace(self.ptr.get_mut(), null_mut());
+        match ptr.is_null() {
+            true => None,
+            false => unsafe { Some(Box::from_raw(ptr)) }
+        }
+    }
+
+    /// Returns the inner box if it's been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn into_inner (mut self) -> Option<Box<T>> {
+        self.take()
+    }
+
+    /// Stores `value` if the cell is empty. Otherwise, returns it back alongside the value that won
+    fn publish (&self, value: Box<T>) -> Result<&T, (&T, Box<T>)> {
+        let ptr = Box::into_raw(value);
+        match self.ptr.compare_exchange(null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => unsafe { Ok(&*ptr) },
+            Err(winner) => unsafe { Err((&*winner, Box::from_raw(ptr))) }
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Default for OnceBox<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceBox<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("OnceBox").field(&self.get()).finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Drop for OnceBox<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        drop(self.take())
+    }
+}
+
+// Racing initializers hand their values to whichever thread wins, which shares them
+#[cfg(feature = "alloc")]
+unsafe impl<T: Send> Send for OnceBox<T> {}
+#[cfg(feature = "alloc")]
+unsafe impl<T: Send + Sync> Sync for OnceBox<T> {}
+
+/// A cell that's initialized at most once, where concurrent initializers race to store their value.
+///
+/// It's a [`OnceBox`] that boxes the values it's handed
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub struct OnceRace<T> {
+    inner: OnceBox<T>
+}
+
+#[cfg(feature = "alloc")]
+impl<T> OnceRace<T> {
+    /// Builds a new, empty ```OnceRace```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { inner: OnceBox::new() }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&T> {
+        self.inner.get()
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> Option<&mut T> {
+        self.inner.get_mut()
+    }
+
+    /// Stores `value` if the cell is empty, returning it back otherwise
+    #[inline(always)]
+    pub fn set (&self, value: T) -> Result<(), T> {
+        self.inner.set(Box::new(value)).map_err(|value| *value)
+    }
+
+    /// Returns a reference to the value, computing it with `f` if it's empty. If some other thread stores its value
+    /// first, the one computed by `f` is dropped
+    #[inline(always)]
+    pub fn get_or_init<F: FnOnce() -> T> (&self, f: F) -> &T {
+        self.inner.get_or_init(|| Box::new(f()))
+    }
+
+    /// Like [`get_or_init`](OnceRace::get_or_init), but with a fallible function. If `f` fails, the cell is left empty
+    #[inline(always)]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<T, E>> (&self, f: F) -> Result<&T, E> {
+        self.inner.get_or_try_init(|| f().map(Box::new))
+    }
+
+    /// Takes the value out of the cell if it's been stored, leaving it empty
+    #[inline(always)]
+    pub fn take (&mut self) -> Option<T> {
+        self.inner.take().map(|value| *value)
+    }
+
+    /// Returns the inner value if it's been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn into_inner (self) -> Option<T> {
+        self.inner.into_inner().map(|value| *value)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Default for OnceRace<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceRace<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("OnceRace");
+        match self.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninitialized>"))
+        };
+        tuple.finish()
+    }
+}
+
+/// A lazy value whose concurrent initializations all run, with a single one of them being stored.
+///
+/// Since it may run more than once, its initialization function is called by reference
+///
+/// ```rust
+/// use laizy::race::LazyRace;
+///
+/// static ID: LazyRace<u64> = LazyRace::new(|| 42);
+/// assert_eq!(*ID, 42);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[cfg(feature = "alloc")]
+pub struct LazyRace<T, F = fn() -> T> {
+    cell: OnceRace<T>,
+    f: F
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F> LazyRace<T, F> {
+    /// Builds a new ```LazyRace``` value
+    #[inline(always)]
+    pub const fn new (f: F) -> Self {
+        Self { cell: OnceRace::new(), f }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already been stored, ```None``` otherwise
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    /// Returns the inner value if it's been stored, alongside the initialization function
+    #[inline(always)]
+    pub fn into_parts (self) -> (Option<T>, F) {
+        (self.cell.into_inner(), self.f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F: Fn() -> T> LazyRace<T, F> {
+    /// Returns a reference to the inner value, computing it if it hasn't been stored yet
+    #[inline(always)]
+    pub fn get (&self) -> &T {
+        self.cell.get_or_init(&self.f)
+    }
+
+    /// Returns the inner value, computing it if it hasn't been stored yet
+    #[inline]
+    pub fn into_inner (self) -> T {
+        match self.into_parts() {
+            (Some(value), _) => value,
+            (None, f) => f()
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F: Fn() -> T> Deref for LazyRace<T, F> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: core::fmt::Debug, F> core::fmt::Debug for LazyRace<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("LazyRace");
+        match self.try_get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninitialized>"))
+        };
+        tuple.finish()
+    }
+}
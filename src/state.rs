@@ -0,0 +1,68 @@
+//! Values of the state of [`Lazy`](crate::Lazy) and [`AsyncLazy`](crate::AsyncLazy) cells
+
+pub(crate) const UNINIT: u8 = 0;
+pub(crate) const INITIALIZING: u8 = 1;
+pub(crate) const INIT: u8 = 2;
+pub(crate) const POISONED: u8 = 3;
+/// The initializer of a [`TryLazy`](crate::TryLazy) returned an error, which is stored in its place
+pub(crate) const FAILED: u8 = 8;
+/// The task initializing an [`AsyncLazy`](crate::AsyncLazy) dropped its future before it finished (without ```alloc```),
+/// or the initialization was aborted
+#[cfg(feature = "futures")]
+pub(crate) const CANCELLED: u8 = 4;
+/// The initialization future of an [`AsyncLazy`](crate::AsyncLazy) was left in the cell, partially polled,
+/// by [`AsyncLazy::poll_get`](crate::AsyncLazy::poll_get)
+#[cfg(feature = "futures")]
+pub(crate) const PARKED: u8 = 5;
+/// The value of an [`AsyncLazy`](crate::AsyncLazy) was moved out by [`AsyncLazy::take`](crate::AsyncLazy::take)
+#[cfg(feature = "futures")]
+pub(crate) const TAKEN: u8 = 6;
+/// The initialization of an [`AsyncLazy`](crate::AsyncLazy) was aborted by [`AsyncLazy::abort`](crate::AsyncLazy::abort),
+/// but the task running it hasn't dropped its future yet
+#[cfg(feature = "futures")]
+pub(crate) const ABORTING: u8 = 7;
+/// The initialization future of an [`AsyncLazy`](crate::AsyncLazy) was handed back to the cell, partially polled
+/// (and boxed), by a [`get`](crate::AsyncLazy::get) future dropped before it finished
+#[cfg(feature = "futures")]
+pub(crate) const SUSPENDED: u8 = 9;
+/// The value of an [`AsyncCached`](crate::AsyncCached) was [invalidated](crate::AsyncCached::invalidate_shared), and is
+/// held until it's dropped by the next computation
+#[cfg(feature = "futures")]
+pub(crate) const STALE: u8 = 10;
+
+/// Observable state of an [`AsyncLazy`](crate::AsyncLazy)
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+#[cfg(feature = "futures")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum State {
+    /// The initialization future hasn't started running
+    Uninit,
+    /// The initialization future is running
+    Initializing,
+    /// The value is initialized
+    Initialized,
+    /// The initialization future panicked
+    Poisoned,
+    /// The task running the initialization future dropped it before it finished, without the ```alloc``` feature (see
+    /// [`AsyncLazy::get`](crate::AsyncLazy::get)), or it was [aborted](crate::AsyncLazy::abort)
+    Cancelled,
+    /// The value was moved out of the cell
+    Taken
+}
+
+#[cfg(feature = "futures")]
+impl State {
+    #[inline(always)]
+    pub(crate) const fn from_raw (state: u8) -> Self {
+        match state {
+            UNINIT => Self::Uninit,
+            INITIALIZING | PARKED | SUSPENDED => Self::Initializing,
+            INIT => Self::Initialized,
+            POISONED => Self::Poisoned,
+            // an aborted initialization is as good as cancelled to everyone but the task running it
+            CANCELLED | ABORTING => Self::Cancelled,
+            _ => Self::Taken
+        }
+    }
+}
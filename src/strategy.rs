@@ -0,0 +1,146 @@
+//! Strategies that decide how a [`Lazy`](crate::Lazy) waits for another thread's initialization
+
+use core::sync::atomic::Ordering;
+use crate::sync::AtomicU8;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// How threads wait for a [`Lazy`](crate::Lazy) that's being initialized by someone else.
+///
+/// This trait is sealed, and implemented by [`Spin`], [`Backoff`], (under ```std```) [`Park`] and (under ```cortex-m-wfe```) [`Wfe`]
+pub trait WaitStrategy: sealed::Sealed {
+    /// Blocks until `state` stops being `until`, returning the new state
+    #[doc(hidden)]
+    fn wait (state: &AtomicU8, until: u8) -> u8;
+
+    /// Wakes every thread waiting on `state`. Called after `state` changes
+    #[doc(hidden)]
+    fn notify (state: &AtomicU8);
+}
+
+/// Spins in a tight loop until the initialization finishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Spin;
+
+/// Spins exponentially longer the longer the initialization takes, yielding the thread under ```std```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Backoff;
+
+/// Backs off for a while, and then parks the thread until the initialization finishes
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Park;
+
+/// Sleeps the core with ```wfe``` until the initialization finishes, which wakes it up with ```sev```.
+/// Outside of ARM targets, it spins instead.
+///
+/// ```wfe``` also returns on interrupts, so values initialized by an interrupt handler (or after a DMA completion) wake their waiters,
+/// but only while that interrupt isn't masked: waiting inside a critical section on a value that only an interrupt handler
+/// can initialize deadlocks, just like spinning would
+#[cfg_attr(docsrs, doc(cfg(feature = "cortex-m-wfe")))]
+#[cfg(feature = "cortex-m-wfe")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Wfe;
+
+/// Strategy used when none is specified: [`Park`] under ```std```, [`Wfe`] under ```cortex-m-wfe```, [`Backoff`] otherwise
+#[cfg(feature = "std")]
+pub type DefaultStrategy = Park;
+
+/// Strategy used when none is specified: [`Park`] under ```std```, [`Wfe`] under ```cortex-m-wfe```, [`Backoff`] otherwise
+#[cfg(all(not(feature = "std"), feature = "cortex-m-wfe"))]
+pub type DefaultStrategy = Wfe;
+
+/// Strategy used when none is specified: [`Park`] under ```std```, [`Wfe`] under ```cortex-m-wfe```, [`Backoff`] otherwise
+#[cfg(not(any(feature = "std", feature = "cortex-m-wfe")))]
+pub type DefaultStrategy = Backoff;
+
+impl sealed::Sealed for Spin {}
+impl sealed::Sealed for Backoff {}
+#[cfg(feature = "std")]
+impl sealed::Sealed for Park {}
+#[cfg(feature = "cortex-m-wfe")]
+impl sealed::Sealed for Wfe {}
+
+impl WaitStrategy for Spin {
+    #[inline]
+    fn wait (state: &AtomicU8, until: u8) -> u8 {
+        loop {
+            match state.load(Ordering::Acquire) {
+                current if current == until => crate::sync::spin_loop(),
+                current => return current
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn notify (_: &AtomicU8) {}
+}
+
+impl WaitStrategy for Backoff {
+    #[inline]
+    fn wait (state: &AtomicU8, until: u8) -> u8 {
+        let mut backoff = crate::utils::Backoff::new();
+        loop {
+            match state.load(Ordering::Acquire) {
+                current if current == until => backoff.snooze(),
+                current => return current
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn notify (_: &AtomicU8) {}
+}
+
+#[cfg(feature = "std")]
+impl WaitStrategy for Park {
+    #[inline]
+    fn wait (state: &AtomicU8, until: u8) -> u8 {
+        let mut backoff = crate::utils::Backoff::new();
+        loop {
+            match state.load(Ordering::Acquire) {
+                current if current == until => {},
+                current => return current
+            }
+
+            if backoff.is_completed() {
+                return crate::utils::park(state, until)
+            }
+            backoff.snooze();
+        }
+    }
+
+    #[inline(always)]
+    fn notify (state: &AtomicU8) {
+        crate::utils::unpark(state)
+    }
+}
+
+#[cfg(feature = "cortex-m-wfe")]
+impl WaitStrategy for Wfe {
+    #[inline]
+    fn wait (state: &AtomicU8, until: u8) -> u8 {
+        loop {
+            match state.load(Ordering::Acquire) {
+                // if the event was sent in between the load and `wfe`, the latter returns immediately
+                current if current == until => {
+                    #[cfg(target_arch = "arm")]
+                    unsafe { core::arch::asm!("wfe", options(nostack, preserves_flags)) };
+                    #[cfg(not(target_arch = "arm"))]
+                    crate::sync::spin_loop();
+                },
+                current => return current
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn notify (_: &AtomicU8) {
+        // called after the `Release` store that changed the state
+        #[cfg(target_arch = "arm")]
+        unsafe { core::arch::asm!("sev", options(nostack, preserves_flags)) };
+    }
+}
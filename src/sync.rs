@@ -0,0 +1,137 @@
+//! Synchronization primitives the cells are built on: those of ```core```, or [loom](https://docs.rs/loom)'s under
+//! ```--cfg loom```, so that ```tests/loom.rs``` can check every interleaving of the real cells.
+//!
+//! loom's atomics and cells can't be built in constants, so the constructors declared with [`loom_const`] stop being
+//! ```const``` under loom (the cells can't be used in ```static```s there, which loom wouldn't allow anyway)
+
+#[cfg(not(loom))]
+pub(crate) use core::{hint::spin_loop, sync::atomic::AtomicU8};
+#[cfg(all(not(loom), feature = "futures"))]
+pub(crate) use core::sync::atomic::{AtomicUsize, AtomicBool, fence};
+#[cfg(loom)]
+pub(crate) use loom::hint::spin_loop;
+#[cfg(all(loom, feature = "futures"))]
+pub(crate) use loom::sync::atomic::{AtomicBool, fence};
+
+/// Declares a ```const fn``` that isn't ```const``` under loom
+macro_rules! loom_const {
+    ($(#[$attr:meta])* $vis:vis const $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])* $vis const $($rest)*
+        #[cfg(loom)]
+        $(#[$attr])* $vis $($rest)*
+    };
+}
+
+/// Lets loom check the accesses to the data of a cell (which lives in a ```core``` ```UnsafeCell```) for races: the
+/// cell marks where it reads and writes it, relying on its state to order them. Empty, and a no-op, outside of loom
+pub(crate) struct Track {
+    #[cfg(loom)]
+    cell: loom::cell::UnsafeCell<()>
+}
+
+impl Track {
+    loom_const! {
+        #[inline(always)]
+        pub const fn new () -> Self {
+            Self {
+                #[cfg(loom)]
+                cell: loom::cell::UnsafeCell::new(())
+            }
+        }
+    }
+
+    /// Marks a shared access to the data
+    #[inline(always)]
+    pub fn read (&self) {
+        #[cfg(loom)]
+        self.cell.with(|_| ());
+    }
+
+    /// Marks an exclusive access to the data (writing it, moving it out or dropping it)
+    #[inline(always)]
+    pub fn write (&self) {
+        #[cfg(loom)]
+        self.cell.with_mut(|_| ());
+    }
+}
+
+impl core::fmt::Debug for Track {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Track")
+    }
+}
+
+/// loom's atomics, with the ```get_mut``` of ```core```'s
+#[cfg(loom)]
+macro_rules! atomics {
+    ($($name:ident: $int:ty),+) => {
+        $(
+            #[derive(Debug)]
+            pub struct $name(loom::sync::atomic::$name);
+
+            impl $name {
+                #[inline(always)]
+                pub fn new (value: $int) -> Self {
+                    Self(loom::sync::atomic::$name::new(value))
+                }
+
+                /// Reads the value non-atomically, writing it back once the guard is dropped
+                #[inline(always)]
+                pub fn get_mut (&mut self) -> GetMut<'_, $int, loom::sync::atomic::$name> {
+                    let value = self.0.with_mut(|value| *value);
+                    GetMut { atomic: &mut self.0, value, write: |atomic, value| atomic.with_mut(|v| *v = value) }
+                }
+            }
+
+            impl core::ops::Deref for $name {
+                type Target = loom::sync::atomic::$name;
+
+                #[inline(always)]
+                fn deref (&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(loom)]
+atomics!(AtomicU8: u8);
+#[cfg(all(loom, feature = "futures"))]
+atomics!(AtomicUsize: usize);
+
+/// Value of a loom atomic borrowed mutably, see [`AtomicU8::get_mut`]
+#[cfg(loom)]
+pub struct GetMut<'a, T: Copy, A> {
+    atomic: &'a mut A,
+    value: T,
+    write: fn(&mut A, T)
+}
+
+#[cfg(loom)]
+impl<T: Copy, A> core::ops::Deref for GetMut<'_, T, A> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref (&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(loom)]
+impl<T: Copy, A> core::ops::DerefMut for GetMut<'_, T, A> {
+    #[inline(always)]
+    fn deref_mut (&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(loom)]
+impl<T: Copy, A> Drop for GetMut<'_, T, A> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        (self.write)(self.atomic, self.value)
+    }
+}
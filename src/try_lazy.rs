@@ -0,0 +1,370 @@
+//! Lazy values whose initialization may fail
+
+use core::{cell::UnsafeCell, marker::PhantomData, mem::{ManuallyDrop, MaybeUninit}, ptr::addr_of_mut, sync::atomic::Ordering};
+use crate::sync::{AtomicU8, Track};
+use crate::{Initializer, initialize_erased, UNINIT, INITIALIZING, INIT, POISONED, FAILED, policy::{PoisonPolicy, Repanic, CacheErrors, RetryErrors}};
+use crate::strategy::{WaitStrategy, DefaultStrategy};
+
+#[cfg(feature = "std")]
+use crate::utils::{Payload, catch_payload};
+
+/// A lazy value whose initialization function may fail.
+///
+/// What happens once the function returns an error is decided by the error policy ```M```: with [`CacheErrors`] (the
+/// default), every access (including those of the threads that were waiting for it) returns a reference to that error.
+/// With [`RetryErrors`], the error is returned to the thread that ran the function, and the next access calls it again.
+/// Either way, only one thread runs the function at a time. If it panics, the value is poisoned, like a [`Lazy`](crate::Lazy)'s
+pub struct TryLazy<T, E, F = fn() -> Result<T, E>, M = CacheErrors> {
+    state: AtomicU8,
+    slot: UnsafeCell<TrySlot<T, E, F>>,
+    track: Track,
+    policy: PhantomData<M>
+}
+
+/// Storage of a ```TryLazy```, like a [`Slot`](crate::Slot): the initializer while ```UNINIT```, the value once ```INIT```,
+/// the error once ```FAILED```, and (under ```std```) the panic message once ```POISONED```
+#[repr(C)]
+union TrySlot<T, E, F> {
+    value: ManuallyDrop<MaybeUninit<T>>,
+    error: ManuallyDrop<MaybeUninit<E>>,
+    f: ManuallyDrop<MaybeUninit<F>>,
+    #[cfg(feature = "std")]
+    payload: ManuallyDrop<Payload>
+}
+
+impl<T, E, F> TryLazy<T, E, F> {
+    loom_const! {
+        /// Builds a new ```TryLazy``` value that caches the error of its initializer
+        #[inline(always)]
+        pub const fn new (f: F) -> Self {
+            Self::with_policy(f)
+        }
+    }
+
+    loom_const! {
+        /// Builds a new ```TryLazy``` value that caches the error of its initializer. Same as [`new`](TryLazy::new)
+        #[inline(always)]
+        pub const fn new_cached (f: F) -> Self {
+            Self::with_policy(f)
+        }
+    }
+}
+
+impl<T, E, F> TryLazy<T, E, F, RetryErrors> {
+    loom_const! {
+        /// Builds a new ```TryLazy``` value that calls its initializer again on the next access whenever it fails
+        #[inline(always)]
+        pub const fn new_retrying (f: F) -> Self {
+            Self::with_policy(f)
+        }
+    }
+}
+
+impl<T, E, F, M> TryLazy<T, E, F, M> {
+    loom_const! {
+        /// Builds a new ```TryLazy``` value with a custom error policy
+        #[inline(always)]
+        pub const fn with_policy (f: F) -> Self {
+            Self {
+                state: AtomicU8::new(UNINIT),
+                slot: UnsafeCell::new(TrySlot { f: ManuallyDrop::new(MaybeUninit::new(f)) }),
+                track: Track::new(),
+                policy: PhantomData
+            }
+        }
+    }
+
+    /// Returns ```true``` if the value is uninitialized, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_uninit (&self) -> bool {
+        self.state.load(Ordering::Acquire) == UNINIT
+    }
+
+    /// Returns ```true``` if the value is currently initializing, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initializing (&self) -> bool {
+        self.state.load(Ordering::Acquire) == INITIALIZING
+    }
+
+    /// Returns ```true``` if the value has initialized successfully, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_initialized (&self) -> bool {
+        self.state.load(Ordering::Acquire) == INIT
+    }
+
+    /// Returns ```true``` if the initialization function returned an error, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_failed (&self) -> bool {
+        self.state.load(Ordering::Acquire) == FAILED
+    }
+
+    /// Returns ```true``` if the initialization function panicked, ```false``` otherwise
+    #[inline(always)]
+    pub fn is_poisoned (&self) -> bool {
+        self.state.load(Ordering::Acquire) == POISONED
+    }
+
+    /// Returns ```Some(Ok(ref value))``` or ```Some(Err(ref error))``` if the initialization has finished, ```None``` otherwise.
+    /// Errors are only ever returned if they're cached (see [`CacheErrors`])
+    #[inline(always)]
+    pub fn try_get (&self) -> Option<Result<&T, &E>> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                Some(Ok((*self.value()).assume_init_ref()))
+            },
+            FAILED => unsafe {
+                self.track.read();
+                Some(Err((*self.error()).assume_init_ref()))
+            },
+            _ => None
+        }
+    }
+
+    /// Replaces the outcome of the initialization (or the initializer, if it hasn't run) with a new initializer, so
+    /// that the next access runs it. Also clears the poisoning of an initializer that panicked
+    pub fn reset (&mut self, f: F) {
+        unsafe { self.drop_slot() }
+        *self.state.get_mut() = UNINIT;
+        unsafe { self.f().write(MaybeUninit::new(f)) }
+    }
+
+    /// Drops whatever the slot holds, as told by the state
+    unsafe fn drop_slot (&mut self) {
+        let state = *self.state.get_mut();
+        debug_assert_ne!(state, INITIALIZING, "TryLazy dropped while initializing");
+        self.track.write();
+
+        match state {
+            UNINIT => (*self.f()).assume_init_drop(),
+            INIT => (*self.value()).assume_init_drop(),
+            FAILED => (*self.error()).assume_init_drop(),
+            #[cfg(feature = "std")]
+            POISONED => core::ptr::drop_in_place(self.payload()),
+            _ => {}
+        }
+    }
+
+    /// Pointer to the value's storage
+    #[inline(always)]
+    fn value (&self) -> *mut MaybeUninit<T> {
+        unsafe { addr_of_mut!((*self.slot.get()).value).cast() }
+    }
+
+    /// Pointer to the error's storage
+    #[inline(always)]
+    fn error (&self) -> *mut MaybeUninit<E> {
+        unsafe { addr_of_mut!((*self.slot.get()).error).cast() }
+    }
+
+    /// Pointer to the initializer's storage
+    #[inline(always)]
+    fn f (&self) -> *mut MaybeUninit<F> {
+        unsafe { addr_of_mut!((*self.slot.get()).f).cast() }
+    }
+
+    /// Pointer to the panic payload's storage, only initialized while ```POISONED```
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn payload (&self) -> *mut Payload {
+        unsafe { addr_of_mut!((*self.slot.get()).payload).cast() }
+    }
+}
+
+impl<T, E, F: FnOnce() -> Result<T, E>> TryLazy<T, E, F, CacheErrors> {
+    /// Returns a reference to the inner value or error, initializing or waiting for it of necesary
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization function panicked (the value is poisoned)
+    #[inline(always)]
+    pub fn get (&self) -> Result<&T, &E> {
+        match self.try_get() {
+            Some(result) => result,
+            None => self.initialize_slow()
+        }
+    }
+
+    /// Slow path of [`get`](TryLazy::get): initializes the value, or waits for it to be initialized
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow (&self) -> Result<&T, &E> {
+        unsafe {
+            match initialize_erased(&self.state, &self.track, self.slot.get().cast(), core::ptr::null_mut(), Self::INITIALIZER) {
+                INIT => Ok((*self.value()).assume_init_ref()),
+                _ => Err((*self.error()).assume_init_ref())
+            }
+        }
+    }
+
+    const INITIALIZER: Initializer = Initializer {
+        init: init_try_slot::<T, E, F>,
+        poison: <Repanic as PoisonPolicy<F>>::poison,
+        wait: DefaultStrategy::wait,
+        notify: DefaultStrategy::notify,
+        #[cfg(feature = "std")]
+        name: core::any::type_name::<T>
+    };
+
+    /// Returns the inner value or error, initializing it if necessary
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization function panicked (the value is poisoned)
+    #[inline]
+    pub fn into_inner (self) -> Result<T, E> {
+        // initializes it if necessary, so that only the outcome is left to move out
+        let _ = self.get();
+        let mut this = ManuallyDrop::new(self);
+
+        let state = *this.state.get_mut();
+        unsafe {
+            match state {
+                INIT => Ok(core::ptr::read(this.value()).assume_init()),
+                _ => Err(core::ptr::read(this.error()).assume_init())
+            }
+        }
+    }
+}
+
+/// Runs the initializer in `slot` (a ```TrySlot<T, E, F>```), replacing it with its outcome
+unsafe fn init_try_slot<T, E, F: FnOnce() -> Result<T, E>> (slot: *mut u8, _: *mut u8) -> u8 {
+    let slot = slot.cast::<TrySlot<T, E, F>>();
+    let f = core::ptr::read(addr_of_mut!((*slot).f).cast::<MaybeUninit<F>>()).assume_init();
+
+    #[cfg(feature = "std")]
+    let result = catch_payload(f, addr_of_mut!((*slot).payload).cast());
+    #[cfg(not(feature = "std"))]
+    let result = f();
+
+    match result {
+        Ok(value) => {
+            addr_of_mut!((*slot).value).cast::<MaybeUninit<T>>().write(MaybeUninit::new(value));
+            INIT
+        },
+        Err(error) => {
+            addr_of_mut!((*slot).error).cast::<MaybeUninit<E>>().write(MaybeUninit::new(error));
+            FAILED
+        }
+    }
+}
+
+impl<T, E, F: FnMut() -> Result<T, E>> TryLazy<T, E, F, RetryErrors> {
+    /// Returns a reference to the inner value, initializing or waiting for it of necesary. If this call ran the
+    /// initializer and it failed, its error is returned, and the cell is left uninitialized. Threads that were waiting
+    /// for it run the initializer themselves, one at a time
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization function panicked (the value is poisoned)
+    #[inline(always)]
+    pub fn get (&self) -> Result<&T, E> {
+        match self.state.load(Ordering::Acquire) {
+            INIT => unsafe {
+                self.track.read();
+                Ok((*self.value()).assume_init_ref())
+            },
+            _ => self.initialize_slow()
+        }
+    }
+
+    /// Slow path of [`get`](TryLazy::get): attempts to initialize the value, or waits for it to be initialized
+    #[cold]
+    #[inline(never)]
+    fn initialize_slow (&self) -> Result<&T, E> {
+        let mut error = MaybeUninit::<E>::uninit();
+        unsafe {
+            match initialize_erased(&self.state, &self.track, self.slot.get().cast(), error.as_mut_ptr().cast(), Self::INITIALIZER) {
+                INIT => Ok((*self.value()).assume_init_ref()),
+                _ => Err(error.assume_init())
+            }
+        }
+    }
+
+    const INITIALIZER: Initializer = Initializer {
+        init: init_retrying::<T, E, F>,
+        poison: <Repanic as PoisonPolicy<F>>::poison,
+        wait: DefaultStrategy::wait,
+        notify: DefaultStrategy::notify,
+        #[cfg(feature = "std")]
+        name: core::any::type_name::<T>
+    };
+
+    /// Returns the inner value, initializing it if necessary, or the error of the initializer if it fails (dropping the cell)
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initialization function panicked (the value is poisoned)
+    #[inline]
+    pub fn into_inner (self) -> Result<T, E> {
+        self.get()?;
+        let this = ManuallyDrop::new(self);
+        unsafe { Ok(core::ptr::read(this.value()).assume_init()) }
+    }
+}
+
+/// Runs the initializer in `slot` (a ```TrySlot<T, E, F>```) without moving it out. If it succeeds, replaces it with
+/// the value. Otherwise, it stays in the slot, and the error is written to `error` (a ```MaybeUninit<E>```)
+unsafe fn init_retrying<T, E, F: FnMut() -> Result<T, E>> (slot: *mut u8, error: *mut u8) -> u8 {
+    let slot = slot.cast::<TrySlot<T, E, F>>();
+    let f = addr_of_mut!((*slot).f).cast::<F>();
+
+    // a panicking initializer is dropped before its payload takes its place
+    #[cfg(feature = "std")]
+    let result = catch_payload(|| {
+        let guard = DropOnUnwind(f);
+        let result = (*f)();
+        core::mem::forget(guard);
+        result
+    }, addr_of_mut!((*slot).payload).cast());
+    #[cfg(not(feature = "std"))]
+    let result = (*f)();
+
+    match result {
+        Ok(value) => {
+            core::ptr::drop_in_place(f);
+            addr_of_mut!((*slot).value).cast::<MaybeUninit<T>>().write(MaybeUninit::new(value));
+            INIT
+        },
+        Err(e) => {
+            error.cast::<MaybeUninit<E>>().write(MaybeUninit::new(e));
+            UNINIT
+        }
+    }
+}
+
+/// Drops the initializer it points to, if the initializer panics
+#[cfg(feature = "std")]
+struct DropOnUnwind<F>(*mut F);
+
+#[cfg(feature = "std")]
+impl<F> Drop for DropOnUnwind<F> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(self.0) }
+    }
+}
+
+impl<T: core::fmt::Debug, E: core::fmt::Debug, F, M> core::fmt::Debug for TryLazy<T, E, F, M> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("TryLazy");
+        match self.try_get() {
+            Some(result) => tuple.field(&result),
+            None if self.is_initializing() => tuple.field(&format_args!("<initializing>")),
+            None if self.is_poisoned() => tuple.field(&format_args!("<poisoned>")),
+            None => tuple.field(&format_args!("<uninitialized>"))
+        };
+        tuple.finish()
+    }
+}
+
+impl<T, E, F, M> Drop for TryLazy<T, E, F, M> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe { self.drop_slot() }
+    }
+}
+
+// Like a `Lazy`, but handing out `&E` too
+unsafe impl<T: Send, E: Send, F: Send, M> Send for TryLazy<T, E, F, M> {}
+unsafe impl<T: Send + Sync, E: Send + Sync, F: Send, M> Sync for TryLazy<T, E, F, M> {}
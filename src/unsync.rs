@@ -0,0 +1,198 @@
+//! Single-threaded counterparts of the cells in the crate root, with no atomics
+
+use core::{cell::{Cell, UnsafeCell}, marker::PhantomData, mem::{ManuallyDrop, MaybeUninit}};
+use crate::{UNINIT, INITIALIZING, INIT};
+
+/// A cell that's initialized at most once, by a function supplied at each call site, for a single thread.
+///
+/// Unlike [`OnceCell`](crate::OnceCell), it uses no atomics, and it's not ```Sync```. Since there's nobody to wait for,
+/// trying to initialize it from within its own initialization function panics. If the function panics (or fails,
+/// through [`get_or_try_init`](OnceCell::get_or_try_init)), the cell is left uninitialized.
+///
+/// It allows caching values behind a shared reference:
+///
+/// ```rust
+/// use laizy::unsync::OnceCell;
+///
+/// struct Token<'a> {
+///     source: &'a str,
+///     lowercase: OnceCell<String>
+/// }
+///
+/// impl Token<'_> {
+///     fn lowercase (&self) -> &str {
+///         self.lowercase.get_or_init(|| self.source.to_lowercase())
+///     }
+/// }
+///
+/// let token = Token { source: "SELECT", lowercase: OnceCell::new() };
+/// assert_eq!(token.lowercase(), "select");
+/// ```
+pub struct OnceCell<T> {
+    state: Cell<u8>,
+    value: UnsafeCell<MaybeUninit<T>>,
+    // not `Sync`
+    _local: PhantomData<*const ()>
+}
+
+impl<T> OnceCell<T> {
+    /// Builds a new, uninitialized ```OnceCell```
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self {
+            state: Cell::new(UNINIT),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            _local: PhantomData
+        }
+    }
+
+    /// Returns ```Some(ref value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get (&self) -> Option<&T> {
+        match self.state.get() {
+            INIT => unsafe { Some((*self.value.get()).assume_init_ref()) },
+            _ => None
+        }
+    }
+
+    /// Returns ```Some(ref mut value)``` if the value has already initialized, ```None``` otherwise
+    #[inline(always)]
+    pub fn get_mut (&mut self) -> Option<&mut T> {
+        match self.state.get() {
+            INIT => unsafe { Some(self.value.get_mut().assume_init_mut()) },
+            _ => None
+        }
+    }
+
+    /// Initializes the cell with `value`.
+    ///
+    /// Returns `value` back if the cell is already initialized, or it's being initialized
+    pub fn set (&self, value: T) -> Result<(), T> {
+        if self.state.get() != UNINIT {
+            return Err(value)
+        }
+
+        unsafe { self.value.get().write(MaybeUninit::new(value)) }
+        self.state.set(INIT);
+        Ok(())
+    }
+
+    /// Returns a reference to the value, initializing it with `f` if necessary
+    ///
+    /// # Panics
+    ///
+    /// If `f` initializes the cell itself
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> T> (&self, f: F) -> &T {
+        match self.get_or_try_init(|| Ok::<T, core::convert::Infallible>(f())) {
+            Ok(value) => value,
+            Err(e) => match e {}
+        }
+    }
+
+    /// Like [`get_or_init`](OnceCell::get_or_init), but with a fallible function. If `f` fails, its error is returned and
+    /// the cell is left uninitialized
+    ///
+    /// # Panics
+    ///
+    /// If `f` initializes the cell itself
+    #[inline]
+    pub fn get_or_try_init<E, F: FnOnce() -> Result<T, E>> (&self, f: F) -> Result<&T, E> {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => self.try_initialize_slow(f)
+        }
+    }
+
+    /// Takes the value out of the cell if it's initialized, leaving it uninitialized
+    #[inline]
+    pub fn take (&mut self) -> Option<T> {
+        match self.state.get() {
+            INIT => unsafe {
+                self.state.set(UNINIT);
+                Some(self.value.get_mut().assume_init_read())
+            },
+            _ => None
+        }
+    }
+
+    /// Returns the inner value if it's initialized, ```None``` otherwise
+    #[inline]
+    pub fn into_inner (self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        this.take()
+    }
+
+    /// Slow path of [`get_or_try_init`](OnceCell::get_or_try_init)
+    #[cold]
+    #[inline(never)]
+    fn try_initialize_slow<E, F: FnOnce() -> Result<T, E>> (&self, f: F) -> Result<&T, E> {
+        if self.state.replace(INITIALIZING) == INITIALIZING {
+            reentrant()
+        }
+
+        // leaves it uninitialized if `f` fails, or panics
+        let unlock = Unlock(&self.state);
+        let value = f()?;
+        core::mem::forget(unlock);
+
+        unsafe {
+            self.value.get().write(MaybeUninit::new(value));
+            self.state.set(INIT);
+            Ok((*self.value.get()).assume_init_ref())
+        }
+    }
+}
+
+/// Leaves the cell uninitialized if dropped before being forgotten (i.e. if its initialization failed)
+struct Unlock<'a>(&'a Cell<u8>);
+
+impl Drop for Unlock<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.set(UNINIT)
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn reentrant () -> ! {
+    panic!("OnceCell initialized from within its own initialization function")
+}
+
+impl<T> Default for OnceCell<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    #[inline(always)]
+    fn from(value: T) -> Self {
+        Self { state: Cell::new(INIT), value: UnsafeCell::new(MaybeUninit::new(value)), _local: PhantomData }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut tuple = f.debug_tuple("OnceCell");
+        match self.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninitialized>"))
+        };
+        tuple.finish()
+    }
+}
+
+impl<T> Drop for OnceCell<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if self.state.get() == INIT {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+// It can be moved to another thread along with its value, as long as it's never shared
+unsafe impl<T: Send> Send for OnceCell<T> {}
@@ -1,41 +1,714 @@
+use crate::sync::AtomicU8;
+#[cfg(any(feature = "std", feature = "futures"))]
+use core::sync::atomic::Ordering;
+
+#[cfg(feature = "std")]
+use {core::any::Any, std::boxed::Box};
+
+/// Payload of a panicked initialization function
+#[cfg(feature = "std")]
+pub(crate) type Payload = Option<Box<dyn Any + Send>>;
+
+/// Calls `f`, writing a copy of its panic message into `payload` (without dropping its previous contents) if it panics,
+/// and resuming the panic with its own payload, so that the caller gets it back as is
+#[cfg(feature = "std")]
+#[inline(always)]
+pub(crate) unsafe fn catch_payload<T> (f: impl FnOnce() -> T, payload: *mut Payload) -> T {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(e) => {
+            payload.write(copy_message(&*e));
+            std::panic::resume_unwind(e)
+        }
+    }
+}
+
+/// Copies the message of a panic payload (keeping its type), if it has one
+#[cfg(feature = "std")]
+fn copy_message (payload: &(dyn Any + Send)) -> Payload {
+    if let Some(msg) = payload.downcast_ref::<&'static str>() {
+        return Some(Box::new(*msg))
+    }
+    payload.downcast_ref::<std::string::String>().map(|msg| Box::new(msg.clone()) as _)
+}
+
+/// Returns the message of a panic payload, if it has one
+#[cfg(feature = "std")]
+pub(crate) fn panic_message (payload: &(dyn Any + Send)) -> Option<&str> {
+    match payload.downcast_ref::<&'static str>() {
+        Some(msg) => Some(msg),
+        None => payload.downcast_ref::<std::string::String>().map(|msg| msg.as_str())
+    }
+}
+
+/// Poisons the state it guards if dropped before being disarmed (i.e. while unwinding out of an initializer)
+pub(crate) struct PoisonGuard<'a> {
+    state: &'a AtomicU8,
+    poison: fn(&AtomicU8),
+    notify: fn(&AtomicU8)
+}
+
+impl<'a> PoisonGuard<'a> {
+    #[inline(always)]
+    pub const fn new (state: &'a AtomicU8, poison: fn(&AtomicU8), notify: fn(&AtomicU8)) -> Self {
+        Self { state, poison, notify }
+    }
+
+    /// Consumes the guard without poisoning the state
+    #[inline(always)]
+    pub fn disarm (self) {
+        core::mem::forget(self)
+    }
+}
+
+impl Drop for PoisonGuard<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        (self.poison)(self.state);
+        (self.notify)(self.state)
+    }
+}
+
+/// Threads parked while waiting on a state, alongside the state's address
+#[cfg(feature = "std")]
+static PARKED: std::sync::Mutex<std::vec::Vec<(usize, std::thread::Thread)>> = std::sync::Mutex::new(std::vec::Vec::new());
+
+// Steps after which `Backoff` stops spinning (and starts yielding the thread) and stops growing
+#[cfg(not(loom))]
+const SPIN_LIMIT: u32 = 6;
+#[cfg_attr(loom, allow(dead_code))]
+const YIELD_LIMIT: u32 = 10;
+
+/// Exponential backoff for spin-waiting loops
+pub(crate) struct Backoff {
+    #[cfg_attr(loom, allow(dead_code))]
+    step: u32
+}
+
+impl Backoff {
+    #[inline(always)]
+    pub const fn new () -> Self {
+        Self { step: 0 }
+    }
+
+    /// Backs off, spinning exponentially longer on every call, and yielding the thread (under ```std```) once the spin limit is reached
+    #[cfg(not(loom))]
+    #[inline]
+    pub fn snooze (&mut self) {
+        if self.step <= SPIN_LIMIT {
+            for _ in 0..(1 << self.step) { core::hint::spin_loop() }
+        } else {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+            #[cfg(not(feature = "std"))]
+            for _ in 0..(1 << SPIN_LIMIT) { core::hint::spin_loop() }
+        }
+
+        if self.step <= YIELD_LIMIT {
+            self.step += 1
+        }
+    }
+
+    /// Yields to loom, which only has to know the thread is waiting (it would go through every spin otherwise). Backing off
+    /// never completes under loom, so that [`Park`](crate::strategy::Park) doesn't park on its behalf
+    #[cfg(loom)]
+    #[inline]
+    pub fn snooze (&mut self) {
+        loom::thread::yield_now()
+    }
+
+    /// Returns ```true``` once backing off has stopped growing, meaning the thread should block instead (if possible)
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn is_completed (&self) -> bool {
+        self.step > YIELD_LIMIT
+    }
+}
+
+/// Parks the thread until `state` stops being `until`, returning the new state
+#[cfg(feature = "std")]
+#[cold]
+pub(crate) fn park (state: &AtomicU8, until: u8) -> u8 {
+    park_until(state, until, None)
+}
+
+/// Parks the thread until `state` stops being `until` or the `deadline` is reached, returning the current state
+#[cfg(feature = "std")]
+#[cold]
+pub(crate) fn park_until (state: &AtomicU8, until: u8, deadline: Option<std::time::Instant>) -> u8 {
+    let key = state as *const AtomicU8 as usize;
+    let current = std::thread::current();
+
+    loop {
+        let mut parked = PARKED.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let registered = parked.iter().position(|(k, thread)| *k == key && thread.id() == current.id());
+        let expired = deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline);
+
+        // the state is checked while holding the lock, so `unpark` can't run in between the check and the registration
+        match (state.load(Ordering::Acquire), registered) {
+            (state, registered) if state != until || expired => {
+                if let Some(idx) = registered { parked.swap_remove(idx); }
+                return state
+            },
+            (_, None) => parked.push((key, current.clone())),
+            (_, Some(_)) => {}
+        }
+
+        drop(parked);
+        match deadline {
+            Some(deadline) => std::thread::park_timeout(deadline.saturating_duration_since(std::time::Instant::now())),
+            None => std::thread::park()
+        }
+    }
+}
+
+/// Wakes every thread parked on `state`. Must be called after `state` changes
+#[cfg(feature = "std")]
+pub(crate) fn unpark (state: &AtomicU8) {
+    let key = state as *const AtomicU8 as usize;
+    let mut parked = PARKED.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    let mut i = 0;
+    while i < parked.len() {
+        match parked[i].0 == key {
+            true => parked.swap_remove(i).1.unpark(),
+            false => i += 1
+        }
+    }
+}
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "futures")] {
-        use core::sync::atomic::AtomicU8;
-        use core::{pin::Pin, task::{Context, Poll}};
-        use futures::{Future};
-        use futures::task::AtomicWaker;
+        use core::{pin::Pin, cell::{Cell, UnsafeCell}, marker::PhantomPinned, ptr::null_mut};
+        use core::task::{Context, Poll, Waker};
+        use crate::sync::{AtomicBool, Track};
+        use core::future::Future;
+
+        /// Tasks waiting on an initialization, as an intrusive list of the [`AwaitInit`] futures registered on it.
+        /// It's a queue: waiters are woken in the order they first registered (polling again keeps their place)
+        pub(crate) struct Waiters {
+            lock: AtomicBool,
+            list: UnsafeCell<List>,
+            /// Accesses to the list (and the nodes in it)
+            track: Track
+        }
+
+        struct List {
+            queue: Queue,
+            /// Waker of a caller that can't own a node (see [`AsyncLazy::poll_get`](crate::AsyncLazy::poll_get))
+            untracked: Option<Waker>,
+            /// Waker of the task running the initialization future (see [`AsyncLazy::abort`](crate::AsyncLazy::abort))
+            driver: Option<Waker>
+        }
+
+        /// Tasks waiting on the initialization of a [`local::AsyncLazy`](crate::asnc::local::AsyncLazy): the same queue
+        /// as [`Waiters`], without the lock (nor the untracked and driver wakers)
+        pub(crate) struct LocalWaiters {
+            queue: UnsafeCell<Queue>
+        }
+
+        /// Intrusive queue of nodes, owned by the [`AwaitInit`] futures that registered them
+        struct Queue {
+            head: *mut Node,
+            tail: *mut Node,
+            /// Number of nodes in the queue
+            len: usize
+        }
+
+        /// Entry of a queue, owned by the [`AwaitInit`] future that registered it. Only accessed with the list locked (if it has a lock)
+        pub(crate) struct Node {
+            waker: Option<Waker>,
+            prev: *mut Node,
+            next: *mut Node,
+            queued: bool
+        }
+
+        /// List of waiters an [`AwaitInit`] future registers on
+        pub(crate) trait WaitList {
+            /// Registers `node` to be woken with `waker`, or updates its waker if it already was
+            unsafe fn register (&self, node: *mut Node, waker: &Waker);
+            /// Removes `node` from the list, if it's still there
+            unsafe fn deregister (&self, node: *mut Node);
+            /// Wakes every registered waiter, removing them from the list
+            fn wake_all (&self);
+        }
+
+        /// State of a cell, as seen by the futures waiting on it: an atomic, or a ```Cell``` for the single-threaded
+        /// [`local::AsyncLazy`](crate::asnc::local::AsyncLazy)
+        pub(crate) trait WaitState {
+            /// Reads the state, synchronizing with the end of the initialization
+            fn current (&self) -> u8;
+            /// Ends the initialization in `state`, notifying the threads parked on it
+            fn settle (&self, state: u8);
+        }
+
+        impl WaitState for AtomicU8 {
+            #[inline(always)]
+            fn current (&self) -> u8 {
+                self.load(Ordering::Acquire)
+            }
+
+            #[inline(always)]
+            fn settle (&self, state: u8) {
+                self.store(state, Ordering::Release);
+                <crate::strategy::DefaultStrategy as crate::strategy::WaitStrategy>::notify(self);
+            }
+        }
+
+        impl WaitState for Cell<u8> {
+            #[inline(always)]
+            fn current (&self) -> u8 {
+                self.get()
+            }
+
+            #[inline(always)]
+            fn settle (&self, state: u8) {
+                self.set(state)
+            }
+        }
+
+        impl Queue {
+            const EMPTY: Self = Self { head: null_mut(), tail: null_mut(), len: 0 };
+
+            /// Registers `node` to be woken with `waker`, or updates its waker if it already was
+            unsafe fn register (&mut self, node: *mut Node, waker: &Waker) {
+                match &(*node).waker {
+                    Some(prev) if prev.will_wake(waker) => {},
+                    _ => (*node).waker = Some(waker.clone())
+                }
+
+                if !(*node).queued {
+                    (*node).prev = self.tail;
+                    (*node).next = null_mut();
+                    match self.tail.is_null() {
+                        true => self.head = node,
+                        false => (*self.tail).next = node
+                    }
+                    self.tail = node;
+                    self.len += 1;
+                    (*node).queued = true;
+                }
+            }
+
+            /// Removes `node` from the queue, if it's still there
+            unsafe fn deregister (&mut self, node: *mut Node) {
+                if !(*node).queued {
+                    return
+                }
+
+                match (*node).prev.is_null() {
+                    true => self.head = (*node).next,
+                    false => (*(*node).prev).next = (*node).next
+                }
+                match (*node).next.is_null() {
+                    true => self.tail = (*node).prev,
+                    false => (*(*node).next).prev = (*node).prev
+                }
+                self.len -= 1;
+                (*node).queued = false;
+            }
+
+            /// Removes the oldest node of the queue, taking its waker
+            unsafe fn pop_front (&mut self) -> Option<Option<Waker>> {
+                let node = self.head;
+                if node.is_null() {
+                    return None
+                }
+
+                self.head = (*node).next;
+                match self.head.is_null() {
+                    true => self.tail = null_mut(),
+                    false => (*self.head).prev = null_mut()
+                }
+                self.len -= 1;
+                (*node).queued = false;
+                Some((*node).waker.take())
+            }
+        }
+
+        impl Waiters {
+            loom_const! {
+                #[inline(always)]
+                pub const fn new () -> Self {
+                    Self {
+                        lock: AtomicBool::new(false),
+                        list: UnsafeCell::new(List { queue: Queue::EMPTY, untracked: None, driver: None }),
+                        track: Track::new()
+                    }
+                }
+            }
+
+            /// Wakes every registered waiter, removing them from the list
+            pub fn wake_all (&self) {
+                self.wake_queued();
+
+                let (untracked, _driver) = {
+                    let _guard = self.lock();
+                    let list = unsafe { &mut *self.list.get() };
+                    // the driver only calls this once it's done, so it's just dropped
+                    (list.untracked.take(), list.driver.take())
+                };
+
+                if let Some(waker) = untracked {
+                    waker.wake()
+                }
+            }
+
+            /// Registers `waker` as the one of the task running the initialization future
+            pub fn register_driver (&self, waker: &Waker) {
+                let _prev = {
+                    let _guard = self.lock();
+                    let list = unsafe { &mut *self.list.get() };
+                    match &list.driver {
+                        Some(prev) if prev.will_wake(waker) => None,
+                        _ => list.driver.replace(waker.clone())
+                    }
+                };
+            }
+
+            /// Wakes the task running the initialization future, if it's registered
+            pub fn wake_driver (&self) {
+                let driver = {
+                    let _guard = self.lock();
+                    unsafe { (*self.list.get()).driver.take() }
+                };
+
+                if let Some(waker) = driver {
+                    waker.wake()
+                }
+            }
+
+            /// Wakes every waiter registered with a node, removing them from the list
+            pub fn wake_queued (&self) {
+                loop {
+                    // wake outside of the lock, since waking may run arbitrary code
+                    let waker = {
+                        let _guard = self.lock();
+                        match unsafe { (*self.list.get()).queue.pop_front() } {
+                            Some(waker) => waker,
+                            None => return
+                        }
+                    };
+
+                    if let Some(waker) = waker {
+                        waker.wake()
+                    }
+                }
+            }
+
+            /// Forgets every registered waiter. With exclusive access to the list, those can only belong to leaked futures,
+            /// which will never be polled nor dropped again
+            pub fn clear (&mut self) {
+                self.track.write();
+                *self.list.get_mut() = List { queue: Queue::EMPTY, untracked: None, driver: None }
+            }
+
+            /// Registers `waker` to be woken by [`wake_all`](Waiters::wake_all), in place of the previous untracked waker (which is
+            /// woken right away, since it won't be anymore)
+            pub fn register_untracked (&self, waker: &Waker) {
+                let prev = {
+                    let _guard = self.lock();
+                    let list = unsafe { &mut *self.list.get() };
+                    match &list.untracked {
+                        Some(prev) if prev.will_wake(waker) => None,
+                        _ => list.untracked.replace(waker.clone())
+                    }
+                };
+
+                if let Some(prev) = prev {
+                    prev.wake()
+                }
+            }
+
+            /// Returns the number of registered waiters, untracked one included
+            pub fn len (&self) -> usize {
+                let _guard = self.lock();
+                let list = unsafe { &*self.list.get() };
+                list.queue.len + list.untracked.is_some() as usize
+            }
+
+            #[inline]
+            fn lock (&self) -> WaitersGuard<'_> {
+                while self.lock.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                    crate::sync::spin_loop()
+                }
+                self.track.write();
+                WaitersGuard { lock: &self.lock }
+            }
+        }
+
+        impl WaitList for Waiters {
+            #[inline]
+            unsafe fn register (&self, node: *mut Node, waker: &Waker) {
+                let _guard = self.lock();
+                (*self.list.get()).queue.register(node, waker)
+            }
+
+            #[inline]
+            unsafe fn deregister (&self, node: *mut Node) {
+                let _guard = self.lock();
+                (*self.list.get()).queue.deregister(node)
+            }
+
+            #[inline(always)]
+            fn wake_all (&self) {
+                Waiters::wake_all(self)
+            }
+        }
+
+        impl Drop for Waiters {
+            #[inline(always)]
+            fn drop(&mut self) {
+                // the untracked and driver wakers are dropped along with the list
+                self.track.write()
+            }
+        }
+
+        // the list is only ever accessed with the lock held
+        unsafe impl Send for Waiters {}
+        unsafe impl Sync for Waiters {}
+        unsafe impl Send for Node {}
 
-        /// Flag awaiter
-        pub struct AwaitInit<'a> {
-            state: &'a AtomicU8,
-            waker: &'a AtomicWaker,
-            target: u8
+        struct WaitersGuard<'a> {
+            lock: &'a AtomicBool
         }
 
-        impl<'a> AwaitInit<'a> {
+        impl Drop for WaitersGuard<'_> {
             #[inline(always)]
-            pub const fn new (target: u8, state: &'a AtomicU8, waker: &'a AtomicWaker) -> Self {
+            fn drop(&mut self) {
+                self.lock.store(false, Ordering::Release)
+            }
+        }
+
+        impl LocalWaiters {
+            #[inline(always)]
+            pub const fn new () -> Self {
+                Self { queue: UnsafeCell::new(Queue::EMPTY) }
+            }
+        }
+
+        // a local cell is only ever accessed by one thread, so there's no need for a lock. Borrows of the queue never
+        // outlive the call that made them, and waking (which may run arbitrary code) happens outside of them
+        impl WaitList for LocalWaiters {
+            #[inline]
+            unsafe fn register (&self, node: *mut Node, waker: &Waker) {
+                (*self.queue.get()).register(node, waker)
+            }
+
+            #[inline]
+            unsafe fn deregister (&self, node: *mut Node) {
+                (*self.queue.get()).deregister(node)
+            }
+
+            fn wake_all (&self) {
+                while let Some(waker) = unsafe { (*self.queue.get()).pop_front() } {
+                    if let Some(waker) = waker {
+                        waker.wake()
+                    }
+                }
+            }
+        }
+
+        /// Flag awaiter. Resolves to the new state once it stops being ```INITIALIZING``` (or any state before the value
+        /// is settled, see [`AwaitInit::settled`])
+        pub struct AwaitInit<'a, S: WaitState = AtomicU8, W: WaitList = Waiters> {
+            state: &'a S,
+            waiters: &'a W,
+            pending: fn(u8) -> bool,
+            node: UnsafeCell<Node>,
+            // `waiters` points to `node` while it's registered
+            _pinned: PhantomPinned
+        }
+
+        impl<'a, S: WaitState, W: WaitList> AwaitInit<'a, S, W> {
+            #[inline(always)]
+            pub const fn new (state: &'a S, waiters: &'a W) -> Self {
+                Self::with(state, waiters, |state| state == crate::INITIALIZING)
+            }
+
+            /// Also waits while the value is uninitialized, or left partially polled by ```poll_get``` (or a dropped ```get``` future)
+            #[inline(always)]
+            pub const fn settled (state: &'a S, waiters: &'a W) -> Self {
+                Self::with(state, waiters, |state| matches!(state, crate::UNINIT | crate::INITIALIZING | crate::PARKED | crate::SUSPENDED))
+            }
+
+            #[inline(always)]
+            const fn with (state: &'a S, waiters: &'a W, pending: fn(u8) -> bool) -> Self {
                 Self {
                     state,
-                    waker,
-                    target
+                    waiters,
+                    pending,
+                    node: UnsafeCell::new(Node { waker: None, prev: null_mut(), next: null_mut(), queued: false }),
+                    _pinned: PhantomPinned
                 }
             }
         }
 
-        impl Future for AwaitInit<'_> {
-            type Output = ();
+        impl<S: WaitState, W: WaitList> Future for AwaitInit<'_, S, W> {
+            type Output = u8;
 
+            #[inline]
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = self.into_ref().get_ref();
+                match this.state.current() {
+                    state if (this.pending)(state) => {},
+                    state => return Poll::Ready(state)
+                }
+
+                // the node is pinned, and removed from the list before it's dropped
+                unsafe { this.waiters.register(this.node.get(), cx.waker()) }
+
+                // check again, in case the initialization finished (and woke the list) before we registered
+                match this.state.current() {
+                    state if (this.pending)(state) => Poll::Pending,
+                    state => Poll::Ready(state)
+                }
+            }
+        }
+
+        // the node is only accessed through `poll` and `drop` (which take it mutably), with the list locked
+        unsafe impl<S: WaitState + Sync, W: WaitList + Sync> Sync for AwaitInit<'_, S, W> {}
+
+        impl<S: WaitState, W: WaitList> Drop for AwaitInit<'_, S, W> {
+            #[inline]
+            fn drop(&mut self) {
+                unsafe { self.waiters.deregister(self.node.get()) }
+            }
+        }
+
+        /// Polls two futures concurrently, resolving to both of their outputs
+        pub struct Join<A: Future, B: Future> {
+            a: MaybeDone<A>,
+            b: MaybeDone<B>
+        }
+
+        enum MaybeDone<F: Future> {
+            Pending(F),
+            Done(F::Output),
+            Taken
+        }
+
+        impl<A: Future, B: Future> Join<A, B> {
             #[inline(always)]
+            pub const fn new (a: A, b: B) -> Self {
+                Self { a: MaybeDone::Pending(a), b: MaybeDone::Pending(b) }
+            }
+        }
+
+        impl<F: Future> MaybeDone<F> {
+            /// Returns ```true``` once the future has resolved
+            #[inline]
+            fn poll (self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+                // the future is never moved out of while pinned, only dropped in place by assignment
+                let this = unsafe { self.get_unchecked_mut() };
+                if let MaybeDone::Pending(f) = this {
+                    match unsafe { Pin::new_unchecked(f) }.poll(cx) {
+                        Poll::Ready(output) => *this = MaybeDone::Done(output),
+                        Poll::Pending => return false
+                    }
+                }
+                true
+            }
+
+            #[inline]
+            fn take (self: Pin<&mut Self>) -> F::Output {
+                match core::mem::replace(unsafe { self.get_unchecked_mut() }, MaybeDone::Taken) {
+                    MaybeDone::Done(output) => output,
+                    _ => panic!("`Join` polled after completion")
+                }
+            }
+        }
+
+        impl<A: Future, B: Future> Future for Join<A, B> {
+            type Output = (A::Output, B::Output);
+
             fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-                self.waker.register(cx.waker());
+                // both fields are structurally pinned
+                let this = unsafe { self.get_unchecked_mut() };
+                let (mut a, mut b) = unsafe { (Pin::new_unchecked(&mut this.a), Pin::new_unchecked(&mut this.b)) };
+
+                // poll both, even if the first one is still pending
+                let a_done = a.as_mut().poll(cx);
+                let b_done = b.as_mut().poll(cx);
+                match a_done && b_done {
+                    true => Poll::Ready((a.take(), b.take())),
+                    false => Poll::Pending
+                }
+            }
+        }
+
+        /// Drives `f` to completion on the current thread, parking it while `f` is pending
+        #[cfg(feature = "std")]
+        pub(crate) fn block_on<Fut: Future> (f: Fut) -> Fut::Output {
+            struct Unparker(std::thread::Thread);
 
-                if self.state.load(core::sync::atomic::Ordering::Acquire) == self.target {
-                    return Poll::Ready(())
+            impl std::task::Wake for Unparker {
+                #[inline]
+                fn wake(self: std::sync::Arc<Self>) {
+                    self.0.unpark()
                 }
 
-                Poll::Pending
+                #[inline]
+                fn wake_by_ref(self: &std::sync::Arc<Self>) {
+                    self.0.unpark()
+                }
+            }
+
+            let waker = Waker::from(std::sync::Arc::new(Unparker(std::thread::current())));
+            let mut cx = Context::from_waker(&waker);
+            let mut f = core::pin::pin!(f);
+
+            loop {
+                match f.as_mut().poll(&mut cx) {
+                    Poll::Ready(output) => return output,
+                    // spurious unparks just poll again
+                    Poll::Pending => std::thread::park()
+                }
+            }
+        }
+
+        /// Poisons (or cancels, if not unwinding) the state it guards and wakes its waiters if dropped before being disarmed
+        pub(crate) struct AsyncPoisonGuard<'a, S: WaitState = AtomicU8, W: WaitList = Waiters> {
+            state: &'a S,
+            waiters: &'a W,
+            /// State stored if the guard is dropped without panicking
+            cancelled: u8
+        }
+
+        impl<'a, S: WaitState, W: WaitList> AsyncPoisonGuard<'a, S, W> {
+            #[inline(always)]
+            pub const fn new (state: &'a S, waiters: &'a W) -> Self {
+                Self { state, waiters, cancelled: crate::CANCELLED }
+            }
+
+            /// Makes the state uninitialized again if the guard is dropped without panicking, so that someone else can retry
+            #[inline(always)]
+            pub const fn retrying (state: &'a S, waiters: &'a W) -> Self {
+                Self { state, waiters, cancelled: crate::UNINIT }
+            }
+
+            /// Consumes the guard without poisoning the state
+            #[inline(always)]
+            pub fn disarm (self) {
+                core::mem::forget(self)
+            }
+        }
+
+        impl<S: WaitState, W: WaitList> Drop for AsyncPoisonGuard<'_, S, W> {
+            #[inline(always)]
+            fn drop(&mut self) {
+                // without `std` we can't tell, but panics usually abort there anyway
+                #[cfg(feature = "std")]
+                let state = match std::thread::panicking() {
+                    true => crate::POISONED,
+                    false => self.cancelled
+                };
+                #[cfg(not(feature = "std"))]
+                let state = self.cancelled;
+
+                self.state.settle(state);
+                self.waiters.wake_all();
             }
         }
     }
-}
\ No newline at end of file
+}
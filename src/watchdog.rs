@@ -0,0 +1,103 @@
+//! Reports initializations that take suspiciously long
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::sync::AtomicU8;
+use std::{sync::{Mutex, PoisonError}, time::{Duration, Instant}, vec::Vec};
+use crate::INITIALIZING;
+
+/// Hook called with the type of the value and the time it's been initializing for
+type Hook = fn(&'static str, Duration);
+
+/// Whether a hook is installed, so initializations don't have to lock anything otherwise
+static HOOKED: AtomicBool = AtomicBool::new(false);
+static HOOK: Mutex<Option<(Duration, Hook)>> = Mutex::new(None);
+
+/// Initializations currently running (while a hook is installed)
+static RUNNING: Mutex<Vec<Running>> = Mutex::new(Vec::new());
+
+struct Running {
+    key: usize,
+    start: Instant,
+    reported: bool
+}
+
+/// Installs a global hook, called (at most once per initialization) whenever a thread waits on an initialization
+/// that's been running for longer than `threshold`, with the type of the value and the time it's been running for.
+///
+/// The check is made by the waiting threads, so initializations that nobody waits for are never reported
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn set_slow_init_hook (threshold: Duration, f: fn(&'static str, Duration)) {
+    *HOOK.lock().unwrap_or_else(PoisonError::into_inner) = Some((threshold, f));
+    HOOKED.store(true, Ordering::Release);
+}
+
+/// Removes the hook installed by [`set_slow_init_hook`], if any
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn clear_slow_init_hook () {
+    HOOKED.store(false, Ordering::Release);
+    *HOOK.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+#[inline(always)]
+fn hook () -> Option<(Duration, Hook)> {
+    match HOOKED.load(Ordering::Acquire) {
+        true => *HOOK.lock().unwrap_or_else(PoisonError::into_inner),
+        false => None
+    }
+}
+
+/// Registers the initialization of `state` while it's alive, so that waiters can tell how long it's been running for
+pub(crate) struct Watch<'a> {
+    state: &'a AtomicU8
+}
+
+impl<'a> Watch<'a> {
+    #[inline(always)]
+    pub fn start (state: &'a AtomicU8) -> Option<Self> {
+        hook()?;
+
+        let key = state as *const AtomicU8 as usize;
+        RUNNING.lock().unwrap_or_else(PoisonError::into_inner).push(Running { key, start: Instant::now(), reported: false });
+        Some(Self { state })
+    }
+}
+
+impl Drop for Watch<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        let key = self.state as *const AtomicU8 as usize;
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(idx) = running.iter().position(|running| running.key == key) {
+            running.swap_remove(idx);
+        }
+        drop(running);
+
+        // waiters may be watching regardless of the cell's wait strategy, and the state has changed by now
+        crate::utils::unpark(self.state)
+    }
+}
+
+/// Waits on the initialization of `state` until it finishes, or until it's been running for longer than the hook's threshold,
+/// in which case the hook is called (unless some other waiter already did)
+pub(crate) fn watch (state: &AtomicU8, name: &'static str) {
+    let Some((threshold, f)) = hook() else { return };
+    let key = state as *const AtomicU8 as usize;
+
+    loop {
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(entry) = running.iter_mut().find(|running| running.key == key && !running.reported) else { return };
+
+        let elapsed = entry.start.elapsed();
+        if elapsed >= threshold {
+            entry.reported = true;
+            drop(running);
+            return f(name, elapsed)
+        }
+
+        let deadline = entry.start + threshold;
+        drop(running);
+        if crate::utils::park_until(state, INITIALIZING, Some(deadline)) != INITIALIZING {
+            return
+        }
+    }
+}
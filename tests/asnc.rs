@@ -0,0 +1,2440 @@
+#![cfg(feature = "futures")]
+
+use std::{time::Duration, panic::AssertUnwindSafe};
+use futures::FutureExt;
+use laizy::AsyncLazy;
+
+#[tokio::test]
+async fn poisoned () {
+    let lazy = AsyncLazy::new(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        panic!("initialization failed")
+    });
+
+    let init = AssertUnwindSafe(async { *lazy.get().await }).catch_unwind();
+    let waiters = futures::future::join_all((0..3).map(|_| {
+        AssertUnwindSafe(async {
+            tokio::task::yield_now().await;
+            *lazy.get().await
+        }).catch_unwind()
+    }));
+
+    let (init, waiters): (Result<u8, _>, _) = tokio::time::timeout(Duration::from_secs(5), async { futures::join!(init, waiters) })
+        .await
+        .expect("waiters hanged");
+
+    assert!(init.is_err());
+    assert!(waiters.iter().all(Result::is_err));
+    assert!(lazy.is_poisoned());
+    assert!(lazy.try_get().is_none());
+    drop(lazy);
+}
+
+#[test]
+fn consume_on_other_thread () {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let consumer = std::thread::spawn(move || {
+        let lazy: AsyncLazy<Vec<u8>, futures::future::BoxFuture<'static, Vec<u8>>> = rx.recv().unwrap();
+        let value = futures::executor::block_on(lazy.into_inner());
+        drop(rx.recv().unwrap());
+        value
+    });
+
+    std::thread::spawn(move || {
+        for i in 1u8..=2 {
+            let lazy = AsyncLazy::new(Box::pin(async move { vec![i] }) as futures::future::BoxFuture<'static, _>);
+            futures::executor::block_on(lazy.get());
+            tx.send(lazy).unwrap();
+        }
+    }).join().unwrap();
+
+    assert_eq!(consumer.join().unwrap(), vec![1]);
+}
+
+#[tokio::test]
+async fn get_paths () {
+    let lazy = laizy::async_lazy(async { 1u8 });
+    let (a, b) = futures::join!(lazy.get(), lazy.get());
+    assert_eq!((*a, *b), (1, 1));
+    assert_eq!(*lazy.get().await, 1);
+
+    let mut other = laizy::async_lazy(async { 2u8 });
+    *other.get_mut().await += 1;
+    assert_eq!(*other.get_mut().await, 3);
+    assert_eq!(*other.get().await, 3);
+}
+
+#[test]
+fn drop_after_leaked_get () {
+    let lazy = laizy::async_lazy(async {
+        futures::future::pending::<()>().await;
+        vec![1u8]
+    });
+
+    let mut get = Box::pin(lazy.get());
+    assert!(get.as_mut().now_or_never().is_none());
+    std::mem::forget(get);
+
+    assert!(lazy.try_get().is_none());
+    drop(lazy);
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn state_queries () {
+    let lazy = laizy::async_lazy(tokio::time::sleep(Duration::from_millis(50)));
+    assert!(lazy.is_uninit() && !lazy.is_initializing() && !lazy.is_initialized());
+    assert!(!lazy.is_init() && !lazy.has_init());
+
+    let mut get = Box::pin(lazy.get());
+    assert!(get.as_mut().now_or_never().is_none());
+
+    // `is_init` means initializing, not initialized
+    assert!(lazy.is_initializing() && !lazy.is_initialized());
+    assert!(lazy.is_init() && !lazy.has_init());
+
+    get.await;
+    assert!(!lazy.is_uninit() && !lazy.is_initializing() && lazy.is_initialized());
+    assert!(!lazy.is_init() && lazy.has_init());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_waiters () {
+    let lazy = std::sync::Arc::new(AsyncLazy::new(Box::pin(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        7u8
+    }) as futures::future::BoxFuture<'static, u8>));
+
+    let mut init = Box::pin(lazy.get());
+    assert!(init.as_mut().now_or_never().is_none());
+
+    // a waiter that registers, and is cancelled before the initialization finishes
+    let mut cancelled = Box::pin(lazy.get());
+    assert!(cancelled.as_mut().now_or_never().is_none());
+
+    let tasks = (0..10).map(|_| {
+        let lazy = lazy.clone();
+        tokio::spawn(async move { *lazy.get().await })
+    }).collect::<Vec<_>>();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(cancelled);
+
+    let waiters = futures::future::join_all(tasks);
+    let (init, waiters) = tokio::time::timeout(Duration::from_secs(5), async { futures::join!(init, waiters) })
+        .await
+        .expect("waiters hanged");
+
+    assert_eq!(*init, 7);
+    assert!(waiters.into_iter().all(|value| value.unwrap() == 7));
+}
+
+#[cfg(feature = "alloc")]
+#[tokio::test]
+async fn cancelled () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // the initializing `get` times out, handing the future back to the cell, and the waiter resumes it
+    let started = AtomicUsize::new(0);
+    let lazy = laizy::async_lazy(async {
+        started.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        1u8
+    });
+
+    let waiter = async {
+        tokio::task::yield_now().await;
+        *lazy.get().await
+    };
+
+    let init = tokio::time::timeout(Duration::from_millis(50), lazy.get());
+    let (init, waiter) = tokio::time::timeout(Duration::from_secs(5), async { futures::join!(init, waiter) })
+        .await
+        .expect("waiter hanged");
+
+    assert!(init.is_err());
+    assert_eq!(waiter, 1);
+    assert_eq!(started.load(Ordering::Relaxed), 1);
+
+    // with nobody waiting, the next `get` resumes it
+    let lazy = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        2u8
+    });
+
+    assert!(tokio::time::timeout(Duration::from_millis(10), lazy.get()).await.is_err());
+    assert!(lazy.is_initializing() && !lazy.is_cancelled());
+    assert_eq!(*lazy.get().await, 2);
+
+    // so do `poll_get` (moving it back into the cell) and `into_inner`
+    let sleepy = |value| Box::pin(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        value
+    });
+
+    let lazy = AsyncLazy::new(sleepy(3u8));
+    assert!(tokio::time::timeout(Duration::from_millis(10), lazy.get()).await.is_err());
+    assert_eq!(futures::future::poll_fn(|cx| lazy.poll_get(cx).map(|value| *value)).await, 3);
+
+    let lazy = AsyncLazy::new(sleepy(4u8));
+    assert!(tokio::time::timeout(Duration::from_millis(10), lazy.get()).await.is_err());
+    assert_eq!(lazy.into_inner().await, 4);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn from_fn () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use laizy::FromFn;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    type Init = FromFn<fn() -> futures::future::BoxFuture<'static, Vec<u8>>>;
+    static VALUE: AsyncLazy<Vec<u8>, Init> = AsyncLazy::from_fn(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            vec![1, 2, 3]
+        })
+    });
+
+    assert!(VALUE.is_uninit());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+    let tasks = [tokio::spawn(async { VALUE.get().await.len() }), tokio::spawn(async { VALUE.get().await.len() })];
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), 3);
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    // the closure may capture, as long as the cell isn't `static`
+    let prefix = String::from("a");
+    let lazy = AsyncLazy::from_fn(move || async move { prefix.len() });
+    assert_eq!(lazy.into_inner().await, 1);
+}
+
+#[test]
+fn get_future () {
+    use std::{future::Future, pin::pin, task::{Context, Poll}};
+    use futures::future::FusedFuture;
+
+    // the future can be named
+    struct Pending<'a, F: std::future::IntoFuture<Output = u8>> {
+        get: laizy::Get<'a, u8, F>
+    }
+
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(async { rx.await.unwrap() });
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+
+    {
+        let mut init = pin!(Pending { get: lazy.get() }.get);
+        let mut waiter = pin!(lazy.get());
+        assert!(init.as_mut().poll(&mut cx).is_pending());
+        assert!(waiter.as_mut().poll(&mut cx).is_pending());
+        assert!(lazy.is_initializing() && !init.is_terminated() && !waiter.is_terminated());
+
+        tx.send(4).unwrap();
+        assert!(waiter.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(init.as_mut().poll(&mut cx), Poll::Ready(&4));
+        assert_eq!(waiter.as_mut().poll(&mut cx), Poll::Ready(&4));
+        assert!(init.is_terminated() && waiter.is_terminated());
+    }
+
+    let mut lazy = lazy;
+    {
+        let mut get = pin!(lazy.get_mut());
+        match get.as_mut().poll(&mut cx) {
+            Poll::Ready(value) => *value += 1,
+            Poll::Pending => panic!("initialized value pending")
+        }
+        assert!(get.is_terminated());
+    }
+    assert_eq!(lazy.try_get(), Some(&5));
+}
+
+#[test]
+fn poll_get () {
+    use std::{future::Future, pin::pin, sync::{Arc, atomic::{AtomicUsize, Ordering}}, task::{Context, Poll}};
+    use futures::task::{ArcWake, waker};
+
+    struct Counter(AtomicUsize);
+    impl ArcWake for Counter {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let waker = waker(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let wakes = || counter.0.load(Ordering::SeqCst);
+
+    // driven by `poll_get` alone, and woken by the initialization future itself
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }));
+    assert!(lazy.poll_get(&mut cx).is_pending());
+    assert!(lazy.is_initializing());
+    assert_eq!(wakes(), 0);
+
+    tx.send(1).unwrap();
+    assert_eq!(wakes(), 1);
+    assert_eq!(lazy.poll_get(&mut cx), Poll::Ready(&1));
+    assert_eq!(lazy.poll_get(&mut cx), Poll::Ready(&1));
+
+    // waiting on a `get` future, and woken once it finishes
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }));
+    let noop = &mut Context::from_waker(futures::task::noop_waker_ref());
+    {
+        let mut get = pin!(lazy.get());
+        assert!(get.as_mut().poll(noop).is_pending());
+        assert!(lazy.poll_get(&mut cx).is_pending());
+
+        let before = wakes();
+        tx.send(2).unwrap();
+        assert_eq!(get.as_mut().poll(noop), Poll::Ready(&2));
+        assert_eq!(wakes(), before + 1);
+    }
+    assert_eq!(lazy.poll_get(&mut cx), Poll::Ready(&2));
+
+    // started by `poll_get`, and resumed by a `get` future
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }));
+    assert!(lazy.poll_get(&mut cx).is_pending());
+    {
+        let mut get = pin!(lazy.get());
+        assert!(get.as_mut().poll(noop).is_pending());
+
+        let before = wakes();
+        tx.send(3).unwrap();
+        assert_eq!(get.as_mut().poll(noop), Poll::Ready(&3));
+        assert_eq!(wakes(), before + 1);
+    }
+    assert_eq!(lazy.poll_get(&mut cx), Poll::Ready(&3));
+
+    // left partially polled in the cell
+    let (_tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }));
+    assert!(lazy.poll_get(&mut cx).is_pending());
+    drop(lazy);
+
+    // the waker left in a parked cell is dropped with it, even if it's consumed
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }));
+    assert!(lazy.poll_get(&mut Context::from_waker(&futures::task::waker(counter.clone()))).is_pending());
+    assert!(Arc::strong_count(&counter) > 1);
+    tx.send(4).unwrap();
+    assert_eq!(futures::executor::block_on(lazy.into_inner()), 4);
+    assert_eq!(Arc::strong_count(&counter), 1);
+}
+
+#[cfg(feature = "std")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_blocking () {
+    // cold: the blocked thread drives the future itself
+    let lazy = AsyncLazy::new(async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        vec![1u8]
+    });
+    let value = tokio::task::block_in_place(|| lazy.get_blocking() as *const Vec<u8>);
+    assert_eq!(value, lazy.try_get().unwrap() as *const _);
+
+    // warm: another task is initializing it
+    let lazy = std::sync::Arc::new(AsyncLazy::new(Box::pin(async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        vec![2u8]
+    }) as futures::future::BoxFuture<'static, Vec<u8>>));
+
+    let init = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.get().await as *const Vec<u8> as usize }
+    });
+    while !lazy.is_initializing() {
+        tokio::task::yield_now().await;
+    }
+
+    let blocking = tokio::task::spawn_blocking({
+        let lazy = lazy.clone();
+        move || lazy.get_blocking() as *const Vec<u8> as usize
+    });
+    assert_eq!(init.await.unwrap(), blocking.await.unwrap());
+    assert_eq!(lazy.get_blocking(), &[2]);
+}
+
+#[tokio::test]
+async fn get_timeout () {
+    use laizy::Elapsed;
+    let sleep = |millis| tokio::time::sleep(Duration::from_millis(millis));
+
+    // win
+    let lazy = laizy::async_lazy(async { 1u8 });
+    assert_eq!(lazy.get_timeout(sleep(1000)).await, Ok(&1));
+
+    // lose while waiting: the initialization keeps going
+    let lazy = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        2u8
+    });
+    let init = lazy.get();
+    let waiter = async {
+        tokio::task::yield_now().await;
+        lazy.get_timeout(sleep(10)).await
+    };
+    let (init, waiter) = futures::join!(init, waiter);
+    assert_eq!((*init, waiter), (2, Err(Elapsed)));
+
+    // lose while initializing: the future is handed back to the cell as soon as the deadline finishes, and the next
+    // `get` resumes it (without `alloc`, the cell is cancelled instead)
+    let lazy = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        3u8
+    });
+    let mut timeout = Box::pin(lazy.get_timeout(sleep(10)));
+    assert_eq!(timeout.as_mut().await, Err(Elapsed));
+    #[cfg(feature = "alloc")]
+    {
+        assert!(lazy.is_initializing() && !lazy.is_cancelled());
+        assert_eq!(*lazy.get().await, 3);
+    }
+    #[cfg(not(feature = "alloc"))]
+    assert!(lazy.is_cancelled() && !lazy.is_initializing());
+    drop(timeout);
+
+    // a waiter takes over once the deadline of the initializing task finishes
+    #[cfg(feature = "alloc")]
+    {
+        let lazy = laizy::async_lazy(async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            4u8
+        });
+        let init = lazy.get_timeout(sleep(10));
+        let waiter = async {
+            tokio::task::yield_now().await;
+            lazy.get_timeout(sleep(1000)).await
+        };
+        assert_eq!(futures::join!(init, waiter), (Err(Elapsed), Ok(&4)));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn set () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    struct Captured(Arc<AtomicUsize>);
+    impl Drop for Captured {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // the future is dropped without being polled
+    let drops = Arc::new(AtomicUsize::new(0));
+    let captured = Captured(drops.clone());
+    let lazy = laizy::async_lazy(async move {
+        let _captured = captured;
+        panic!("initialization future polled")
+    });
+
+    assert_eq!(lazy.set(vec![1u8]), Ok(()));
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+    assert_eq!(lazy.get().await, &[1]);
+    assert_eq!(lazy.set(vec![2]), Err(vec![2]));
+    drop(lazy);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+    // racing a `get`, exactly one of them initializes it
+    for _ in 0..100 {
+        let lazy = Arc::new(AsyncLazy::new(Box::pin(async { 1u8 }) as futures::future::BoxFuture<'static, u8>));
+        let get = tokio::spawn({
+            let lazy = lazy.clone();
+            async move { *lazy.get().await }
+        });
+
+        let set = lazy.set(2);
+        let got = get.await.unwrap();
+        match set {
+            Ok(()) => assert_eq!(got, 2),
+            Err(value) => assert_eq!((value, got), (2, 1))
+        }
+    }
+}
+
+#[tokio::test]
+async fn take () {
+    let mut lazy = laizy::async_lazy(async { vec![1u8] });
+    assert_eq!(lazy.take(), None);
+
+    lazy.get().await;
+    assert_eq!(lazy.take(), Some(vec![1]));
+    assert_eq!(lazy.take(), None);
+    assert!(lazy.try_get().is_none() && !lazy.is_initialized());
+
+    let get = AssertUnwindSafe(lazy.get()).catch_unwind().await;
+    assert!(get.is_err());
+    drop(lazy);
+}
+
+#[tokio::test]
+async fn replace () {
+    use std::{rc::Rc, cell::Cell};
+
+    struct Counted(u8, Rc<Cell<usize>>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1)
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+
+    // uninit: the future is dropped unpolled
+    let captured = Counted(0, drops.clone());
+    let mut lazy = laizy::async_lazy(async move {
+        let _captured = captured;
+        panic!("initialization future polled")
+    });
+    assert!(lazy.replace(Counted(1, drops.clone())).is_none());
+    assert_eq!(drops.get(), 1);
+    assert!(lazy.is_initialized());
+    assert_eq!(lazy.get().await.0, 1);
+
+    // initialized, by `get`
+    let prev = lazy.replace(Counted(2, drops.clone())).unwrap();
+    assert_eq!((prev.0, drops.get()), (1, 1));
+    drop(prev);
+    assert_eq!(drops.get(), 2);
+
+    // initialized, by `init`
+    let mut init = AsyncLazy::<_, std::future::Ready<Counted>>::init(Counted(3, drops.clone()));
+    assert_eq!(init.replace(Counted(4, drops.clone())).map(|prev| prev.0), Some(3));
+    assert_eq!((init.get().await.0, drops.get()), (4, 3));
+
+    // taken
+    assert_eq!(init.take().map(|prev| prev.0), Some(4));
+    assert!(init.replace(Counted(5, drops.clone())).is_none());
+    assert_eq!((init.try_get().unwrap().0, drops.get()), (5, 4));
+
+    drop((lazy, init));
+    assert_eq!(drops.get(), 6);
+}
+
+#[tokio::test]
+async fn reset () {
+    use std::{rc::Rc, cell::Cell};
+
+    struct Counted(u8, Rc<Cell<usize>>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1)
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let polls = Rc::new(Cell::new(0));
+    let connect = |id: u8| {
+        let (drops, polls) = (drops.clone(), polls.clone());
+        Box::pin(async move {
+            polls.set(polls.get() + 1);
+            Counted(id, drops)
+        }) as futures::future::LocalBoxFuture<'static, Counted>
+    };
+
+    let mut lazy = AsyncLazy::new(connect(1));
+    assert_eq!(lazy.get().await.0, 1);
+
+    lazy.reset(connect(2));
+    assert_eq!(drops.get(), 1);
+    assert!(lazy.is_uninit() && lazy.try_get().is_none());
+    assert_eq!((lazy.get().await.0, polls.get()), (2, 2));
+
+    // the unpolled future is dropped too
+    lazy.reset(connect(3));
+    lazy.reset(connect(4));
+    assert_eq!((lazy.get().await.0, polls.get(), drops.get()), (4, 3, 2));
+
+    // recovers from cancellation (with `alloc`, the timed out `get` hands the future back, until it's aborted)
+    let mut cancelled = AsyncLazy::new(Box::pin(futures::future::pending()) as futures::future::LocalBoxFuture<'static, Counted>);
+    assert!(tokio::time::timeout(Duration::from_millis(10), cancelled.get()).await.is_err());
+    #[cfg(feature = "alloc")]
+    assert!(cancelled.abort());
+    assert!(cancelled.is_cancelled());
+    cancelled.reset(connect(5));
+    assert_eq!(cancelled.get().await.0, 5);
+
+    // drops futures handed back by timed out `get`s
+    #[cfg(feature = "alloc")]
+    {
+        let held = Counted(6, drops.clone());
+        let mut suspended = AsyncLazy::new(Box::pin(async move {
+            futures::future::pending::<()>().await;
+            held
+        }) as futures::future::LocalBoxFuture<'static, Counted>);
+        assert!(tokio::time::timeout(Duration::from_millis(10), suspended.get()).await.is_err());
+        assert!(suspended.is_initializing());
+
+        let before = drops.get();
+        suspended.reset(connect(7));
+        assert_eq!(drops.get(), before + 1);
+        assert_eq!(suspended.get().await.0, 7);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn wait () {
+    let lazy = std::sync::Arc::new(AsyncLazy::<Vec<u8>, _>::new(Box::pin(async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        vec![1u8]
+    }) as futures::future::BoxFuture<'static, Vec<u8>>));
+
+    // nobody initializes it
+    assert!(tokio::time::timeout(Duration::from_millis(20), lazy.wait()).await.is_err());
+    assert!(lazy.is_uninit());
+
+    // subscribed before anyone calls `get`
+    let early = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.wait().await.clone() }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(lazy.is_uninit());
+
+    let init = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.get().await.clone() }
+    });
+    while !lazy.is_initializing() {
+        tokio::task::yield_now().await;
+    }
+
+    let late = (0..3).map(|_| {
+        let lazy = lazy.clone();
+        tokio::spawn(async move { lazy.wait().await.clone() })
+    }).collect::<Vec<_>>();
+
+    assert_eq!(init.await.unwrap(), [1]);
+    assert_eq!(early.await.unwrap(), [1]);
+    for late in late {
+        assert_eq!(late.await.unwrap(), [1]);
+    }
+    assert_eq!(lazy.wait().await, &[1]);
+
+    // woken by `set` too
+    let lazy = laizy::async_lazy(futures::future::pending::<u8>());
+    let (value, ()) = futures::join!(lazy.wait(), async {
+        tokio::task::yield_now().await;
+        lazy.set(2).unwrap();
+    });
+    assert_eq!(*value, 2);
+}
+
+#[test]
+fn state () {
+    use std::{future::Future, pin::pin, task::Context};
+    use laizy::State;
+
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+    let mut lazy = AsyncLazy::new(async { rx.await.unwrap() });
+    assert_eq!(lazy.state(), State::Uninit);
+
+    {
+        let mut get = pin!(lazy.get());
+        assert!(get.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(lazy.state(), State::Initializing);
+
+        tx.send(1).unwrap();
+        assert!(get.as_mut().poll(&mut cx).is_ready());
+    }
+    assert_eq!(lazy.state(), State::Initialized);
+
+    lazy.take();
+    assert_eq!(lazy.state(), State::Taken);
+
+    // cancelled (with `alloc`, the dropped `get` hands the future back to the cell, until it's aborted)
+    let lazy = laizy::async_lazy(futures::future::pending::<u8>());
+    assert!(pin!(lazy.get()).poll(&mut cx).is_pending());
+    #[cfg(feature = "alloc")]
+    {
+        assert_eq!(lazy.state(), State::Initializing);
+        assert!(lazy.abort());
+    }
+    assert_eq!(lazy.state(), State::Cancelled);
+
+    // poisoned
+    let lazy = laizy::async_lazy(async { panic!("initialization failed") });
+    let poll = std::panic::catch_unwind(AssertUnwindSafe(|| pin!(lazy.get()).poll(&mut cx).map(|value: &u8| *value)));
+    assert!(poll.is_err());
+    assert_eq!(lazy.state(), State::Poisoned);
+
+    // left in the cell by `poll_get`
+    let lazy = AsyncLazy::new(Box::pin(futures::future::pending::<u8>()));
+    assert!(lazy.poll_get(&mut cx).is_pending());
+    assert_eq!(lazy.state(), State::Initializing);
+}
+
+#[tokio::test]
+async fn default () {
+    use laizy::DefaultInit;
+
+    let lazy = AsyncLazy::<Vec<u8>, DefaultInit<Vec<u8>>>::default();
+    assert!(lazy.is_initialized());
+    assert!(lazy.get().await.is_empty());
+
+    // used as an initializer, it's only called when needed
+    let lazy = AsyncLazy::new(DefaultInit::<String>::new());
+    assert!(lazy.is_uninit());
+    assert_eq!(lazy.get().await, "");
+
+    // any initializer that implements `Default`
+    #[derive(Default)]
+    struct Zero;
+    impl std::future::Future for Zero {
+        type Output = u8;
+        fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<u8> {
+            std::task::Poll::Ready(0)
+        }
+    }
+
+    let lazy = AsyncLazy::<u8, Zero>::default();
+    assert!(lazy.is_uninit());
+    assert_eq!(lazy.get().await, &0);
+}
+
+#[test]
+fn debug () {
+    use std::{future::Future, pin::pin, task::Context};
+
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    let (tx, rx) = futures::channel::oneshot::channel::<Vec<u8>>();
+    let lazy = AsyncLazy::new(async { rx.await.unwrap() });
+    assert_eq!(format!("{lazy:?}"), "AsyncLazy(Uninit)");
+
+    let mut get = pin!(lazy.get());
+    assert!(get.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(format!("{lazy:?}"), "AsyncLazy(Initializing)");
+
+    tx.send(vec![1, 2]).unwrap();
+    assert!(get.as_mut().poll(&mut cx).is_ready());
+    assert_eq!(format!("{lazy:?}"), "AsyncLazy([1, 2])");
+}
+
+#[tokio::test]
+async fn map () {
+    use std::cell::Cell;
+
+    let polls = Cell::new(0);
+    let root = laizy::async_lazy(async {
+        polls.set(polls.get() + 1);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        1u8
+    });
+
+    let mapped = root.map_ref(|value| *value + 1).map(|value| value * 10);
+    let (a, b, c) = futures::join!(mapped.get(), mapped.get(), root.get());
+    assert_eq!((*a, *b, *c), (20, 20, 1));
+    assert_eq!(polls.get(), 1);
+
+    // already initialized
+    let doubled = root.map_ref(|value| *value * 2);
+    assert_eq!(*doubled.get().await, 2);
+    assert_eq!(polls.get(), 1);
+
+    let owned = laizy::async_lazy(async { vec![1u8, 2] }).map(|value| value.len());
+    assert_eq!(owned.into_inner().await, 2);
+}
+
+#[tokio::test]
+async fn zip () {
+    use std::time::Instant;
+
+    let slow = |value: u8| laizy::async_lazy(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        value
+    });
+
+    let (a, b) = (slow(1), slow(2));
+    let start = Instant::now();
+    assert_eq!(a.zip(&b).await, (&1, &2));
+    assert!(start.elapsed() < Duration::from_millis(180), "initialized sequentially");
+    assert_eq!(a.zip(&b).await, (&1, &2));
+
+    let joined = slow(3).join(slow(4));
+    assert!(joined.is_uninit());
+    let start = Instant::now();
+    assert_eq!(joined.get().await, &(3, 4));
+    assert!(start.elapsed() < Duration::from_millis(180), "initialized sequentially");
+}
+
+#[cfg(feature = "std")]
+#[tokio::test]
+async fn or_else () {
+    use std::cell::Cell;
+
+    let polls = Cell::new(0);
+    let primary = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        panic!("initialization failed")
+    });
+    let fallback = laizy::async_lazy(async {
+        polls.set(polls.get() + 1);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        2u8
+    });
+
+    let callers = futures::future::join_all((0..3).map(|_| primary.or_else(&fallback)));
+    let values = tokio::time::timeout(Duration::from_secs(5), callers).await.expect("callers hanged");
+    assert_eq!(values, [&2; 3]);
+    assert!(primary.is_poisoned());
+    assert_eq!(polls.get(), 1);
+
+    // already failed
+    assert_eq!(primary.or_else(&fallback).await, &2);
+
+    // doesn't fail
+    let ok = laizy::async_lazy(async { 1u8 });
+    assert_eq!(ok.or_else(&fallback).await, &1);
+}
+
+#[tokio::test]
+async fn get_owned () {
+    let lazy = laizy::async_lazy(async { vec![1, 2, 3] });
+    let value = lazy.get_owned().await;
+    let task = tokio::spawn(async move { value.len() });
+    assert_eq!(task.await.unwrap(), 3);
+
+    type Init = laizy::FromFn<fn() -> futures::future::BoxFuture<'static, String>>;
+    static INIT: AsyncLazy<String, Init> = AsyncLazy::from_fn(|| Box::pin(async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        String::from("static")
+    }));
+
+    let tasks = [tokio::spawn(INIT.get_static()), tokio::spawn(INIT.get_static())];
+    for task in tasks {
+        let value: &'static String = task.await.unwrap();
+        assert_eq!(value, "static");
+    }
+}
+
+#[tokio::test]
+async fn take_initializer () {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Init<'a>(&'a AtomicBool);
+
+    impl std::future::Future for Init<'_> {
+        type Output = u8;
+
+        fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<u8> {
+            panic!("initializer polled")
+        }
+    }
+
+    impl Drop for Init<'_> {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed)
+        }
+    }
+
+    let dropped = AtomicBool::new(false);
+    let mut lazy = AsyncLazy::new(Init(&dropped));
+    assert!(lazy.initializer().is_some());
+
+    let f = lazy.take_initializer().unwrap();
+    assert!(lazy.initializer().is_none());
+    assert!(lazy.take_initializer().is_none());
+    assert!(!dropped.load(Ordering::Relaxed));
+    drop(f);
+    assert!(dropped.load(Ordering::Relaxed));
+
+    // nothing left to initialize it
+    assert!(lazy.is_initializing());
+    assert_eq!(lazy.get().now_or_never(), None);
+
+    lazy.replace(1);
+    assert_eq!(lazy.get().await, &1);
+    assert!(lazy.initializer().is_none());
+}
+
+#[tokio::test]
+async fn as_ptr () {
+    let lazy = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        [1u8; 16]
+    });
+
+    let ptr = lazy.as_ptr();
+    assert_eq!(lazy.as_non_null().as_ptr().cast_const(), ptr);
+    assert!(!lazy.is_initialized());
+
+    let value = lazy.get().await;
+    assert!(std::ptr::eq(value, ptr));
+    assert_eq!(unsafe { *ptr }, [1; 16]);
+}
+
+#[tokio::test]
+async fn update () {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Init<'a>(&'a AtomicBool);
+
+    impl std::future::Future for Init<'_> {
+        type Output = Vec<u8>;
+
+        fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<Vec<u8>> {
+            std::task::Poll::Ready(vec![1])
+        }
+    }
+
+    impl Drop for Init<'_> {
+        fn drop(&mut self) {
+            assert!(!self.0.swap(true, Ordering::Relaxed))
+        }
+    }
+
+    // uninitialized
+    let dropped = AtomicBool::new(false);
+    let mut lazy = AsyncLazy::new(Init(&dropped));
+    lazy.update(|value| value.push(2)).await;
+    assert!(dropped.load(Ordering::Relaxed));
+    assert_eq!(lazy.try_get(), Some(&vec![1, 2]));
+
+    // initialized
+    lazy.update_or(vec![0], |value| value.push(3));
+    assert_eq!(lazy.try_get(), Some(&vec![1, 2, 3]));
+
+    // the future is skipped, and dropped exactly once
+    let dropped = AtomicBool::new(false);
+    let mut lazy = AsyncLazy::new(Init(&dropped));
+    lazy.update_or(vec![0], |value| value.push(1));
+    assert!(dropped.load(Ordering::Relaxed));
+    assert_eq!(lazy.try_get(), Some(&vec![0, 1]));
+    drop(lazy);
+
+    // failed
+    let mut lazy = laizy::async_lazy(async { panic!("initialization failed") });
+    assert!(AssertUnwindSafe(lazy.get()).catch_unwind().await.is_err());
+    lazy.update_or(vec![0], |value| value.push(1));
+    assert_eq!(lazy.get().await, &vec![0, 1]);
+}
+
+#[tokio::test]
+async fn get_mut () {
+    let mut lazy = laizy::async_lazy(async { vec![1u8] });
+    lazy.get_mut().await.push(2);
+    lazy.get_mut().await.push(3);
+    assert_eq!(lazy.get().await, &[1, 2, 3]);
+
+    // abandoned initializations panic instead of hanging forever
+    let mut lazy = laizy::async_lazy(futures::future::ready(1u8));
+    assert!(lazy.take_initializer().is_some());
+    assert!(AssertUnwindSafe(lazy.get_mut()).catch_unwind().await.is_err());
+    assert!(lazy.is_initializing());
+
+    lazy.reset(futures::future::ready(2));
+    assert_eq!(lazy.get_mut().await, &mut 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_in_place () {
+    async fn large () -> usize {
+        let buf = [1u8; 4096];
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        buf.iter().map(|&x| x as usize).sum()
+    }
+
+    let lazy: &'static _ = Box::leak(Box::new(laizy::async_lazy(large())));
+    let moved = std::mem::size_of_val(&lazy.get());
+    let in_place = std::mem::size_of_val(&lazy.get_in_place());
+    // unless `get` boxes it (with `alloc`)
+    assert!(cfg!(feature = "alloc") || moved > 4096, "{moved}");
+    assert!(in_place < 256, "{in_place}");
+
+    let tasks = [tokio::spawn(lazy.get_in_place()), tokio::spawn(lazy.get_in_place()), tokio::spawn(async { lazy.get().await })];
+    for task in tasks {
+        assert_eq!(*task.await.unwrap(), 4096);
+    }
+    assert_eq!(*lazy.get_in_place().await, 4096);
+
+    // cancellation drops the future in place
+    struct Tracked(&'static std::sync::atomic::AtomicBool);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            assert!(!self.0.swap(true, std::sync::atomic::Ordering::Relaxed))
+        }
+    }
+
+    static DROPPED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+    let lazy: &'static _ = Box::leak(Box::new(laizy::async_lazy(async {
+        let _tracked = Tracked(&DROPPED);
+        futures::future::pending::<u8>().await
+    })));
+
+    assert!(lazy.get_in_place().now_or_never().is_none());
+    assert!(DROPPED.load(std::sync::atomic::Ordering::Relaxed));
+    assert!(lazy.is_cancelled());
+
+    // panics poison the cell right away
+    let lazy: &'static _ = Box::leak(Box::new(laizy::async_lazy(async { panic!("initialization failed") })));
+    let mut get = Box::pin(lazy.get_in_place());
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| get.as_mut().now_or_never())).is_err());
+    assert!(lazy.is_poisoned());
+    drop::<std::pin::Pin<Box<laizy::GetInPlace<(), _>>>>(get);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn waker_contention () {
+    use std::sync::Arc;
+
+    let lazy = Arc::new(laizy::async_lazy(async {
+        for _ in 0..32 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        7u64
+    }));
+
+    // waiters that keep registering and deregistering (timing out) while the value initializes
+    let churn = (0..16).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move {
+            while tokio::time::timeout(Duration::from_micros(50), lazy.wait()).await.is_err() {}
+            *lazy.wait().await
+        }
+    })).collect::<Vec<_>>();
+
+    let getters = (0..64).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    })).collect::<Vec<_>>();
+
+    for task in churn.into_iter().chain(getters) {
+        let value = tokio::time::timeout(Duration::from_secs(5), task).await.expect("waiter never woken");
+        assert_eq!(value.unwrap(), 7);
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn spawn_init () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use laizy::FromFn;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    type Init = FromFn<fn() -> futures::future::BoxFuture<'static, u64>>;
+    static DB: AsyncLazy<u64, Init> = AsyncLazy::from_fn(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            42
+        })
+    });
+
+    let init = DB.spawn_init();
+    let tasks = (0..10).map(|_| tokio::spawn(async { *DB.get().await })).collect::<Vec<_>>();
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), 42);
+    }
+
+    init.await.unwrap();
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[cfg(feature = "spawn")]
+#[test]
+fn spawn_init_with () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use futures::executor::ThreadPool;
+    use laizy::FromFn;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    type Init = FromFn<fn() -> futures::future::BoxFuture<'static, u64>>;
+    static VALUE: AsyncLazy<u64, Init> = AsyncLazy::from_fn(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        Box::pin(async {
+            std::thread::sleep(Duration::from_millis(20));
+            42
+        })
+    });
+
+    let pool = ThreadPool::builder().pool_size(4).create().unwrap();
+    VALUE.spawn_init_with(&pool).unwrap();
+    VALUE.spawn_init_with(&pool).unwrap();
+
+    let tasks = (0..10).map(|_| {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        pool.spawn_ok(async move { tx.send(*VALUE.get().await).unwrap() });
+        rx
+    }).collect::<Vec<_>>();
+
+    for value in futures::executor::block_on(futures::future::join_all(tasks)) {
+        assert_eq!(value.unwrap(), 42);
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+}
+
+#[cfg(feature = "spawn")]
+#[test]
+fn spawn_init_local () {
+    use std::{rc::Rc, cell::Cell};
+    use futures::executor::LocalPool;
+
+    thread_local! {
+        static CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // `Rc` makes both the future and the value `!Send`
+    let lazy: &'static _ = Box::leak(Box::new(laizy::async_lazy(async {
+        CALLS.with(|calls| calls.set(calls.get() + 1));
+        let value = Rc::new(42u64);
+        futures::future::ready(()).await;
+        value
+    })));
+
+    let mut pool = LocalPool::new();
+    lazy.spawn_init_local(&pool.spawner()).unwrap();
+    lazy.spawn_init_local(&pool.spawner()).unwrap();
+    assert!(lazy.is_uninit());
+
+    pool.run_until_stalled();
+    assert_eq!(lazy.try_get().map(|value| **value), Some(42));
+    assert_eq!(CALLS.with(Cell::get), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn async_once () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use laizy::AsyncOnce;
+
+    // racing initializations: only one future runs, the rest are dropped unpolled
+    let once = Arc::new(AsyncOnce::new());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let tasks = (0..16).map(|i| tokio::spawn({
+        let (once, runs) = (once.clone(), runs.clone());
+        async move {
+            *once.get_or_init(async move {
+                runs.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                i
+            }).await
+        }
+    })).collect::<Vec<_>>();
+
+    let mut values = Vec::new();
+    for task in tasks {
+        values.push(task.await.unwrap());
+    }
+    assert_eq!(runs.load(Ordering::Relaxed), 1);
+    assert!(values.iter().all(|&value| value == values[0]));
+    assert_eq!(once.get(), Some(&values[0]));
+
+    // cancelling the winner lets a waiter run its own future
+    let once = AsyncOnce::new();
+    let mut winner = Box::pin(once.get_or_init(futures::future::pending::<u8>()));
+    assert!(winner.as_mut().now_or_never().is_none());
+    let mut waiter = Box::pin(once.get_or_init(async { 2 }));
+    assert!(waiter.as_mut().now_or_never().is_none());
+    drop(winner);
+    assert_eq!(waiter.await, &2);
+
+    // `set` loses against a running initialization, and wins before it starts
+    let once = AsyncOnce::new();
+    let mut init = Box::pin(once.get_or_init(async { tokio::task::yield_now().await; 1u8 }));
+    assert!(init.as_mut().now_or_never().is_none());
+    assert_eq!(once.set(2), Err(2));
+    assert_eq!(init.await, &1);
+    assert_eq!(once.set(3), Err(3));
+
+    let once = AsyncOnce::new();
+    assert_eq!(once.set(4u8), Ok(()));
+    assert_eq!(once.get_or_init(async { panic!("initializer polled") }).await, &4);
+    assert_eq!(once.into_inner(), Some(4));
+}
+
+#[tokio::test]
+async fn async_once_try () {
+    use laizy::AsyncOnce;
+
+    // failures leave the cell uninitialized, and waiters retry with their own futures
+    let once = AsyncOnce::<u8>::new();
+    let mut failing = Box::pin(once.get_or_try_init(async { tokio::task::yield_now().await; Err("first attempt") }));
+    assert!(failing.as_mut().now_or_never().is_none());
+    let waiter = once.get_or_try_init(async { Ok::<_, &str>(2) });
+
+    let (failed, waited) = futures::join!(failing, waiter);
+    assert_eq!(failed, Err("first attempt"));
+    assert_eq!(waited, Ok(&2));
+    assert_eq!(once.get_or_try_init(async { Err::<u8, _>("ignored") }).await, Ok(&2));
+
+    // panics poison it
+    let once = AsyncOnce::<u8>::new();
+    assert!(AssertUnwindSafe(once.get_or_init(async { panic!("initialization failed") })).catch_unwind().await.is_err());
+    assert!(once.is_poisoned());
+    assert!(AssertUnwindSafe(once.get_or_init(async { 1 })).catch_unwind().await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn async_try_lazy () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use laizy::AsyncTryLazy;
+
+    // success
+    let lazy = AsyncTryLazy::new(async { Ok::<_, String>(1u8) });
+    assert_eq!(lazy.get().await, Ok(&1));
+    assert!(lazy.is_ok());
+
+    // failures are cached, and every waiter resolves to them
+    let runs = Arc::new(AtomicUsize::new(0));
+    let connect = |ok: bool| {
+        let runs = runs.clone();
+        futures::future::FutureExt::boxed(async move {
+            runs.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            match ok {
+                true => Ok(2u8),
+                false => Err(String::from("connection refused"))
+            }
+        })
+    };
+
+    let mut lazy = Arc::new(AsyncTryLazy::new(connect(false)));
+    let tasks = (0..8).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.get_cloned_err().await.copied() }
+    })).collect::<Vec<_>>();
+
+    for task in tasks {
+        let result = tokio::time::timeout(Duration::from_secs(5), task).await.expect("waiter hanged");
+        assert_eq!(result.unwrap(), Err(String::from("connection refused")));
+    }
+    assert_eq!(lazy.get().await, Err(&String::from("connection refused")));
+    assert!(lazy.is_err());
+    assert_eq!(runs.load(Ordering::Relaxed), 1);
+
+    // reset, then succeed
+    Arc::get_mut(&mut lazy).unwrap().reset(connect(true));
+    assert_eq!(lazy.try_get(), None);
+    assert_eq!(lazy.get().await, Ok(&2));
+    assert_eq!(runs.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retrying () {
+    use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+    use laizy::{AsyncTryLazy, RetryPolicy};
+
+    const POLICY: RetryPolicy = RetryPolicy { max_attempts: 3, backoff: |failed| Duration::from_millis(5 * failed as u64) };
+
+    // flaky, succeeds on the third attempt
+    let attempts = Arc::new(AtomicU32::new(0));
+    let lazy = Arc::new(AsyncTryLazy::retrying(POLICY, {
+        let attempts = attempts.clone();
+        move || {
+            let attempt = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+            async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                match attempt {
+                    3 => Ok(attempt),
+                    _ => Err(attempt)
+                }
+            }
+        }
+    }, tokio::time::sleep));
+
+    let tasks = (0..8).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.get().await.copied().map_err(|&e| e) }
+    })).collect::<Vec<_>>();
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), Ok(3));
+    }
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+    // exhausts its budget, caching the last failure
+    let attempts = AtomicU32::new(0);
+    let lazy = AsyncTryLazy::retrying(POLICY, || {
+        let attempt = attempts.fetch_add(1, Ordering::Relaxed) + 1;
+        async move { Err::<(), _>(attempt) }
+    }, tokio::time::sleep);
+
+    let (first, second) = futures::join!(lazy.get(), lazy.get());
+    assert_eq!((first, second), (Err(&3), Err(&3)));
+    assert_eq!(lazy.get().await, Err(&3));
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn abort () {
+    use std::sync::Arc;
+    use futures::future::BoxFuture;
+
+    let hung = || -> BoxFuture<'static, u8> { Box::pin(async { tokio::time::sleep(Duration::from_secs(3600)).await; 1 }) };
+    let mut lazy = Arc::new(AsyncLazy::new(hung()));
+    assert!(!lazy.abort());
+
+    let driver = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    });
+    while !lazy.is_initializing() {
+        tokio::task::yield_now().await
+    }
+
+    let waiters = (0..4).map(|i| tokio::spawn({
+        let lazy = lazy.clone();
+        async move {
+            match i % 2 {
+                0 => *lazy.get().await,
+                _ => *lazy.wait().await
+            }
+        }
+    })).collect::<Vec<_>>();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert!(lazy.abort());
+    assert!(lazy.is_cancelled());
+    assert!(!lazy.abort());
+
+    for task in waiters.into_iter().chain([driver]) {
+        let result = tokio::time::timeout(Duration::from_secs(1), task).await.expect("task hanged");
+        assert!(result.unwrap_err().is_panic());
+    }
+
+    Arc::get_mut(&mut lazy).unwrap().reset(Box::pin(async { 2 }));
+    assert_eq!(lazy.get().await, &2);
+    assert!(!lazy.abort());
+
+    // left partially polled by `poll_get`
+    let lazy = AsyncLazy::new(hung());
+    assert!(futures::future::poll_fn(|cx| std::task::Poll::Ready(lazy.poll_get(cx).is_pending())).await);
+    assert!(lazy.abort());
+    assert!(lazy.is_cancelled());
+}
+
+#[test]
+fn wake_order () {
+    use std::{sync::{Arc, Mutex}, pin::Pin, task::{Context, Poll}, future::Future};
+    use futures::task::{ArcWake, waker};
+
+    /// Pushes its task's index into the run queue when woken
+    struct Task(usize, Arc<Mutex<Vec<usize>>>);
+
+    impl ArcWake for Task {
+        fn wake_by_ref(this: &Arc<Self>) {
+            this.1.lock().unwrap().push(this.0)
+        }
+    }
+
+    let (tx, rx) = futures::channel::oneshot::channel::<()>();
+    let lazy = laizy::async_lazy(async move { rx.await.unwrap(); 1u8 });
+    let queue = Arc::new(Mutex::new(Vec::new()));
+
+    let mut init = Box::pin(lazy.get());
+    let init_waker = waker(Arc::new(Task(usize::MAX, queue.clone())));
+    assert!(init.as_mut().poll(&mut Context::from_waker(&init_waker)).is_pending());
+
+    // register ten waiters in order, polling the early ones again in between (which must keep their place)
+    let mut waiters = (0..10).map(|_| Box::pin(lazy.get())).collect::<Vec<Pin<Box<_>>>>();
+    let wakers = (0..10).map(|i| waker(Arc::new(Task(i, queue.clone())))).collect::<Vec<_>>();
+    for i in 0..10 {
+        for j in (0..=i).rev() {
+            assert!(waiters[j].as_mut().poll(&mut Context::from_waker(&wakers[j])).is_pending());
+        }
+    }
+
+    tx.send(()).unwrap();
+    assert_eq!(std::mem::take(&mut *queue.lock().unwrap()), [usize::MAX]);
+    assert_eq!(init.as_mut().poll(&mut Context::from_waker(&init_waker)), Poll::Ready(&1));
+
+    // run the woken tasks in the order they were woken, recording the order they complete in
+    let mut completed = Vec::new();
+    loop {
+        let next = std::mem::take(&mut *queue.lock().unwrap());
+        if next.is_empty() {
+            break
+        }
+
+        for i in next {
+            if let Poll::Ready(&value) = waiters[i].as_mut().poll(&mut Context::from_waker(&wakers[i])) {
+                assert_eq!(value, 1);
+                completed.push(i);
+            }
+        }
+    }
+    assert_eq!(completed, (0..10).collect::<Vec<_>>());
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "called on an uninitialized cell"]
+fn get_unchecked_uninit () {
+    let lazy = laizy::async_lazy(async { 1u8 });
+    unsafe { lazy.get_unchecked(); }
+}
+
+#[cfg(feature = "alloc")]
+#[tokio::test]
+async fn leak () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let init = || async {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        vec![1u8, 2, 3]
+    };
+
+    let lazy = laizy::async_lazy(init());
+    let value: &'static mut Vec<u8> = lazy.leak().await;
+    value.push(4);
+    assert_eq!(value, &[1, 2, 3, 4]);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    let lazy = laizy::async_lazy(init());
+    assert_eq!(lazy.get().await.len(), 3);
+    let value: &'static Vec<u8> = lazy.leak_initialized().unwrap();
+    assert_eq!(value, &[1, 2, 3]);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+
+    assert!(laizy::async_lazy(init()).leak_initialized().is_none());
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn new_with_hook () {
+    use std::sync::{Arc, Mutex};
+
+    // fires once, before anyone sees the value
+    let hooked = Arc::new(Mutex::new(Vec::new()));
+    let lazy = Arc::new(AsyncLazy::new_with_hook(async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        5u8
+    }, {
+        let hooked = hooked.clone();
+        move |value: &u8| hooked.lock().unwrap().push(*value)
+    }));
+
+    let tasks = (0..8).map(|_| tokio::spawn({
+        let (lazy, hooked) = (lazy.clone(), hooked.clone());
+        async move {
+            let value = *lazy.get().await;
+            assert_eq!(*hooked.lock().unwrap(), [value]);
+            value
+        }
+    })).collect::<Vec<_>>();
+
+    for task in tasks {
+        assert_eq!(task.await.unwrap(), 5);
+    }
+    assert_eq!(*hooked.lock().unwrap(), [5]);
+
+    // any way of initializing it runs the hook
+    let count = std::cell::Cell::new(0);
+    let mut lazy = AsyncLazy::new_with_hook(async { 1u8 }, |_: &u8| count.set(count.get() + 1));
+    lazy.get_mut().await;
+    assert_eq!(count.get(), 1);
+    assert_eq!(AsyncLazy::new_with_hook(async { 1u8 }, |_: &u8| count.set(count.get() + 1)).into_inner().await, 1);
+    assert_eq!(count.get(), 2);
+
+    // but not setting it
+    let lazy = AsyncLazy::new_with_hook(async { 1u8 }, |_: &u8| count.set(count.get() + 1));
+    lazy.set(2).unwrap();
+    assert_eq!(lazy.get().await, &2);
+    assert_eq!(count.get(), 2);
+}
+
+#[tokio::test]
+async fn clone () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let make = || async {
+        RUNS.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        vec![1u8, 2, 3]
+    };
+
+    // uninitialized: every clone runs its own initializer
+    let template = AsyncLazy::from_fn(make);
+    let workers = (0..4).map(|_| template.clone()).collect::<Vec<_>>();
+    assert!(workers.iter().all(AsyncLazy::is_uninit));
+
+    let values = futures::future::join_all(workers.iter().map(AsyncLazy::get)).await;
+    assert!(values.iter().all(|value| **value == [1, 2, 3]));
+    assert_eq!(RUNS.load(Ordering::Relaxed), 4);
+    assert!(template.is_uninit());
+
+    // initialized: the value is cloned
+    template.get().await;
+    let cloned = template.clone();
+    assert_eq!(cloned.try_get(), Some(&vec![1, 2, 3]));
+    assert_eq!(RUNS.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn clone_threaded () {
+    use std::sync::{Barrier, mpsc};
+
+    // clones of one template don't get in each other's way
+    let template = AsyncLazy::<Vec<u8>, _>::from_fn(|| async { vec![1u8, 2, 3] });
+    let barrier = Barrier::new(8);
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                barrier.wait();
+                for _ in 0..2000 {
+                    assert!(template.clone().is_uninit());
+                }
+            });
+        }
+    });
+
+    // nor count as initializing it: the initialization can start while it's cloned, once the clone is done with the future
+    struct Slow(mpsc::Sender<()>);
+    impl Clone for Slow {
+        fn clone(&self) -> Self {
+            self.0.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+            Self(self.0.clone())
+        }
+    }
+    impl std::future::Future for Slow {
+        type Output = u8;
+        fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<u8> {
+            std::task::Poll::Ready(1)
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let template = AsyncLazy::new(Slow(tx));
+    let mut cx = std::task::Context::from_waker(futures::task::noop_waker_ref());
+    std::thread::scope(|s| {
+        let cloner = s.spawn(|| template.clone());
+        rx.recv().unwrap();
+        assert!(template.begin_init().is_some());
+        assert!(cloner.join().unwrap().is_uninit());
+
+        let cloner = s.spawn(|| template.clone());
+        rx.recv().unwrap();
+        assert!(template.force_now(&mut cx));
+        assert!(cloner.join().unwrap().is_uninit());
+    });
+    assert_eq!(template.try_get(), Some(&1));
+}
+
+#[cfg(feature = "alloc")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn shared () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use laizy::SharedAsyncLazy;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let lazy = SharedAsyncLazy::new({
+        let runs = runs.clone();
+        async move {
+            runs.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Arc::new(String::from("shared"))
+        }
+    });
+
+    let tasks = (0..100).map(|_| tokio::spawn(lazy.clone().get())).collect::<Vec<_>>();
+    for task in tasks {
+        assert_eq!(*task.await.unwrap(), "shared");
+    }
+
+    assert_eq!(runs.load(Ordering::Relaxed), 1);
+    assert_eq!(lazy.try_get().map(|value| value.as_str()), Some("shared"));
+}
+
+#[tokio::test]
+async fn with_arg () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // not `Clone`, so it can only be moved into the future once
+    struct Config(u8);
+    static BUILT: AtomicUsize = AtomicUsize::new(0);
+
+    let lazy = AsyncLazy::with_arg(Config(3), |config: Config| {
+        BUILT.fetch_add(1, Ordering::Relaxed);
+        async move { config.0 * 2 }
+    });
+    let (a, b) = futures::join!(lazy.get(), lazy.get());
+    assert_eq!((*a, *b), (6, 6));
+    assert_eq!(BUILT.load(Ordering::Relaxed), 1);
+
+    // the argument can be recovered until the future is built
+    let lazy: AsyncLazy<u8, _> = AsyncLazy::with_arg(Config(4), |config: Config| async move { config.0 });
+    let (config, f) = lazy.into_parts().ok().unwrap();
+    assert_eq!(config.0, 4);
+
+    let lazy = AsyncLazy::with_arg(config, f);
+    assert_eq!(lazy.get().await, &4);
+    assert!(lazy.into_parts().is_err());
+}
+
+#[test]
+fn local () {
+    use std::{rc::Rc, future::Future, sync::{Arc, atomic::{AtomicUsize, Ordering}}, task::Context};
+    use futures::{executor::LocalPool, task::{LocalSpawnExt, ArcWake, waker}};
+    use laizy::local;
+
+    struct Counter(AtomicUsize);
+    impl ArcWake for Counter {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut pool = LocalPool::new();
+    let spawner = pool.spawner();
+    let (tx, rx) = futures::channel::oneshot::channel::<()>();
+
+    // the future holds an `Rc`, so it isn't `Send`
+    let runs = Rc::new(std::cell::Cell::new(0));
+    let lazy = Rc::new(local::AsyncLazy::new({
+        let runs = runs.clone();
+        async move {
+            runs.set(runs.get() + 1);
+            rx.await.unwrap();
+            Rc::new(7u8)
+        }
+    }));
+
+    for _ in 0..4 {
+        let lazy = lazy.clone();
+        spawner.spawn_local(async move { assert_eq!(**lazy.get().await, 7) }).unwrap();
+    }
+
+    pool.run_until_stalled();
+    assert_eq!(lazy.state(), laizy::State::Initializing);
+    tx.send(()).unwrap();
+    pool.run();
+
+    assert_eq!(runs.get(), 1);
+    assert_eq!(lazy.try_get().map(|value| **value), Some(7));
+
+    let lazy = local::AsyncLazy::from_fn(|| async { Rc::new(1u8) });
+    assert_eq!(*pool.run_until(lazy.into_inner()), 1);
+
+    // dropping the `get` future running the initialization cancels the cell, and wakes its waiters
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let waker = waker(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let lazy = local::AsyncLazy::new(futures::future::pending::<u8>());
+    let (mut init, mut waiter) = (Box::pin(lazy.get()), Box::pin(lazy.get()));
+    assert!(init.as_mut().poll(&mut cx).is_pending());
+    assert!(waiter.as_mut().poll(&mut cx).is_pending());
+
+    drop(init);
+    assert_eq!(lazy.state(), laizy::State::Cancelled);
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| waiter.as_mut().poll(&mut cx))).is_err());
+}
+
+#[tokio::test]
+async fn lazy_stream () {
+    use futures::{Stream, StreamExt, stream::FusedStream};
+    use laizy::{LazyStream, AsyncLazyStream};
+
+    let built = std::cell::Cell::new(false);
+    let mut stream = LazyStream::new(|| {
+        built.set(true);
+        futures::stream::iter([1u8, 2, 3])
+    });
+
+    assert!(!built.get());
+    assert_eq!(stream.size_hint(), (0, None));
+    assert_eq!(stream.next().await, Some(1));
+    assert!(built.get());
+    assert_eq!(stream.size_hint(), (2, Some(2)));
+    assert_eq!(stream.collect::<Vec<_>>().await, [2, 3]);
+
+    // built by a future that's pending once
+    let mut pending = true;
+    let stream = AsyncLazyStream::new(std::future::poll_fn(move |cx| match std::mem::take(&mut pending) {
+        true => {
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        },
+        false => std::task::Poll::Ready(futures::stream::iter([4u8, 5]).fuse())
+    }));
+
+    futures::pin_mut!(stream);
+    assert!(!stream.is_terminated());
+    assert_eq!(futures::poll!(stream.next()), std::task::Poll::Pending);
+    assert!(!stream.is_initialized());
+    assert_eq!(stream.next().await, Some(4));
+    assert_eq!(stream.next().await, Some(5));
+    assert_eq!(stream.next().await, None);
+    assert!(stream.is_terminated());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn select () {
+    use futures::future::BoxFuture;
+    use laizy::Either;
+
+    fn source (delay: u64, value: u8) -> &'static AsyncLazy<u8, BoxFuture<'static, u8>> {
+        let f: BoxFuture<'static, u8> = Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            value
+        });
+        Box::leak(Box::new(AsyncLazy::new(f)))
+    }
+
+    let (fast, slow) = (source(10, 1), source(50, 2));
+    // keeps driving the loser once `select` is done with it
+    let waiter = tokio::spawn(slow.get_static());
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    assert_eq!(laizy::select(slow, fast).await, Either::Right(&1));
+    assert_eq!(waiter.await.unwrap(), &2);
+    assert!(fast.is_initialized() && slow.is_initialized());
+
+    // nobody waits on the loser, so the next access resumes it
+    let (fast, slow) = (source(10, 3), source(50, 4));
+    assert_eq!(laizy::race(fast, slow).await, &3);
+    assert!(slow.is_initializing());
+    assert_eq!(slow.get().await, &4);
+
+    // dropping it mid-way doesn't wedge either cell
+    let (a, b) = (source(20, 5), source(20, 6));
+    assert!(tokio::time::timeout(Duration::from_millis(5), laizy::race(a, b)).await.is_err());
+    assert_eq!((*a.get().await, *b.get().await), (5, 6));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn begin_init () {
+    use std::sync::Arc;
+    use futures::future::BoxFuture;
+
+    let make = |value: u8| -> BoxFuture<'static, u8> { Box::pin(async move { value }) };
+
+    // finished externally, while others wait
+    let lazy = Arc::new(AsyncLazy::new(make(1)));
+    let guard = lazy.begin_init().unwrap();
+    assert!(lazy.begin_init().is_none());
+
+    let waiter = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.wait().await }
+    });
+    let getter = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!getter.is_finished());
+    assert_eq!(guard.finish(2), &2);
+    assert_eq!((waiter.await.unwrap(), getter.await.unwrap()), (2, 2));
+    assert!(lazy.begin_init().is_none());
+
+    // aborted with a `get` waiting on it, which then runs the future itself
+    let lazy = Arc::new(AsyncLazy::new(make(3)));
+    let guard = lazy.begin_init().unwrap();
+    let getter = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!getter.is_finished());
+    guard.abort();
+    assert_eq!(getter.await.unwrap(), 3);
+
+    // dropping the guard does the same
+    let lazy = AsyncLazy::new(make(4));
+    drop(lazy.begin_init());
+    assert!(lazy.is_uninit());
+    assert_eq!(lazy.get().await, &4);
+}
+
+/// Future that counts how many times it's dropped
+#[cfg(any(feature = "tokio", feature = "async-once-cell"))]
+struct DropCounter<'a>(&'a std::sync::atomic::AtomicUsize);
+
+#[cfg(any(feature = "tokio", feature = "async-once-cell"))]
+impl std::future::Future for DropCounter<'_> {
+    type Output = u8;
+
+    fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<u8> {
+        std::task::Poll::Ready(1)
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "async-once-cell"))]
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn tokio_once_cell () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::OnceCell;
+    use laizy::AsyncOnce;
+
+    let lazy = AsyncLazy::new(async { 1u8 });
+    lazy.get().await;
+    let cell = lazy.into_tokio_once_cell();
+    assert_eq!(cell.get(), Some(&1));
+
+    let lazy = AsyncLazy::<u8, DropCounter>::try_from(cell).unwrap();
+    assert_eq!(lazy.try_get(), Some(&1));
+    assert!(AsyncLazy::<u8, DropCounter>::try_from(OnceCell::new()).is_err());
+
+    // the future is dropped without being polled
+    let drops = AtomicUsize::new(0);
+    let cell: OnceCell<u8> = OnceCell::from(AsyncLazy::new(DropCounter(&drops)));
+    assert!(!cell.initialized());
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+    let once = AsyncOnce::from(OnceCell::new_with(Some(2u8)));
+    assert_eq!(once.get(), Some(&2));
+    assert_eq!(OnceCell::from(once).get(), Some(&2));
+    assert_eq!(AsyncOnce::<u8>::from(OnceCell::new()).get(), None);
+}
+
+#[cfg(feature = "async-once-cell")]
+#[tokio::test]
+async fn async_once_cell () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use async_once_cell::OnceCell;
+    use laizy::AsyncOnce;
+
+    let lazy = AsyncLazy::new(async { 1u8 });
+    lazy.get().await;
+    let cell = lazy.into_async_once_cell();
+    assert_eq!(cell.get(), Some(&1));
+
+    let lazy = AsyncLazy::<u8, DropCounter>::try_from(cell).unwrap();
+    assert_eq!(lazy.try_get(), Some(&1));
+    assert!(AsyncLazy::<u8, DropCounter>::try_from(OnceCell::new()).is_err());
+
+    // the future is dropped without being polled
+    let drops = AtomicUsize::new(0);
+    let cell: OnceCell<u8> = OnceCell::from(AsyncLazy::new(DropCounter(&drops)));
+    assert_eq!(cell.get(), None);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+    let once = AsyncOnce::from(OnceCell::new_with(2u8));
+    assert_eq!(once.get(), Some(&2));
+    assert_eq!(OnceCell::from(once).get(), Some(&2));
+    assert_eq!(AsyncOnce::<u8>::from(OnceCell::new()).get(), None);
+}
+
+#[test]
+fn force_now () {
+    let waker = futures::task::noop_waker();
+    let mut cx = std::task::Context::from_waker(&waker);
+
+    let lazy = AsyncLazy::new(futures::future::ready(1u8));
+    assert!(lazy.force_now(&mut cx));
+    assert_eq!(lazy.try_get(), Some(&1));
+    assert!(lazy.force_now(&mut cx));
+
+    // pending once: kept in the cell until the next attempt
+    let mut pending = true;
+    let lazy = AsyncLazy::new(std::future::poll_fn(move |_| match std::mem::take(&mut pending) {
+        true => std::task::Poll::Pending,
+        false => std::task::Poll::Ready(2u8)
+    }));
+
+    assert!(!lazy.force_now(&mut cx));
+    assert!(lazy.is_initializing());
+    assert!(lazy.force_now(&mut cx));
+    assert_eq!(futures::executor::block_on(lazy.get()), &2);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn new_blocking () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let lazy = AsyncLazy::new_blocking(|| {
+        std::thread::sleep(Duration::from_millis(100));
+        3u8
+    });
+
+    // the timer keeps ticking while the closure blocks
+    let ticks = AtomicUsize::new(0);
+    let timer = async {
+        while !lazy.is_initialized() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            ticks.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+
+    let (value, ()) = tokio::join!(lazy.get(), timer);
+    assert_eq!(value, &3);
+    assert!(ticks.load(Ordering::Relaxed) >= 5);
+
+    // a panic poisons the cell
+    let lazy = AsyncLazy::<u8, _>::new_blocking(|| panic!("blocking initializer"));
+    let get = std::panic::AssertUnwindSafe(lazy.get());
+    assert!(futures::FutureExt::catch_unwind(get).await.is_err());
+    assert!(lazy.is_poisoned());
+}
+
+#[tokio::test]
+async fn map_initializer () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    #[derive(Clone)]
+    struct Tracked(Arc<AtomicUsize>, u8);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // the future is moved into the wrapper, not dropped
+    let drops = Arc::new(AtomicUsize::new(0));
+    let lazy = AsyncLazy::new({
+        let tracked = Tracked(drops.clone(), 1);
+        async move {
+            let tracked = tracked;
+            tracked.1
+        }
+    });
+    let lazy = lazy.map_initializer(|f| async move {
+        tokio::time::timeout(Duration::from_secs(1), f).await.unwrap()
+    });
+    assert_eq!(drops.load(Ordering::Relaxed), 0);
+    assert_eq!(lazy.get().await, &1);
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+    // the value is passed through
+    let lazy = AsyncLazy::new(async { unreachable!() });
+    lazy.set(Tracked(drops.clone(), 2)).unwrap_or_else(|_| unreachable!());
+    let lazy = lazy.map_initializer(|_| async { unreachable!() });
+    assert_eq!(drops.load(Ordering::Relaxed), 1);
+    assert_eq!(lazy.try_get().map(|value| value.1), Some(2));
+    drop(lazy);
+    assert_eq!(drops.load(Ordering::Relaxed), 2);
+
+    // the waker left by `poll_get` is dropped, whether the future is wrapped or not
+    struct Counter;
+    impl futures::task::ArcWake for Counter {
+        fn wake_by_ref(_: &Arc<Self>) {}
+    }
+
+    let counter = Arc::new(Counter);
+    let waker = futures::task::waker(counter.clone());
+    let cx = &mut std::task::Context::from_waker(&waker);
+    for wrap in [true, false] {
+        let (tx, rx) = futures::channel::oneshot::channel::<u8>();
+        let lazy = AsyncLazy::new(Box::pin(async { rx.await.unwrap() }) as futures::future::BoxFuture<'static, u8>);
+        assert!(lazy.poll_get(cx).is_pending());
+        if !wrap {
+            lazy.abort();
+        }
+
+        drop((lazy.map_initializer(|f| f), tx));
+        assert_eq!(Arc::strong_count(&counter), 2);
+    }
+}
+
+#[tokio::test]
+async fn get_ready () {
+    type Config = laizy::FromFn<fn() -> std::future::Ready<String>>;
+    static CONFIG: AsyncLazy<String, Config> = AsyncLazy::from_fn(|| std::future::ready(String::new()));
+    let lazy = laizy::async_lazy(async { String::from("ready") });
+
+    // built early, used late
+    let view = lazy.as_ready();
+    assert_eq!(view.try_get(), None);
+    lazy.get().await;
+    assert_eq!(lazy.get_ready(), "ready");
+    assert_eq!(view.len(), 5);
+
+    let err = std::panic::catch_unwind(|| CONFIG.get_ready().len()).unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.contains("alloc::string::String") && msg.contains("Uninit"), "{msg}");
+    assert!(std::panic::catch_unwind(|| CONFIG.as_ready().len()).is_err());
+    assert!(CONFIG.is_uninit());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cached () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use futures::future::BoxFuture;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut cached = laizy::AsyncCached::new({
+        let calls = calls.clone();
+        move || -> BoxFuture<'static, usize> {
+            let calls = calls.clone();
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                calls.fetch_add(1, Ordering::Relaxed) + 1
+            })
+        }
+    });
+
+    // computed once
+    assert_eq!(cached.try_get(), None);
+    let values = futures::future::join_all((0..10).map(|_| cached.get())).await;
+    assert!(values.into_iter().all(|x| x == 1));
+    assert_eq!(cached.get().await, 1);
+
+    // invalidated while idle
+    cached.invalidate();
+    assert!(!cached.is_valid());
+    assert_eq!(cached.get().await, 2);
+    cached.invalidate_shared();
+    assert_eq!(cached.try_get(), None);
+    assert_eq!(cached.get().await, 3);
+
+    // invalidated while someone is awaiting, which starts the computation over
+    let cached = Arc::new(cached);
+    cached.invalidate_shared();
+    let getters = (0..4).map(|_| tokio::spawn({
+        let cached = cached.clone();
+        async move { cached.get().await }
+    })).collect::<Vec<_>>();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    cached.invalidate_shared();
+    for getter in getters {
+        assert_eq!(getter.await.unwrap(), 5);
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 5);
+    assert!(cached.is_valid());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cached_readers () {
+    use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
+    use futures::future::BoxFuture;
+
+    // readers clone the value while it's invalidated and replaced under them
+    let calls = Arc::new(AtomicUsize::new(0));
+    let cached = Arc::new(laizy::AsyncCached::<String, _>::new({
+        let calls = calls.clone();
+        move || -> BoxFuture<'static, String> {
+            let calls = calls.clone();
+            Box::pin(async move { calls.fetch_add(1, Ordering::Relaxed).to_string().repeat(64) })
+        }
+    }));
+
+    let done = Arc::new(AtomicBool::new(false));
+    let readers = (0..4).map(|_| std::thread::spawn({
+        let (cached, done) = (cached.clone(), done.clone());
+        move || while !done.load(Ordering::Relaxed) {
+            if let Some(value) = cached.try_get() {
+                assert_eq!(value.len() % 64, 0);
+            }
+        }
+    })).collect::<Vec<_>>();
+
+    for i in 0..256 {
+        cached.invalidate_shared();
+        let value = cached.get().await;
+        assert_eq!(value, i.to_string().repeat(64));
+    }
+
+    done.store(true, Ordering::Relaxed);
+    readers.into_iter().for_each(|reader| reader.join().unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timed_lazy () {
+    use std::sync::{Arc, atomic::{AtomicU64, AtomicUsize, Ordering}};
+    use futures::future::BoxFuture;
+
+    let now = Arc::new(AtomicU64::new(0));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let (release, wait) = (Arc::new(tokio::sync::Notify::new()), Arc::new(std::sync::atomic::AtomicBool::new(false)));
+
+    let lazy = Arc::new(laizy::AsyncTimedLazy::new(Duration::from_secs(60), {
+        let now = now.clone();
+        move || Duration::from_secs(now.load(Ordering::Relaxed))
+    }, {
+        let (calls, release, wait) = (calls.clone(), release.clone(), wait.clone());
+        move || -> BoxFuture<'static, usize> {
+            let (calls, release, wait) = (calls.clone(), release.clone(), wait.clone());
+            Box::pin(async move {
+                if wait.load(Ordering::Relaxed) {
+                    release.notified().await;
+                }
+                calls.fetch_add(1, Ordering::Relaxed) + 1
+            })
+        }
+    }));
+
+    // fresh hit
+    assert_eq!(lazy.get().await, 1);
+    now.store(59, Ordering::Relaxed);
+    assert_eq!(lazy.try_get(), Some(1));
+    assert_eq!(lazy.get().await, 1);
+
+    // expired refresh, counting the age from then on
+    now.store(60, Ordering::Relaxed);
+    assert_eq!(lazy.try_get(), None);
+    assert_eq!(lazy.get().await, 2);
+    now.store(119, Ordering::Relaxed);
+    assert_eq!(lazy.get().await, 2);
+
+    // concurrent callers during the refresh share it
+    now.store(120, Ordering::Relaxed);
+    wait.store(true, Ordering::Relaxed);
+    let getters = (0..8).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move { lazy.get().await }
+    })).collect::<Vec<_>>();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    release.notify_one();
+    for getter in getters {
+        assert_eq!(getter.await.unwrap(), 3);
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+}
+
+#[cfg(feature = "std")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn lazy_map () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    let map = Arc::new(laizy::AsyncLazyMap::<usize, String>::new());
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let fetch = |map: Arc<laizy::AsyncLazyMap<usize, String>>, calls: Arc<AtomicUsize>, key: usize| async move {
+        map.get_or_init(key, || async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            calls.fetch_add(1, Ordering::Relaxed);
+            key.to_string()
+        }).await
+    };
+
+    let gets = (0..100).map(|i| tokio::spawn(fetch(map.clone(), calls.clone(), i % 10))).collect::<Vec<_>>();
+    for (i, get) in gets.into_iter().enumerate() {
+        assert_eq!(*get.await.unwrap(), (i % 10).to_string());
+    }
+    assert_eq!(calls.load(Ordering::Relaxed), 10);
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.get(&3).as_deref().map(String::as_str), Some("3"));
+
+    // removed keys are initialized again
+    assert!(map.remove(&3).is_some());
+    assert_eq!(map.get(&3), None);
+    assert_eq!(*fetch(map.clone(), calls.clone(), 3).await, "3");
+    assert_eq!(calls.load(Ordering::Relaxed), 11);
+
+    map.clear();
+    assert!(map.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_or_init_with () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+
+    /// Future that counts how many times it's dropped and polled
+    struct Counted(&'static AtomicUsize, &'static AtomicUsize, u8);
+
+    impl std::future::Future for Counted {
+        type Output = u8;
+
+        fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<u8> {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            std::task::Poll::Ready(self.2)
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    static POLLS: AtomicUsize = AtomicUsize::new(0);
+    let lazy = Arc::new(AsyncLazy::new(Counted(&DROPS, &POLLS, 0)));
+
+    // two callers with distinguishable futures race, and exactly one of them runs
+    let ran = Arc::new(AtomicUsize::new(0));
+    let callers = (1..=2).map(|id| tokio::spawn({
+        let (lazy, ran) = (lazy.clone(), ran.clone());
+        async move {
+            *lazy.get_or_init_with(async move {
+                ran.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                id
+            }).await
+        }
+    })).collect::<Vec<_>>();
+
+    let mut values = Vec::new();
+    for caller in callers {
+        values.push(caller.await.unwrap());
+    }
+    assert_eq!(ran.load(Ordering::Relaxed), 1);
+    assert!(values[0] == values[1] && values[0] != 0);
+
+    // the stored future was dropped without being polled
+    assert_eq!((DROPS.load(Ordering::Relaxed), POLLS.load(Ordering::Relaxed)), (1, 0));
+    assert_eq!(lazy.get_or_init_with(async { 3 }).await, &values[0]);
+}
+
+#[tokio::test]
+async fn partial_eq () {
+    let (a, b) = (laizy::async_lazy(async { 1u8 }), laizy::async_lazy(async { 1u8 }));
+
+    // uninit/uninit, even with itself
+    assert_ne!(a, b);
+    #[allow(clippy::eq_op)]
+    let reflexive = a == a;
+    assert!(!reflexive);
+    assert_ne!(a, 1);
+
+    // init/uninit
+    a.get().await;
+    assert_ne!(a, b);
+    assert_ne!(b, a);
+    assert!(b.is_uninit());
+
+    // init/init
+    b.get().await;
+    assert_eq!(a, b);
+    assert_eq!(a, 1);
+    assert_ne!(a, AsyncLazy::<u8, std::future::Ready<u8>>::init(2));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn display () {
+    use std::sync::Arc;
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let shared = Arc::new(AsyncLazy::new(async move {
+        rx.await.unwrap();
+        255u8
+    }));
+    let lazy = &*shared;
+
+    assert_eq!(format!("{lazy} {lazy:x} {lazy:#b} {lazy:>16}"), "<uninitialized> <uninitialized> <uninitialized>  <uninitialized>");
+
+    let getter = tokio::spawn({
+        let shared = shared.clone();
+        async move { *shared.get().await }
+    });
+    while !lazy.is_initializing() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(format!("{lazy} {lazy:X} {lazy:o}"), "<initializing> <initializing> <initializing>");
+
+    tx.send(()).unwrap();
+    assert_eq!(getter.await.unwrap(), 255);
+    assert_eq!(format!("{lazy} {lazy:x} {lazy:#X} {lazy:o} {lazy:#b} {lazy:>4}"), "255 ff 0xFF 377 0b11111111  255");
+}
+
+#[tokio::test]
+async fn display_states () {
+    use std::{future::Future, pin::Pin, sync::Arc, task::Poll};
+
+    // partially polled by `poll_get`, and then dropped by `abort`
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let parked = AsyncLazy::new(Box::pin(async move {
+        let _ = rx.await;
+        1u8
+    }) as Pin<Box<dyn Future<Output = u8>>>);
+    assert!(std::future::poll_fn(|cx| Poll::Ready(parked.poll_get(cx).is_pending())).await);
+    assert_eq!(format!("{parked} {parked:x} {parked:>16}"), "<initializing> <initializing>   <initializing>");
+    assert!(parked.abort());
+    assert_eq!(format!("{parked} {parked:x} {parked:>16}"), "<cancelled> <cancelled>      <cancelled>");
+
+    // aborted while its driver is running it, before the driver notices
+    let (_tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let shared = Arc::new(AsyncLazy::new(async move {
+        let _ = rx.await;
+        1u8
+    }));
+    let running = &*shared;
+    let driver = tokio::spawn({
+        let shared = shared.clone();
+        async move { *shared.get().await }
+    });
+    while !running.is_initializing() {
+        tokio::task::yield_now().await;
+    }
+    assert!(running.abort());
+    assert_eq!(format!("{running} {running:X}"), "<cancelled> <cancelled>");
+    assert!(driver.await.is_err());
+    assert_eq!(format!("{running} {running:X}"), "<cancelled> <cancelled>");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn waiter_count () {
+    use std::sync::Arc;
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let lazy = Arc::new(AsyncLazy::new(async move {
+        rx.await.unwrap();
+        1u8
+    }));
+    assert_eq!(lazy.waiter_count(), 0);
+    #[cfg(feature = "std")]
+    assert_eq!(lazy.initializing_since(), None);
+
+    let driver = tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    });
+    while !lazy.is_initializing() {
+        tokio::task::yield_now().await;
+    }
+
+    // three waiters parked behind the gated future
+    let waiters = (0..3).map(|_| tokio::spawn({
+        let lazy = lazy.clone();
+        async move { *lazy.get().await }
+    })).collect::<Vec<_>>();
+    while lazy.waiter_count() < 3 {
+        tokio::task::yield_now().await;
+    }
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(lazy.waiter_count(), 3);
+    #[cfg(feature = "std")]
+    assert!(lazy.initializing_since().unwrap() >= Duration::from_millis(20));
+
+    tx.send(()).unwrap();
+    assert_eq!(driver.await.unwrap(), 1);
+    for waiter in waiters {
+        assert_eq!(waiter.await.unwrap(), 1);
+    }
+    assert_eq!(lazy.waiter_count(), 0);
+    #[cfg(feature = "std")]
+    assert_eq!(lazy.initializing_since(), None);
+}
+
+#[tokio::test]
+async fn into_future () {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Builder that records when it's converted into a future
+    struct Request<'a>(&'a AtomicUsize, u8);
+
+    impl<'a> std::future::IntoFuture for Request<'a> {
+        type Output = u8;
+        type IntoFuture = std::future::Ready<u8>;
+
+        fn into_future(self) -> Self::IntoFuture {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            std::future::ready(self.1)
+        }
+    }
+
+    let converted = AtomicUsize::new(0);
+    let lazy = laizy::async_lazy(Request(&converted, 1));
+    let from_fn = AsyncLazy::from_fn(|| Request(&converted, 2));
+    assert_eq!(converted.load(Ordering::Relaxed), 0);
+
+    // converted once it's first driven, and only then
+    let get = lazy.get();
+    assert_eq!(converted.load(Ordering::Relaxed), 0);
+    assert_eq!(get.await, &1);
+    assert_eq!(converted.load(Ordering::Relaxed), 1);
+    assert_eq!(lazy.get().await, &1);
+    assert_eq!(from_fn.get().await, &2);
+    assert_eq!(converted.load(Ordering::Relaxed), 2);
+
+    // never converted if it's never needed
+    let unused = AsyncLazy::new(Request(&converted, 3));
+    assert!(unused.set(3).is_ok());
+    assert_eq!(unused.get().await, &3);
+    assert_eq!(converted.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn join_macro () {
+    use std::time::Instant;
+    use futures::future::BoxFuture;
+
+    type Slow = laizy::FromFn<fn() -> BoxFuture<'static, u8>>;
+    static A: AsyncLazy<u8, Slow> = AsyncLazy::from_fn(|| Box::pin(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        1
+    }));
+
+    let b = laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        "b"
+    });
+    let c = std::sync::Arc::new(laizy::async_lazy(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        3.0
+    }));
+
+    // concurrently, not one after the other
+    let start = Instant::now();
+    let (a, b, c) = laizy::join!(A, &b, c);
+    assert!(start.elapsed() < Duration::from_millis(250), "{:?}", start.elapsed());
+    assert_eq!((*a, *b, *c), (1, "b", 3.0));
+    assert_eq!(laizy::join!(A), (&1,));
+
+    let ok = laizy::AsyncTryLazy::<u8, &str, _>::new(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok(1)
+    });
+    let err = laizy::AsyncTryLazy::<u16, &str, _>::new(async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Err("failed")
+    });
+
+    let start = Instant::now();
+    assert_eq!(laizy::try_join!(&ok, &err), Err(&"failed"));
+    assert!(start.elapsed() < Duration::from_millis(150));
+    assert_eq!(laizy::try_join!(ok), Ok((&1,)));
+}
+
+#[cfg(feature = "alloc")]
+#[tokio::test(flavor = "multi_thread")]
+async fn boxed_async_lazy () {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    use laizy::{BoxedAsyncLazy, BoxedAsyncLazyFn, LocalBoxedAsyncLazy};
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        port: u16
+    }
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static CONFIG: BoxedAsyncLazyFn<Config> = AsyncLazy::from_fn(|| Box::pin(async {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Config { port: 8080 }
+    }));
+
+    let handles = (0..8).map(|_| tokio::spawn(async { CONFIG.get().await.port })).collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap(), 8080);
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    // a struct field initialized from a capturing `async` block
+    struct Service {
+        config: BoxedAsyncLazy<Config>
+    }
+
+    let port = 9090;
+    let service = Arc::new(Service { config: AsyncLazy::boxed(async move { Config { port } }) });
+    let handles = (0..4).map(|_| {
+        let service = service.clone();
+        tokio::spawn(async move { service.config.get().await.port })
+    }).collect::<Vec<_>>();
+    for handle in handles {
+        assert_eq!(handle.await.unwrap(), 9090);
+    }
+
+    let shared = std::rc::Rc::new(1);
+    let local: LocalBoxedAsyncLazy<i32> = AsyncLazy::boxed_local({
+        let shared = shared.clone();
+        async move { *shared + 1 }
+    });
+    assert_eq!(local.get().await, &2);
+}
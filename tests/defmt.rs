@@ -0,0 +1,70 @@
+#![cfg(feature = "defmt")]
+
+use std::{cell::RefCell, sync::atomic::{AtomicUsize, Ordering}};
+use laizy::Lazy;
+
+// captures the encoded frames, so that formatting can be checked on the host
+#[defmt::global_logger]
+struct Capture;
+
+thread_local! {
+    static FRAMES: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe impl defmt::Logger for Capture {
+    fn acquire() {}
+    unsafe fn flush() {}
+    unsafe fn release() {}
+    unsafe fn write(bytes: &[u8]) {
+        FRAMES.with(|frames| frames.borrow_mut().extend_from_slice(bytes))
+    }
+}
+
+defmt::timestamp!("");
+
+/// Encodes `value`, returning its frame
+fn encode (value: &impl defmt::Format) -> Vec<u8> {
+    FRAMES.with(|frames| frames.borrow_mut().clear());
+    defmt::println!("{}", value);
+    FRAMES.with(|frames| frames.take())
+}
+
+#[test]
+fn lazy () {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let lazy = Lazy::new(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        7u32
+    });
+
+    // formatting never initializes it, and the value only shows up once it's initialized
+    let uninit = encode(&lazy);
+    assert_eq!(encode(&lazy), uninit);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+
+    lazy.get();
+    let init = encode(&lazy);
+    assert_ne!(init, uninit);
+    assert_eq!(init, encode(&Lazy::<u32>::init(7)));
+    assert_ne!(init, encode(&Lazy::<u32>::init(8)));
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    let poisoned = Lazy::new(|| -> u32 { panic!("initialization failed") });
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *poisoned.get())).is_err());
+    let poisoned = encode(&poisoned);
+    assert_ne!(poisoned, uninit);
+    assert_ne!(poisoned, init);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn async_lazy () {
+    let lazy = laizy::AsyncLazy::new(async { 7u32 });
+    let uninit = encode(&lazy);
+    assert!(lazy.try_get().is_none());
+
+    futures::executor::block_on(lazy.get());
+    let init = encode(&lazy);
+    assert_ne!(init, uninit);
+    assert_eq!(init, encode(&laizy::AsyncLazy::<u32, std::future::Ready<u32>>::init(7)));
+}
@@ -0,0 +1,375 @@
+//! Scenarios meant to be run under loom (`make loom`), which checks every interleaving (and every value a load is allowed
+//! to see) of the real cells for data races: under ```--cfg loom```, their states are loom's atomics, and their accesses
+//! to the value are reported to it (see `src/sync.rs`). Without it, they run as ordinary threaded tests.
+//!
+//! Every cell is built inside of the model, since loom's atomics only live for one of its executions
+
+use shim::{model, Arc, mpsc, thread};
+use laizy::{Lazy, policy::Repanic, strategy::{WaitStrategy, DefaultStrategy, Spin, Backoff}};
+
+#[cfg(loom)]
+mod shim {
+    pub use loom::{sync::{Arc, mpsc}, thread};
+
+    /// Runs `f` once per interleaving, with at most 4 preemptions each (waiting is spinning under loom, so an unbounded
+    /// search wouldn't end), unless overridden with ```LOOM_MAX_PREEMPTIONS```
+    pub fn model (f: impl Fn() + Sync + Send + 'static) {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound.get_or_insert(4);
+        builder.check(f)
+    }
+}
+
+#[cfg(not(loom))]
+mod shim {
+    pub use std::sync::{Arc, mpsc};
+    pub use std::thread;
+
+    /// Runs `f` a number of times, instead of once per interleaving
+    pub fn model (f: impl Fn() + Sync + Send + 'static) {
+        for _ in 0..64 {
+            f()
+        }
+    }
+}
+
+/// Waits for `cell` to be ours alone
+fn unwrap<T> (mut cell: Arc<T>) -> T {
+    loop {
+        match Arc::try_unwrap(cell) {
+            Ok(cell) => return cell,
+            Err(shared) => {
+                cell = shared;
+                thread::yield_now()
+            }
+        }
+    }
+}
+
+/// Initialized on one thread, moved through a channel, and consumed on another
+#[test]
+fn initialize_then_send () {
+    model(|| {
+        let (tx, rx) = mpsc::channel();
+        let a = thread::spawn(move || {
+            let cell = Lazy::new(|| String::from("value"));
+            assert_eq!(cell.get().len(), 5);
+            tx.send(cell).unwrap();
+        });
+
+        let b = thread::spawn(move || rx.recv().unwrap().into_inner());
+        a.join().unwrap();
+        assert_eq!(b.join().unwrap(), "value");
+    })
+}
+
+/// Initialized by either of two threads sharing it, and consumed by one once the other lets go of it
+#[test]
+fn initialize_then_unwrap () {
+    model(|| {
+        let cell = Arc::new(Lazy::new(|| String::from("value")));
+        let other = thread::spawn({
+            let cell = cell.clone();
+            move || assert_eq!(cell.get().len(), 5)
+        });
+
+        assert_eq!(cell.get().len(), 5);
+        assert_eq!(unwrap(cell).into_inner(), "value");
+        other.join().unwrap();
+    })
+}
+
+/// Maybe initialized by the thread sharing it, and consumed by the other one once it lets go of it
+#[test]
+fn initialize_then_try_unwrap () {
+    model(|| {
+        let cell = Arc::new(Lazy::new(|| String::from("value")));
+        let other = thread::spawn({
+            let cell = cell.clone();
+            move || assert_eq!(cell.get().len(), 5)
+        });
+
+        assert_eq!(unwrap(cell).try_into_inner().ok().unwrap(), "value");
+        other.join().unwrap();
+    })
+}
+
+/// Dropped by whichever of two threads lets go of it last, while the other may still be initializing it
+#[test]
+fn initialize_then_drop () {
+    model(|| {
+        let cell = Arc::new(Lazy::new(|| String::from("value")));
+        let other = thread::spawn({
+            let cell = cell.clone();
+            move || assert_eq!(cell.get().len(), 5)
+        });
+
+        drop(cell);
+        other.join().unwrap();
+    })
+}
+
+/// Initialized on one thread, and dropped on another it's sent to
+#[test]
+fn initialize_then_send_and_drop () {
+    model(|| {
+        let (tx, rx) = mpsc::channel();
+        let a = thread::spawn(move || {
+            let cell = Lazy::new(|| String::from("value"));
+            assert_eq!(cell.get().len(), 5);
+            tx.send(cell).unwrap();
+        });
+
+        let b = thread::spawn(move || drop(rx.recv().unwrap()));
+        a.join().unwrap();
+        b.join().unwrap();
+    })
+}
+
+/// Never initialized, and dropped (along with its initializer) on another thread it's sent to
+#[test]
+fn uninitialized_then_send_and_drop () {
+    model(|| {
+        let (tx, rx) = mpsc::channel();
+        let name = String::from("value");
+        let a = thread::spawn(move || tx.send(Lazy::<String, _>::new(move || name)).unwrap());
+        let b = thread::spawn(move || drop(rx.recv().unwrap()));
+        a.join().unwrap();
+        b.join().unwrap();
+    })
+}
+
+/// Poisoned by either of two threads sharing it, its payload read by both, and dropped by whichever lets go of it last
+#[cfg(feature = "std")]
+#[test]
+fn poison_then_drop () {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    fn get (cell: &Lazy<String>) {
+        assert!(catch_unwind(AssertUnwindSafe(|| cell.get().len())).is_err());
+        let payload = cell.poison_payload().and_then(|payload| payload.downcast_ref::<&str>());
+        assert_eq!(payload, Some(&"initialization failed"));
+    }
+
+    quiet_panics();
+    model(|| {
+        let cell = Arc::new(Lazy::<String>::new(|| panic!("initialization failed")));
+        let other = thread::spawn({
+            let cell = cell.clone();
+            move || get(&cell)
+        });
+
+        get(&cell);
+        drop(cell);
+        other.join().unwrap();
+    })
+}
+
+/// Keeps the expected panics of the initializers from flooding the output, since loom runs them once per interleaving
+#[cfg(feature = "std")]
+fn quiet_panics () {
+    static QUIET: std::sync::Once = std::sync::Once::new();
+    QUIET.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let expected = ["initialization failed", "poisoned"].iter()
+                .any(|msg| info.payload().downcast_ref::<&str>() == Some(msg) || info.payload().downcast_ref::<String>().is_some_and(|s| s.contains(msg)));
+
+            if !expected {
+                hook(info)
+            }
+        }))
+    })
+}
+
+/// Read through the fast path (`get`, then `try_get`) while another thread initializes it, and while a third one waits
+/// for it with `S`, so that the values read on the fast path or once waiting are checked against the initializer's write
+fn fast_path<S: WaitStrategy + 'static> () {
+    model(|| {
+        let cell = Arc::new(Lazy::<String, fn() -> String, Repanic, S>::with_policy(|| String::from("value")));
+        let threads = [0, 1].map(|_| thread::spawn({
+            let cell = cell.clone();
+            move || assert_eq!(cell.get().len(), 5)
+        }));
+
+        // give the other threads a head start, so that the value may be read initialized
+        thread::yield_now();
+        if let Some(value) = cell.try_get() {
+            assert_eq!(value, "value");
+        }
+        for thread in threads {
+            thread.join().unwrap()
+        }
+    })
+}
+
+#[test]
+fn fast_path_during_initialization () {
+    fast_path::<DefaultStrategy>()
+}
+
+#[test]
+fn fast_path_while_spinning () {
+    fast_path::<Spin>()
+}
+
+#[test]
+fn fast_path_while_backing_off () {
+    fast_path::<Backoff>()
+}
+
+#[cfg(feature = "futures")]
+mod asnc {
+    use super::{model, mpsc, thread, Arc};
+    use core::{future::{Future, Ready, ready}, pin::{Pin, pin}, task::{Context, Poll}};
+    use laizy::AsyncLazy;
+
+    /// Pending the first time it's polled (waking its task right away), ready with its value the next
+    pub struct PendingOnce {
+        value: Option<String>,
+        polled: bool
+    }
+
+    impl PendingOnce {
+        pub fn new (value: &str) -> Self {
+            Self { value: Some(String::from(value)), polled: false }
+        }
+    }
+
+    impl Future for PendingOnce {
+        type Output = String;
+
+        fn poll (mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<String> {
+            if !core::mem::replace(&mut self.polled, true) {
+                cx.waker().wake_by_ref();
+                return Poll::Pending
+            }
+            Poll::Ready(self.value.take().unwrap())
+        }
+    }
+
+    /// Polls `f` until it's ready, yielding the thread in between
+    pub fn block_on<F: Future> (f: F) -> F::Output {
+        let mut f = pin!(f);
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        loop {
+            match f.as_mut().poll(&mut cx) {
+                Poll::Ready(value) => return value,
+                Poll::Pending => thread::yield_now()
+            }
+        }
+    }
+
+    /// Initialized on one thread, moved through a channel, and consumed on another
+    #[test]
+    fn initialize_then_send () {
+        model(|| {
+            let (tx, rx) = mpsc::channel();
+            let a = thread::spawn(move || {
+                let cell = AsyncLazy::<String, Ready<String>>::new(ready(String::from("value")));
+                assert_eq!(block_on(cell.get()).len(), 5);
+                tx.send(cell).unwrap();
+            });
+
+            let b = thread::spawn(move || block_on(rx.recv().unwrap().into_inner()));
+            a.join().unwrap();
+            assert_eq!(b.join().unwrap(), "value");
+        })
+    }
+
+    /// Polled once on each of two threads sharing it (each thread's waker left behind, either as the one of the task
+    /// driving the initialization or as a waiter's), and dropped by whichever lets go of it last
+    #[test]
+    fn wakers_then_drop () {
+        use futures::task::ArcWake;
+        use std::sync::Arc as StdArc;
+
+        struct Noop;
+        impl ArcWake for Noop {
+            fn wake_by_ref (_: &StdArc<Self>) {}
+        }
+
+        fn poll (cell: &AsyncLazy<String, PendingOnce>, waker: &StdArc<Noop>) {
+            let waker = futures::task::waker(waker.clone());
+            if let Poll::Ready(value) = cell.poll_get(&mut Context::from_waker(&waker)) {
+                assert_eq!(value, "value")
+            }
+        }
+
+        model(|| {
+            let wakers = StdArc::new([StdArc::new(Noop), StdArc::new(Noop)]);
+            let cell = Arc::new(AsyncLazy::new(PendingOnce::new("value")));
+            let other = thread::spawn({
+                let (cell, wakers) = (cell.clone(), wakers.clone());
+                move || poll(&cell, &wakers[1])
+            });
+
+            poll(&cell, &wakers[0]);
+            drop(cell);
+            other.join().unwrap();
+            assert!(wakers.iter().all(|waker| StdArc::strong_count(waker) == 1));
+        })
+    }
+
+    /// Started by a `get` future that's dropped before it finishes on one thread, and dropped (along with the suspended
+    /// future) by whichever of two threads sharing it lets go of it last
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn suspend_then_drop () {
+        model(|| {
+            let cell = Arc::new(AsyncLazy::new(PendingOnce::new("value")));
+            let other = thread::spawn({
+                let cell = cell.clone();
+                move || {
+                    let get = pin!(cell.get());
+                    let _ = get.poll(&mut Context::from_waker(futures::task::noop_waker_ref()));
+                }
+            });
+
+            drop(cell);
+            other.join().unwrap();
+        })
+    }
+
+    /// Started by a `get` future that's dropped before it finishes on one thread, resumed by a `get` future on the
+    /// other, and dropped by whichever lets go of it last
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn suspend_then_resume () {
+        model(|| {
+            let cell = Arc::new(AsyncLazy::new(PendingOnce::new("value")));
+            let other = thread::spawn({
+                let cell = cell.clone();
+                move || {
+                    let get = pin!(cell.get());
+                    let _ = get.poll(&mut Context::from_waker(futures::task::noop_waker_ref()));
+                }
+            });
+
+            assert_eq!(block_on(cell.get()), "value");
+            drop(cell);
+            other.join().unwrap();
+        })
+    }
+
+    /// Read through the fast path (`try_get`) while the two other threads sharing it initialize it or wait for it
+    #[test]
+    fn fast_path_during_initialization () {
+        model(|| {
+            let cell = Arc::new(AsyncLazy::<String, Ready<String>>::new(ready(String::from("value"))));
+            let threads = [0, 1].map(|_| thread::spawn({
+                let cell = cell.clone();
+                move || assert_eq!(block_on(cell.get()), "value")
+            }));
+
+            // give the other threads a head start, so that the value may be read initialized
+            thread::yield_now();
+            if let Some(value) = cell.try_get() {
+                assert_eq!(value, "value");
+            }
+            for thread in threads {
+                thread.join().unwrap()
+            }
+        })
+    }
+}
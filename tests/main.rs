@@ -1,5 +1,5 @@
-use std::{sync::{Mutex}};
-use laizy::{Lazy};
+use std::{sync::{Mutex, atomic::{AtomicUsize, Ordering}}, time::Duration};
+use laizy::{Lazy, policy::{Repanic, Retry, Abort}, strategy::{WaitStrategy, Spin, Backoff}};
 
 static SYNC : Lazy<Mutex<Vec<u8>>> = Lazy::new(|| Mutex::new(Vec::with_capacity(10)));
 
@@ -28,4 +28,1084 @@ fn threaded () {
     for handle in handles {
         handle.join().unwrap();
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn poisoned () {
+    use std::time::Instant;
+
+    let lazy = Lazy::new(|| -> u8 {
+        std::thread::sleep(Duration::from_millis(100));
+        panic!("initialization failed")
+    });
+
+    let start = Instant::now();
+    std::thread::scope(|s| {
+        let init = s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| *lazy.get())).collect::<Vec<_>>();
+        assert!(init.join().is_err());
+        for waiter in waiters {
+            assert!(waiter.join().is_err());
+        }
+    });
+
+    assert!(lazy.is_poisoned());
+    assert_eq!(lazy.try_get(), None);
+    drop(lazy);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn retry_policy () {
+    let attempts = AtomicUsize::new(0);
+    let lazy: Lazy<usize, _, Retry> = Lazy::with_policy(|| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        if attempt == 0 { panic!("first attempt failed") }
+        attempt
+    });
+
+    std::thread::scope(|s| {
+        let init = s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| *lazy.get())).collect::<Vec<_>>();
+        assert!(init.join().is_err());
+        for waiter in waiters {
+            assert_eq!(waiter.join().unwrap(), 1);
+        }
+    });
+
+    assert!(!lazy.is_poisoned());
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn abort_policy () {
+    const CHILD: &str = "LAIZY_ABORT_CHILD";
+
+    if std::env::var_os(CHILD).is_some() {
+        let lazy: Lazy<u8, _, Abort> = Lazy::with_policy(|| panic!("initialization failed"));
+        std::thread::scope(|s| {
+            s.spawn(|| *lazy.get());
+            s.spawn(|| *lazy.get());
+        });
+        unreachable!("the process should have aborted");
+    }
+
+    let status = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["abort_policy", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .status()
+        .unwrap();
+
+    assert!(!status.success());
+    #[cfg(unix)]
+    assert_eq!(std::os::unix::process::ExitStatusExt::signal(&status), Some(6));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn poison_payload () {
+    let mut lazy = Lazy::new(|| -> u8 {
+        std::thread::sleep(Duration::from_millis(50));
+        panic!("invalid configuration")
+    });
+
+    std::thread::scope(|s| {
+        let init = s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| *lazy.get())).collect::<Vec<_>>();
+        // the initializer's caller gets its own panic back, the waiters the cell's
+        let payload = init.join().unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"invalid configuration"));
+        for waiter in waiters {
+            let payload = waiter.join().unwrap_err();
+            let msg = payload.downcast_ref::<String>().unwrap();
+            assert!(msg.contains("poisoned: invalid configuration"), "{msg}");
+        }
+    });
+
+    let payload = lazy.poison_payload().unwrap();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"invalid configuration"));
+    assert!(lazy.take_poison_payload().is_some());
+    assert!(lazy.poison_payload().is_none());
+}
+
+#[test]
+fn slow_init () {
+    let lazy = Lazy::new(|| {
+        std::thread::sleep(Duration::from_millis(200));
+        vec![1u8, 2, 3]
+    });
+
+    std::thread::scope(|s| {
+        let handles = (0..16).map(|_| s.spawn(|| lazy.get().len())).collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 3);
+        }
+    });
+}
+
+#[test]
+fn prompt_wakeup () {
+    use std::time::Instant;
+
+    let done = Mutex::new(None);
+    let lazy = Lazy::new(|| {
+        std::thread::sleep(Duration::from_millis(100));
+        *done.lock().unwrap() = Some(Instant::now());
+    });
+
+    std::thread::scope(|s| {
+        s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| { lazy.get(); Instant::now() })).collect::<Vec<_>>();
+        for waiter in waiters {
+            let woken = waiter.join().unwrap();
+            assert!(woken.duration_since(done.lock().unwrap().unwrap()) < Duration::from_millis(50));
+        }
+    });
+}
+
+#[test]
+fn hot_reads () {
+    static HOT: Lazy<usize> = Lazy::new(|| 42);
+    let done = AtomicUsize::new(0);
+
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..10_000 {
+                    assert_eq!(*HOT, 42);
+                }
+                done.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+    });
+
+    assert_eq!(done.load(Ordering::Relaxed), 8);
+}
+
+#[test]
+fn consume_on_other_thread () {
+    type Boxed = Lazy<Vec<u8>, Box<dyn FnOnce() -> Vec<u8> + Send>>;
+
+    let (tx, rx) = std::sync::mpsc::channel::<Boxed>();
+    let consumer = std::thread::spawn(move || {
+        let init = rx.recv().unwrap().into_inner();
+        let uninit = rx.recv().unwrap().into_inner();
+        let try_init = rx.recv().unwrap().try_into_inner().ok().unwrap();
+        drop(rx.recv().unwrap());
+        vec![init, uninit, try_init]
+    });
+
+    std::thread::spawn(move || {
+        for i in 1u8..=4 {
+            let lazy: Boxed = Lazy::new(Box::new(move || vec![i]));
+            if i != 2 { lazy.get(); }
+            tx.send(lazy).unwrap();
+        }
+    }).join().unwrap();
+
+    assert_eq!(consumer.join().unwrap(), vec![vec![1], vec![2], vec![3]]);
+}
+
+#[test]
+fn overlapping_storage () {
+    use std::mem::size_of_val;
+
+    // both the closure and the value are 4 KiB, but only one of them is alive at a time
+    let big = [1u8; 4096];
+    let lazy = Lazy::new(move || big);
+    assert!(size_of_val(&lazy) < 4096 + 64);
+    assert_eq!(*lazy, big);
+
+    let lazy = Lazy::new(|| [2u8; 4096]);
+    assert!(size_of_val(&lazy) < 4096 + 64);
+    assert_eq!(lazy.into_inner(), [2u8; 4096]);
+
+    let lazy: Lazy<[u8; 4096], Box<dyn FnOnce() -> [u8; 4096]>> = Lazy::new(Box::new(move || big));
+    assert!(size_of_val(&lazy) < 4096 + 64);
+    assert_eq!(lazy.try_into_inner().err().map(|f| f()), Some(big));
+}
+
+#[test]
+fn padded () {
+    use std::mem::align_of;
+    use laizy::padded;
+
+    static PADDED: padded::Lazy<Mutex<Vec<u8>>> = padded::Lazy::new(|| Mutex::new(Vec::new()));
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "powerpc64"))]
+    assert_eq!(align_of::<padded::Lazy<u8>>(), 128);
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "powerpc64")))]
+    assert_eq!(align_of::<padded::Lazy<u8>>(), 64);
+    assert_eq!(std::mem::size_of::<padded::Lazy<u8>>(), align_of::<padded::Lazy<u8>>());
+
+    PADDED.lock().unwrap().push(1);
+    assert!(PADDED.is_initialized());
+    assert_eq!(*PADDED.get().lock().unwrap(), vec![1]);
+}
+
+#[test]
+fn get_paths () {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let lazy = Lazy::new(|| { CALLS.fetch_add(1, Ordering::Relaxed); 1u8 });
+    std::thread::scope(|s| {
+        for _ in 0..4 { s.spawn(|| assert_eq!(*lazy.get(), 1)); }
+    });
+    assert_eq!(*lazy.get(), 1);
+
+    let mut other = Lazy::new(|| { CALLS.fetch_add(1, Ordering::Relaxed); 2u8 });
+    *other.get_mut() += 1;
+    assert_eq!(*other.get_mut(), 3);
+    assert_eq!(*other.get(), 3);
+
+    let mut init = Lazy::<u8>::init(4);
+    assert_eq!(*init.get_mut(), 4);
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+}
+
+fn strategy_scenarios<S: WaitStrategy> () {
+    // many waiters on a slow initialization
+    let lazy: Lazy<Vec<u8>, _, Repanic, S> = Lazy::with_policy(|| {
+        std::thread::sleep(Duration::from_millis(50));
+        vec![1u8, 2, 3]
+    });
+    std::thread::scope(|s| {
+        let handles = (0..8).map(|_| s.spawn(|| lazy.get().len())).collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 3);
+        }
+    });
+
+    // waiters on a panicking initialization
+    let lazy: Lazy<u8, _, Repanic, S> = Lazy::with_policy(|| {
+        std::thread::sleep(Duration::from_millis(50));
+        panic!("initialization failed")
+    });
+    std::thread::scope(|s| {
+        let init = s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| *lazy.get())).collect::<Vec<_>>();
+        assert!(init.join().is_err());
+        for waiter in waiters {
+            assert!(waiter.join().is_err());
+        }
+    });
+    assert!(lazy.is_poisoned());
+
+    // waiters on a retried initialization
+    let attempts = AtomicUsize::new(0);
+    let lazy: Lazy<usize, _, Retry, S> = Lazy::with_policy(|| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        if attempt == 0 { panic!("first attempt failed") }
+        attempt
+    });
+    std::thread::scope(|s| {
+        let init = s.spawn(|| *lazy.get());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        let waiters = (0..4).map(|_| s.spawn(|| *lazy.get())).collect::<Vec<_>>();
+        assert!(init.join().is_err());
+        for waiter in waiters {
+            assert_eq!(waiter.join().unwrap(), 1);
+        }
+    });
+}
+
+#[test]
+fn spin_strategy () {
+    strategy_scenarios::<Spin>()
+}
+
+#[test]
+fn backoff_strategy () {
+    strategy_scenarios::<Backoff>()
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn park_strategy () {
+    strategy_scenarios::<laizy::strategy::Park>()
+}
+
+#[test]
+fn drop_on_other_thread () {
+    let value = std::sync::Arc::new(());
+    let lazy = Lazy::new({
+        let value = value.clone();
+        move || value
+    });
+
+    // initialized by some threads, moved into and dropped by another one
+    std::thread::scope(|s| {
+        for _ in 0..4 { s.spawn(|| { lazy.get(); }); }
+    });
+    std::thread::spawn(move || drop(lazy)).join().unwrap();
+
+    assert_eq!(std::sync::Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn closure_sizes () {
+    fn check<T: PartialEq + std::fmt::Debug + Send + Sync, F: FnOnce() -> T + Send> (f: F, expected: T) {
+        let lazy = Lazy::new(f);
+        std::thread::scope(|s| {
+            let handles = (0..4).map(|_| s.spawn(|| lazy.get() == &expected)).collect::<Vec<_>>();
+            assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+        });
+        assert_eq!(lazy.into_inner(), expected);
+    }
+
+    // zero-sized closures and values
+    check(|| (), ());
+    check(|| 7u8, 7);
+
+    // small and large captures
+    let small = 3u16;
+    check(move || small * 2, 6);
+    let large = [5u8; 8192];
+    check(move || large.iter().map(|&x| x as usize).sum::<usize>(), 5 * 8192);
+    check(move || large, [5u8; 8192]);
+
+    // panicking closures of every size still poison
+    let large = [5u8; 8192];
+    let lazy = Lazy::new(move || -> u8 { if large[0] == 5 { panic!("initialization failed") } 0 });
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *lazy.get())).is_err());
+    assert!(lazy.is_poisoned());
+}
+
+#[test]
+fn in_place () {
+    use std::mem::MaybeUninit;
+    use laizy::InPlace;
+
+    const LEN: usize = 1_000_000;
+    type Big = [f32; LEN];
+    type Fill = fn(&mut MaybeUninit<Big>);
+
+    fn fill (value: &mut MaybeUninit<Big>) {
+        let ptr = value.as_mut_ptr().cast::<f32>();
+        for i in 0..LEN { unsafe { ptr.add(i).write(i as f32) } }
+    }
+
+    static BIG: Lazy<Big, InPlace<Fill>> = unsafe { Lazy::new_in_place(fill as Fill) };
+
+    // the array is 4 MB, way bigger than the stack of this thread
+    let len = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(|| BIG.iter().filter(|&&x| x >= 0.0).count())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(len, LEN);
+    assert_eq!(BIG[LEN - 1], (LEN - 1) as f32);
+
+    // a panic while writing poisons the value instead of leaving it half-initialized
+    let lazy = unsafe {
+        Lazy::<Vec<u8>, _>::new_in_place(|value: &mut MaybeUninit<Vec<u8>>| {
+            value.write(vec![1, 2, 3]);
+            panic!("initialization failed")
+        })
+    };
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| lazy.len())).is_err());
+    assert!(lazy.is_poisoned());
+    assert!(lazy.try_get().is_none());
+}
+
+#[test]
+#[allow(deprecated)]
+fn state_queries () {
+    let lazy = Lazy::new(|| std::thread::sleep(Duration::from_millis(50)));
+    assert!(lazy.is_uninit() && !lazy.is_initializing() && !lazy.is_initialized());
+    assert!(!lazy.is_init() && !lazy.has_init());
+
+    std::thread::scope(|s| {
+        s.spawn(|| *lazy);
+        while lazy.is_uninit() { core::hint::spin_loop() }
+
+        // `is_init` means initializing, not initialized
+        assert!(lazy.is_initializing() && !lazy.is_initialized());
+        assert!(lazy.is_init() && !lazy.has_init());
+    });
+
+    assert!(!lazy.is_uninit() && !lazy.is_initializing() && lazy.is_initialized());
+    assert!(!lazy.is_init() && lazy.has_init());
+}
+
+#[test]
+fn copy_lazy () {
+    use std::mem::{size_of, needs_drop};
+    use laizy::CopyLazy;
+
+    assert_eq!(size_of::<CopyLazy<u64>>(), size_of::<Lazy<u64>>());
+    assert!(!needs_drop::<CopyLazy<u64>>());
+    assert!(needs_drop::<Lazy<u64>>());
+
+    static COPY: CopyLazy<u64> = CopyLazy::new(|| 42);
+    std::thread::scope(|s| {
+        for _ in 0..4 { s.spawn(|| assert_eq!(*COPY.get(), 42)); }
+    });
+
+    let mut lazy = CopyLazy::<u8>::new(|| 1);
+    **lazy += 1;
+    assert_eq!(lazy.into_lazy().into_inner(), 2);
+
+    // values without drop glue still run the initializer only once
+    let calls = AtomicUsize::new(0);
+    let lazy = Lazy::new(|| calls.fetch_add(1, Ordering::SeqCst) as u64);
+    assert_eq!((*lazy, *lazy), (0, 0));
+    drop(lazy);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+#[test]
+fn deadlock_detection () {
+    static A: Lazy<u8> = Lazy::new(|| { std::thread::sleep(Duration::from_millis(50)); *B + 1 });
+    static B: Lazy<u8> = Lazy::new(|| { std::thread::sleep(Duration::from_millis(50)); *A + 1 });
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    for lazy in [&A, &B] {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| **lazy));
+            tx.send(result.map_err(|e| laizy_message(&*e))).unwrap();
+        });
+    }
+
+    let results = (0..2).map(|_| rx.recv_timeout(Duration::from_secs(5)).expect("the cycle wasn't detected")).collect::<Vec<_>>();
+    assert!(results.iter().all(Result::is_err));
+    assert!(results.iter().any(|result| result.as_ref().unwrap_err().contains("deadlock")));
+
+    // reentrant initializations are cycles too
+    static C: Lazy<u8> = Lazy::new(|| *C);
+    let msg = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *C)).map_err(|e| laizy_message(&*e)).unwrap_err();
+    assert!(msg.contains("(this thread)"));
+}
+
+#[cfg(all(feature = "std", debug_assertions))]
+fn laizy_message (payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<String>().cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|msg| msg.to_string()))
+        .unwrap_or_default()
+}
+
+#[test]
+fn default_strategy () {
+    use std::any::TypeId;
+    use laizy::strategy::DefaultStrategy;
+
+    #[cfg(feature = "std")]
+    assert_eq!(TypeId::of::<DefaultStrategy>(), TypeId::of::<laizy::strategy::Park>());
+    #[cfg(all(not(feature = "std"), feature = "cortex-m-wfe"))]
+    assert_eq!(TypeId::of::<DefaultStrategy>(), TypeId::of::<laizy::strategy::Wfe>());
+    #[cfg(not(any(feature = "std", feature = "cortex-m-wfe")))]
+    assert_eq!(TypeId::of::<DefaultStrategy>(), TypeId::of::<Backoff>());
+}
+
+#[cfg(feature = "cortex-m-wfe")]
+#[test]
+fn wfe_strategy () {
+    strategy_scenarios::<laizy::strategy::Wfe>()
+}
+
+#[test]
+fn try_lazy () {
+    use laizy::TryLazy;
+
+    // success
+    let lazy = TryLazy::<u8, String, _>::new(|| Ok(1));
+    assert_eq!(lazy.try_get(), None);
+    assert_eq!(lazy.get(), Ok(&1));
+    assert!(lazy.is_initialized());
+    assert_eq!(lazy.into_inner(), Ok(1));
+
+    // the failure is cached, and observed by every thread, including those waiting on it
+    let calls = AtomicUsize::new(0);
+    let lazy = TryLazy::<u8, String, _>::new(|| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(50));
+        Err(String::from("bad config"))
+    });
+
+    std::thread::scope(|s| {
+        let handles = (0..4).map(|_| s.spawn(|| lazy.get().copied().map_err(String::clone))).collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Err(String::from("bad config")));
+        }
+    });
+    assert!(lazy.is_failed());
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    // reset runs a new initializer, even after a failure
+    let mut lazy = TryLazy::<u8, &str, Box<dyn FnOnce() -> Result<u8, &'static str>>>::new(Box::new(|| Err("failed")));
+    assert_eq!(lazy.get(), Err(&"failed"));
+    lazy.reset(Box::new(|| Ok(2)));
+    assert!(lazy.is_uninit());
+    assert_eq!(lazy.get(), Ok(&2));
+
+    // and after a panic
+    lazy.reset(Box::new(|| panic!("initialization failed")));
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| { let _ = lazy.get(); })).is_err());
+    assert!(lazy.is_poisoned());
+    assert_eq!(format!("{lazy:?}"), "TryLazy(<poisoned>)");
+    lazy.reset(Box::new(|| Ok(3)));
+    assert_eq!(lazy.into_inner(), Ok(3));
+}
+
+#[test]
+fn try_lazy_policies () {
+    use laizy::{TryLazy, policy::RetryErrors};
+
+    // fails twice, then succeeds, asserting that only one attempt is in flight at a time
+    static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+    fn flaky (calls: &AtomicUsize) -> impl FnMut() -> Result<usize, usize> + '_ {
+        move || {
+            assert_eq!(IN_FLIGHT.fetch_add(1, Ordering::SeqCst), 0);
+            let attempt = calls.fetch_add(1, Ordering::Relaxed) + 1;
+            std::thread::sleep(Duration::from_millis(20));
+            IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+            match attempt {
+                1 | 2 => Err(attempt),
+                _ => Ok(attempt)
+            }
+        }
+    }
+
+    // the first error is cached for every thread
+    let calls = AtomicUsize::new(0);
+    let cached = TryLazy::new_cached(flaky(&calls));
+    std::thread::scope(|s| {
+        let handles = (0..4).map(|_| s.spawn(|| cached.get().copied().map_err(|e| *e))).collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Err(1));
+        }
+    });
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    // the attempts run one at a time, each error reaching the thread that ran it, until one succeeds
+    let calls = AtomicUsize::new(0);
+    let retrying: TryLazy<usize, usize, _, RetryErrors> = TryLazy::new_retrying(flaky(&calls));
+    let results = std::thread::scope(|s| {
+        let handles = (0..4).map(|_| s.spawn(|| {
+            let mut errors = Vec::new();
+            loop {
+                match retrying.get() {
+                    Ok(value) => return (*value, errors),
+                    Err(e) => errors.push(e)
+                }
+            }
+        })).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert!(results.iter().all(|(value, _)| *value == 3));
+    let mut errors = results.into_iter().flat_map(|(_, errors)| errors).collect::<Vec<_>>();
+    errors.sort();
+    assert_eq!(errors, [1, 2]);
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+    assert_eq!(retrying.into_inner(), Ok(3));
+}
+
+#[test]
+fn once_cell () {
+    use laizy::OnceCell;
+
+    // racing `set` against `get_or_init`: exactly one of them wins, and every thread sees its value
+    for _ in 0..32 {
+        let cell = OnceCell::new();
+        let calls = AtomicUsize::new(0);
+        let (set, values) = std::thread::scope(|s| {
+            let setter = s.spawn(|| cell.set(1).is_ok());
+            let getters = (0..4).map(|_| s.spawn(|| *cell.get_or_init(|| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                std::thread::sleep(Duration::from_millis(1));
+                2
+            }))).collect::<Vec<_>>();
+            let waiter = s.spawn(|| *cell.wait());
+
+            let values = getters.into_iter().chain([waiter]).map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+            (setter.join().unwrap(), values)
+        });
+
+        let expected = if set { 1 } else { 2 };
+        assert!(values.iter().all(|&value| value == expected));
+        assert_eq!(calls.load(Ordering::Relaxed), if set { 0 } else { 1 });
+        assert_eq!(cell.get(), Some(&expected));
+        assert_eq!(cell.set(3), Err(3));
+    }
+
+    // errors reach the thread whose function failed, and leave the cell for the next one
+    let cell = OnceCell::<usize>::new();
+    let calls = AtomicUsize::new(0);
+    let results = std::thread::scope(|s| {
+        let handles = (0..4).map(|_| s.spawn(|| cell.get_or_try_init(|| match calls.fetch_add(1, Ordering::Relaxed) {
+            0 | 1 => Err("not yet"),
+            n => Ok(n)
+        }).copied())).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert_eq!(results.iter().filter(|result| result.is_err()).count(), 2);
+    assert!(results.iter().flatten().all(|&value| value == 2));
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+    // a panicking function leaves it uninitialized as well
+    let mut cell = OnceCell::new();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.get_or_init(|| -> String { panic!("first attempt") }))).is_err());
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| String::from("second")), "second");
+    assert_eq!(cell.take().as_deref(), Some("second"));
+    assert_eq!(cell.take(), None);
+    assert_eq!(OnceCell::from(5).into_inner(), Some(5));
+}
+
+#[test]
+fn once () {
+    use laizy::Once;
+
+    // exactly one of the racing functions runs, and every caller returns after it's done
+    let once = Once::new();
+    let calls = AtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                once.call_once(|| {
+                    std::thread::sleep(Duration::from_millis(10));
+                    calls.fetch_add(1, Ordering::Relaxed);
+                });
+                assert!(once.is_completed());
+                assert_eq!(calls.load(Ordering::Relaxed), 1);
+            });
+        }
+    });
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    // a panicking function poisons it, until `call_once_force` completes
+    let once = Once::new();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once(|| panic!("first attempt")))).is_err());
+    assert!(!once.is_completed());
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once(|| unreachable!()))).is_err());
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once_force(|state| {
+        assert!(state.is_poisoned());
+        panic!("first attempt")
+    }))).is_err());
+
+    once.call_once_force(|state| assert!(state.is_poisoned()));
+    assert!(once.is_completed());
+    once.call_once(|| unreachable!());
+    once.call_once_force(|_| unreachable!());
+
+    let once = Once::new();
+    once.call_once_force(|state| assert!(!state.is_poisoned()));
+    assert!(once.is_completed());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn once_force_while_poisoned () {
+    use laizy::Once;
+
+    // callers that find it poisoned keep reporting the first panic, while forced calls race to run again
+    for _ in 0..16 {
+        let once = Once::new();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once(|| panic!("first attempt")))).is_err());
+
+        let forced = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for i in 0..8 {
+                let (once, forced) = (&once, &forced);
+                s.spawn(move || match i % 2 {
+                    0 => if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once(|| unreachable!()))) {
+                        let msg = payload.downcast_ref::<String>().unwrap();
+                        assert!(msg.contains("first attempt"), "{msg}");
+                    },
+                    _ => {
+                        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| once.call_once_force(|state| {
+                            assert!(state.is_poisoned());
+                            if forced.fetch_add(1, Ordering::Relaxed) == 0 {
+                                panic!("second attempt")
+                            }
+                        })));
+                    }
+                });
+            }
+        });
+
+        assert!(once.is_completed());
+        assert_eq!(forced.load(Ordering::Relaxed), 2);
+    }
+}
+
+#[test]
+fn unsync_once_cell () {
+    use laizy::unsync::OnceCell;
+
+    let mut cell = OnceCell::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_mut(), None);
+    assert_eq!(cell.get_or_try_init(|| Err("not yet")), Err("not yet"));
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Ok::<_, &str>(1)), Ok(&1));
+    assert_eq!(cell.get_or_init(|| unreachable!()), &1);
+    assert_eq!(cell.set(2), Err(2));
+
+    *cell.get_mut().unwrap() += 1;
+    assert_eq!(cell.take(), Some(2));
+    assert_eq!(cell.take(), None);
+    assert_eq!(cell.set(3), Ok(()));
+    assert_eq!(cell.into_inner(), Some(3));
+    assert_eq!(OnceCell::<u8>::new().into_inner(), None);
+
+    // a panicking function leaves it uninitialized
+    let cell = OnceCell::new();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cell.get_or_init(|| -> u8 { panic!("first attempt") }))).is_err());
+    assert_eq!(cell.get_or_init(|| 4), &4);
+
+    // initializing it from within its own initialization function panics, and leaves it uninitialized
+    let cell = OnceCell::new();
+    let reentrant = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *cell.get_or_init(|| *cell.get_or_init(|| 5)))).unwrap_err();
+    assert_eq!(reentrant.downcast_ref::<&str>(), Some(&"OnceCell initialized from within its own initialization function"));
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| 6), &6);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn lazy_race () {
+    use std::sync::Arc;
+    use laizy::race::{LazyRace, OnceRace};
+
+    // every racer may compute its value, but a single one is stored, and all of the others are dropped
+    for _ in 0..32 {
+        let token = Arc::new(());
+        let calls = AtomicUsize::new(0);
+        let lazy = LazyRace::new(|| (calls.fetch_add(1, Ordering::Relaxed), token.clone()));
+
+        let winners = std::thread::scope(|s| {
+            let handles = (0..8).map(|_| s.spawn(|| lazy.get().0)).collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+        });
+
+        assert!(winners.iter().all(|&winner| winner == winners[0]));
+        assert!((1..=8).contains(&calls.load(Ordering::Relaxed)));
+        assert_eq!(Arc::strong_count(&token), 2);
+        drop(lazy);
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+
+    let mut cell = OnceRace::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Err("not yet")), Err("not yet"));
+    assert_eq!(cell.set(1), Ok(()));
+    assert_eq!(cell.set(2), Err(2));
+    assert_eq!(cell.get_or_init(|| unreachable!()), &1);
+    *cell.get_mut().unwrap() += 1;
+    assert_eq!(cell.take(), Some(2));
+    assert_eq!(cell.into_inner(), None);
+}
+
+#[test]
+fn once_non_zero_usize () {
+    use std::num::NonZeroUsize;
+    use laizy::race::{OnceNonZeroUsize, OnceBool};
+
+    // the stored integer publishes whatever the winner wrote before storing it (here, the slot it indexes)
+    for _ in 0..32 {
+        let slots = [(); 8].map(|_| AtomicUsize::new(0));
+        let cell = OnceNonZeroUsize::new();
+        let ids = std::thread::scope(|s| {
+            let handles = (1..=8).map(|id| {
+                let (slots, cell) = (&slots, &cell);
+                s.spawn(move || {
+                    let id = cell.get_or_init(|| {
+                        slots[id - 1].store(id * 10, Ordering::Relaxed);
+                        NonZeroUsize::new(id).unwrap()
+                    });
+                    assert_eq!(slots[id.get() - 1].load(Ordering::Relaxed), id.get() * 10);
+                    id
+                })
+            }).collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+        });
+
+        assert!(ids.iter().all(|&id| Some(id) == cell.get()));
+        assert_eq!(cell.set(NonZeroUsize::new(9).unwrap()), Err(NonZeroUsize::new(9).unwrap()));
+    }
+
+    let cell = OnceNonZeroUsize::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Err("not yet")), Err("not yet"));
+    assert_eq!(cell.get(), None);
+
+    let flag = OnceBool::new();
+    assert_eq!(flag.get(), None);
+    assert!(!flag.get_or_init(|| false));
+    assert!(!flag.get_or_init(|| true));
+    assert_eq!(flag.set(true), Err(true));
+
+    let flag = OnceBool::new();
+    assert_eq!(flag.set(true), Ok(()));
+    assert_eq!(flag.get(), Some(true));
+    assert_eq!(flag.get_or_try_init(|| Err::<bool, _>("unreachable")), Ok(true));
+}
+
+#[test]
+fn once_ref () {
+    use laizy::race::OnceRef;
+
+    static VALUES: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 7];
+    let cell = OnceRef::new();
+    let winners = std::thread::scope(|s| {
+        let handles = (0..8).map(|i| {
+            let cell = &cell;
+            s.spawn(move || cell.get_or_init(|| &VALUES[i]))
+        }).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert!(winners.iter().all(|&winner| std::ptr::eq(winner, cell.get().unwrap())));
+    assert!(cell.set(&VALUES[0]).is_err());
+    assert_eq!(OnceRef::<usize>::new().get_or_try_init(|| Err("not yet")), Err("not yet"));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn once_box () {
+    use std::sync::Arc;
+    use laizy::race::OnceBox;
+
+    // losers deallocate their boxes, and dropping the cell frees the winner's
+    for _ in 0..32 {
+        let token = Arc::new(());
+        let cell = OnceBox::new();
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| assert!(Arc::ptr_eq(cell.get_or_init(|| Box::new(token.clone())), cell.get().unwrap())));
+            }
+        });
+
+        assert_eq!(Arc::strong_count(&token), 2);
+        drop(cell);
+        assert_eq!(Arc::strong_count(&token), 1);
+    }
+
+    let mut cell = OnceBox::new();
+    assert_eq!(cell.set(Box::new(1)), Ok(()));
+    assert_eq!(cell.set(Box::new(2)), Err(Box::new(2)));
+    *cell.get_mut().unwrap() += 1;
+    assert_eq!(cell.take(), Some(Box::new(2)));
+    assert_eq!(cell.get_or_try_init(|| Err("not yet")), Err("not yet"));
+    assert_eq!(cell.into_inner(), None);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn lazy_arc () {
+    use laizy::LazyArc;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let lazy = LazyArc::new(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        std::thread::sleep(Duration::from_millis(10));
+        vec![1u8, 2, 3]
+    });
+
+    // every clone shares the same cell, initialized once
+    let handles = (0..8).map(|_| {
+        let lazy = lazy.clone();
+        std::thread::spawn(move || lazy.get().len())
+    }).collect::<Vec<_>>();
+    assert!(handles.into_iter().all(|handle| handle.join().unwrap() == 3));
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    // shared handles are handed back, and the last one recovers the value
+    let other = lazy.clone();
+    assert!(lazy.ptr_eq(&other));
+    assert_eq!(lazy.handle_count(), 2);
+    let lazy = lazy.try_unwrap().unwrap_err();
+    drop(other);
+    assert_eq!(lazy.try_unwrap().ok(), Some(vec![1, 2, 3]));
+
+    // a unique handle to an uninitialized cell initializes it
+    let lazy = LazyArc::new(|| 4);
+    assert!(lazy.is_uninit());
+    assert_eq!(lazy.try_unwrap().ok(), Some(4));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn weak_lazy () {
+    use std::sync::{Arc, Barrier};
+    use laizy::LazyArc;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static TOKEN: Mutex<Option<Arc<()>>> = Mutex::new(None);
+    fn make () -> Arc<()> {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        TOKEN.lock().unwrap().get_or_insert_with(Arc::default).clone()
+    }
+
+    let lazy = LazyArc::<Arc<()>>::new(make);
+    let weak = lazy.downgrade();
+    assert!(weak.upgrade().unwrap().ptr_eq(&lazy));
+    lazy.get();
+    drop(lazy);
+    assert!(weak.upgrade().is_none());
+
+    // the value was dropped alongside its last strong handle
+    let token = TOKEN.lock().unwrap().take().unwrap();
+    assert_eq!(Arc::strong_count(&token), 1);
+
+    // concurrent upgraders share a single recreation
+    let barrier = Barrier::new(8);
+    let handles = std::thread::scope(|s| {
+        let handles = (0..8).map(|_| {
+            let (weak, barrier) = (weak.clone(), &barrier);
+            s.spawn(move || {
+                barrier.wait();
+                let lazy = weak.get_or_recreate(make);
+                lazy.get();
+                lazy
+            })
+        }).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+    assert!(handles.iter().all(|lazy| lazy.ptr_eq(&handles[0])));
+    assert!(weak.upgrade().unwrap().ptr_eq(&handles[0]));
+    drop(handles);
+    assert!(weak.upgrade().is_none());
+
+    let token = TOKEN.lock().unwrap().take().unwrap();
+    assert_eq!(Arc::strong_count(&token), 1);
+
+    // so do upgraders through weak handles downgraded separately (from different strong handles)
+    let lazy = LazyArc::<Arc<()>>::new(make);
+    let weaks = [lazy.downgrade(), lazy.clone().downgrade()];
+    drop(lazy);
+    let handles = std::thread::scope(|s| {
+        let handles = (0..8).map(|i| {
+            let (weak, barrier) = (&weaks[i % 2], &barrier);
+            s.spawn(move || {
+                barrier.wait();
+                let lazy = weak.get_or_recreate(make);
+                lazy.get();
+                lazy
+            })
+        }).collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+    assert!(handles.iter().all(|lazy| lazy.ptr_eq(&handles[0])));
+    assert!(weaks.iter().all(|weak| weak.upgrade().unwrap().ptr_eq(&handles[0])));
+
+    // and weak handles downgraded from the recreated cell follow it as well
+    let weak = handles[0].downgrade();
+    drop(handles);
+    assert!(weak.get_or_recreate(make).ptr_eq(&weaks[0].upgrade().unwrap()));
+    assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn boxed_lazy () {
+    use std::{rc::Rc, sync::Arc};
+    use laizy::{BoxedLazy, LocalBoxedLazy};
+
+    fn send_sync<T: Send + Sync> (_: &T) {}
+
+    struct Config {
+        name: String,
+        greeting: BoxedLazy<String>
+    }
+
+    impl Config {
+        fn new (name: &str) -> Self {
+            let owned = name.to_string();
+            Self { name: name.to_string(), greeting: Lazy::boxed(move || format!("hello, {owned}")) }
+        }
+    }
+
+    let config = Arc::new(Config::new("laizy"));
+    send_sync(&*config);
+    let handles = (0..4).map(|_| {
+        let config = config.clone();
+        std::thread::spawn(move || config.greeting.get().len())
+    }).collect::<Vec<_>>();
+    assert!(handles.into_iter().all(|handle| handle.join().unwrap() == 12));
+    assert_eq!(config.greeting.get(), "hello, laizy");
+    assert_eq!(config.name, "laizy");
+
+    let shared = Rc::new(5);
+    let local: LocalBoxedLazy<i32> = Lazy::boxed_local({
+        let shared = shared.clone();
+        move || *shared * 2
+    });
+    assert_eq!(*local, 10);
+    drop(local);
+    assert_eq!(Rc::strong_count(&shared), 1);
+}
+
+#[test]
+fn lazy_future () {
+    use std::task::Poll;
+
+    let calls = AtomicUsize::new(0);
+    let mut lazy = Lazy::new(|| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        let mut polls = 0;
+        futures::future::poll_fn(move |cx| {
+            polls += 1;
+            match polls {
+                3 => Poll::Ready(polls),
+                _ => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        })
+    });
+
+    // the future is only built once it's first polled
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+    futures::executor::block_on(async {
+        assert_eq!(futures::poll!(&mut lazy), Poll::Pending);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        assert_eq!((&mut lazy).await, 3);
+    });
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn into_lazy () {
+    use laizy::{IntoLazy, ByValue, ByFn};
+
+    fn takes (l: impl IntoLazy<Item = String>) -> (bool, String) {
+        let lazy = l.into_lazy();
+        (lazy.is_initialized(), lazy.into_inner())
+    }
+
+    let calls = AtomicUsize::new(0);
+    assert_eq!(takes(ByValue(String::from("value"))), (true, String::from("value")));
+    assert_eq!(takes(ByFn(|| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        String::from("fn")
+    })), (false, String::from("fn")));
+    assert_eq!(takes(Lazy::from_fn(|| String::from("lazy"))), (false, String::from("lazy")));
+    assert_eq!(takes(Lazy::<String>::from(String::from("from"))), (true, String::from("from")));
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,238 @@
+//! Small, single-purpose scenarios meant to be run under Miri (`make miri`), covering every state of the cell
+
+use std::{sync::Arc, panic::AssertUnwindSafe};
+use laizy::{Lazy, policy::Retry};
+
+#[test]
+fn get () {
+    let lazy = Lazy::new(|| vec![1u8, 2, 3]);
+    assert_eq!(lazy.get(), &[1, 2, 3]);
+    assert_eq!(lazy.get(), &[1, 2, 3]);
+}
+
+#[test]
+fn get_threaded () {
+    let lazy = Lazy::new(|| vec![1u8, 2, 3]);
+    std::thread::scope(|s| {
+        for _ in 0..2 { s.spawn(|| assert_eq!(lazy.get().len(), 3)); }
+    });
+}
+
+#[test]
+fn get_fast_path () {
+    // readers that find it initialized take the fast path, while the others race to initialize it
+    let lazy = Lazy::new(|| vec![1u8, 2, 3]);
+    std::thread::scope(|s| {
+        for _ in 0..2 { s.spawn(|| assert_eq!(lazy.get().len(), 3)); }
+        s.spawn(|| while lazy.try_get().is_none() { std::hint::spin_loop() });
+        s.spawn(|| assert_eq!(lazy.get().len(), 3));
+    });
+}
+
+#[test]
+fn get_mut () {
+    let mut lazy = Lazy::new(|| vec![1u8]);
+    lazy.get_mut().push(2);
+    assert_eq!(lazy.get(), &[1, 2]);
+}
+
+#[test]
+fn into_inner () {
+    assert_eq!(Lazy::new(|| vec![1u8]).into_inner(), [1]);
+
+    let lazy = Lazy::new(|| vec![1u8]);
+    lazy.get();
+    assert_eq!(lazy.into_inner(), [1]);
+}
+
+#[test]
+fn try_into_inner () {
+    let lazy = Lazy::new(|| vec![1u8]);
+    let f = lazy.try_into_inner().unwrap_err();
+    assert_eq!(f(), [1]);
+
+    let lazy = Lazy::new(|| vec![1u8]);
+    lazy.get();
+    assert_eq!(lazy.try_into_inner().ok(), Some(vec![1]));
+}
+
+#[test]
+fn poisoned () {
+    let lazy = Lazy::new(|| -> Vec<u8> { panic!("initialization failed") });
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| lazy.get().len())).is_err());
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| lazy.get().len())).is_err());
+    assert!(lazy.is_poisoned());
+    drop(lazy);
+}
+
+/// Neither the value nor the initializer need dropping, so dropping takes the fast exit, but for the poisoned cell
+/// (whose payload would be leaked otherwise)
+#[cfg(feature = "std")]
+#[test]
+fn drop_trivial () {
+    let init = Lazy::<u8>::new(|| 1);
+    assert_eq!(*init, 1);
+    let poisoned = Lazy::<u8>::new(|| panic!("initialization failed"));
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| *poisoned)).is_err());
+    assert!(poisoned.poison_payload().is_some());
+    drop((Lazy::<u8>::new(|| 1), init, poisoned));
+}
+
+#[test]
+fn retried () {
+    let attempts = std::cell::Cell::new(0);
+    let lazy: Lazy<Vec<u8>, _, Retry> = Lazy::with_policy(|| {
+        attempts.set(attempts.get() + 1);
+        if attempts.get() == 1 { panic!("first attempt failed") }
+        vec![attempts.get()]
+    });
+
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| lazy.get().len())).is_err());
+    assert_eq!(lazy.get(), &[2]);
+}
+
+#[test]
+fn drop_uninit () {
+    let value = Arc::new(());
+    let captured = value.clone();
+    drop(Lazy::<Arc<()>, _>::new(move || captured));
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[test]
+fn drop_init () {
+    let value = Arc::new(());
+    let lazy = Lazy::new({
+        let value = value.clone();
+        move || value
+    });
+    lazy.get();
+    drop(lazy);
+    assert_eq!(Arc::strong_count(&value), 1);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn async_take () {
+    let mut lazy = laizy::async_lazy(async { vec![1u8] });
+    futures::executor::block_on(lazy.get());
+    assert_eq!(lazy.take(), Some(vec![1]));
+    assert_eq!(lazy.take(), None);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn async_get_mut () {
+    let mut lazy = laizy::async_lazy(async { vec![1u8] });
+    futures::executor::block_on(lazy.get_mut()).push(2);
+    futures::executor::block_on(lazy.get_mut()).push(3);
+    assert_eq!(lazy.try_get_mut().map(|value| value.len()), Some(3));
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn async_get_in_place () {
+    let raw = Box::into_raw(Box::new(laizy::async_lazy(async {
+        // keeps a reference into itself across the await, so moving it while pinned would be caught
+        let value = [1u8, 2, 3];
+        let first = &value[0];
+        futures::future::ready(()).await;
+        value.len() + *first as usize
+    })));
+    let lazy: &'static _ = unsafe { &*raw };
+
+    assert_eq!(*futures::executor::block_on(lazy.get_in_place()), 4);
+    assert_eq!(*futures::executor::block_on(lazy.get()), 4);
+
+    // so that Miri doesn't report it as leaked
+    drop(unsafe { Box::from_raw(raw) });
+}
+
+#[cfg(all(feature = "futures", feature = "alloc"))]
+#[test]
+fn async_suspended () {
+    fn yield_once () -> impl std::future::Future<Output = ()> {
+        let mut yielded = false;
+        futures::future::poll_fn(move |cx| match std::mem::replace(&mut yielded, true) {
+            true => std::task::Poll::Ready(()),
+            false => {
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        })
+    }
+
+    // handed back to the cell (boxed, with a reference into itself), and resumed in another `get` future
+    let raw = Box::into_raw(Box::new(laizy::async_lazy(async {
+        let value = [1u8, 2, 3];
+        let first = &value[0];
+        yield_once().await;
+        value.len() + *first as usize
+    })));
+    let lazy: &'static _ = unsafe { &*raw };
+    assert!(futures::FutureExt::now_or_never(lazy.get()).is_none());
+    assert_eq!(*futures::executor::block_on(lazy.get_in_place()), 4);
+    drop(unsafe { Box::from_raw(raw) });
+
+    // dropped along with the cell
+    let lazy = laizy::async_lazy(async {
+        futures::future::pending::<()>().await;
+        vec![1u8]
+    });
+    assert!(futures::FutureExt::now_or_never(lazy.get()).is_none());
+    drop(lazy);
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn async_get_unchecked () {
+    let mut lazy = laizy::async_lazy(async { vec![1u8] });
+    futures::executor::block_on(lazy.get());
+    assert!(lazy.is_initialized());
+
+    unsafe {
+        lazy.get_mut_unchecked().push(2);
+        assert_eq!(lazy.get_unchecked(), &[1, 2]);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn race_losers () {
+    let lazy = laizy::race::LazyRace::new(|| vec![1u8, 2, 3]);
+    std::thread::scope(|s| {
+        for _ in 0..2 { s.spawn(|| assert_eq!(lazy.get().len(), 3)); }
+    });
+    assert_eq!(lazy.into_inner(), [1, 2, 3]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn once_box_losers () {
+    let cell = laizy::race::OnceBox::new();
+    std::thread::scope(|s| {
+        for _ in 0..2 { s.spawn(|| assert_eq!(cell.get_or_init(|| Box::new(vec![1u8, 2, 3])).len(), 3)); }
+    });
+    assert_eq!(cell.get().map(Vec::len), Some(3));
+}
+
+#[test]
+fn once_ref_published () {
+    let values = [vec![1u8], vec![2u8]];
+    let cell = laizy::race::OnceRef::new();
+    std::thread::scope(|s| {
+        for value in &values { s.spawn(|| assert_eq!(cell.get_or_init(|| value).len(), 1)); }
+    });
+    assert!(cell.get().is_some_and(|value| values.contains(value)));
+}
+
+#[test]
+fn once_force_poisoned () {
+    let once = laizy::Once::new();
+    let _ = std::panic::catch_unwind(AssertUnwindSafe(|| once.call_once(|| panic!("poisoned"))));
+    std::thread::scope(|s| {
+        s.spawn(|| { let _ = std::panic::catch_unwind(AssertUnwindSafe(|| once.call_once(|| unreachable!()))); });
+        s.spawn(|| once.call_once_force(|state| assert!(state.is_poisoned())));
+    });
+    assert!(once.is_completed());
+}
@@ -0,0 +1,18 @@
+#[test]
+fn ui () {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+
+    #[cfg(feature = "alloc")]
+    {
+        t.compile_fail("tests/ui/alloc/*.rs");
+        t.pass("tests/ui/alloc/pass/*.rs");
+    }
+
+    #[cfg(feature = "futures")]
+    {
+        t.compile_fail("tests/ui/asnc/*.rs");
+        t.pass("tests/ui/asnc/pass/*.rs");
+    }
+}
@@ -0,0 +1,9 @@
+use laizy::{Lazy, LocalBoxedLazy};
+
+fn assert_sync<T: Sync> (_: &T) {}
+
+fn main () {
+    // the boxed initializer isn't `Send`, so neither is the cell `Sync`
+    let lazy: LocalBoxedLazy<u8> = Lazy::boxed_local(|| 0);
+    assert_sync(&lazy);
+}
@@ -0,0 +1,16 @@
+use laizy::{Lazy, BoxedLazy};
+
+fn assert_send_sync<T: Send + Sync> () {}
+
+struct Cache {
+    name: BoxedLazy<String>
+}
+
+fn main () {
+    assert_send_sync::<BoxedLazy<u8>>();
+    assert_send_sync::<Cache>();
+
+    let prefix = String::from("cache");
+    let cache = Cache { name: Lazy::boxed(move || format!("{prefix}-0")) };
+    assert_eq!(cache.name.get(), "cache-0");
+}
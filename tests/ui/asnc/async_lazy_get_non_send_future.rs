@@ -0,0 +1,14 @@
+use std::rc::Rc;
+use laizy::async_lazy;
+
+fn assert_send<T: Send> (_: &T) {}
+
+fn main () {
+    // the future holds an `Rc` across an await point, so neither it nor `get`'s future are `Send`
+    let lazy = async_lazy(async {
+        let rc = Rc::new(1u8);
+        futures::future::ready(()).await;
+        *rc
+    });
+    assert_send(&lazy.get());
+}
@@ -0,0 +1,10 @@
+use std::rc::Rc;
+use laizy::async_lazy;
+
+fn assert_send<T: Send> (_: &T) {}
+
+fn main () {
+    // the value isn't `Send`, so the future that hands it out isn't either
+    let lazy = async_lazy(async { Rc::new(1u8) });
+    assert_send(&lazy.into_inner());
+}
@@ -0,0 +1,12 @@
+use std::future::Ready;
+use laizy::AsyncLazy;
+
+// `AsyncLazy` is invariant over `T`, so it can't be shortened like a `&'static str` could
+fn shorten<'a> (lazy: AsyncLazy<&'static str, Ready<&'static str>>) -> AsyncLazy<&'a str, Ready<&'static str>> {
+    lazy
+}
+
+fn main () {
+    let lazy = laizy::async_lazy(std::future::ready("hello"));
+    drop(shorten(lazy));
+}
@@ -0,0 +1,14 @@
+use std::rc::Rc;
+use laizy::async_lazy;
+
+fn assert_sync<T: Sync> (_: &T) {}
+
+fn main () {
+    // the future holds an `Rc` across an await point, so it isn't `Send`
+    let lazy = async_lazy(async {
+        let rc = Rc::new(1u8);
+        futures::future::ready(()).await;
+        *rc
+    });
+    assert_sync(&lazy);
+}
@@ -0,0 +1,15 @@
+use std::rc::Rc;
+use laizy::AsyncLazy;
+
+fn assert_sync<T: Sync> (_: &T) {}
+
+fn main () {
+    // the initializer is `Send`, but the future it converts into isn't, and a dropped `get` future may hand it back to
+    // the cell for another thread to resume
+    let lazy = AsyncLazy::<u8, _>::from_fn(|| async {
+        let rc = Rc::new(1u8);
+        futures::future::ready(()).await;
+        *rc
+    });
+    assert_sync(&lazy);
+}
@@ -0,0 +1,9 @@
+use std::rc::Rc;
+use laizy::async_lazy;
+
+fn assert_sync<T: Sync> (_: &T) {}
+
+fn main () {
+    let lazy = async_lazy(async { Rc::new(1u8) });
+    assert_sync(&lazy);
+}
@@ -0,0 +1,24 @@
+use std::{future::Future, pin::Pin, task::{Context, Poll}};
+use laizy::AsyncLazy;
+
+// doesn't implement `Debug`
+struct Opaque;
+
+impl Future for Opaque {
+    type Output = u8;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u8> {
+        Poll::Ready(1)
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Holder {
+    lazy: AsyncLazy<u8, Opaque>
+}
+
+fn main () {
+    let holder = Holder { lazy: AsyncLazy::new(Opaque) };
+    assert_eq!(format!("{holder:?}"), "Holder { lazy: AsyncLazy(Uninit) }");
+}
@@ -0,0 +1,26 @@
+use std::{future::Future, pin::Pin, task::{Context, Poll}};
+use laizy::{AsyncLazy, DefaultInit};
+
+#[derive(Default)]
+struct Answer;
+
+impl Future for Answer {
+    type Output = u8;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u8> {
+        Poll::Ready(42)
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    answer: AsyncLazy<u8, Answer>,
+    names: AsyncLazy<Vec<String>, DefaultInit<Vec<String>>>
+}
+
+fn main () {
+    let stats = Stats::default();
+    assert!(stats.answer.is_uninit() && stats.names.is_initialized());
+    assert_eq!(*futures::executor::block_on(stats.answer.get()), 42);
+    assert!(futures::executor::block_on(stats.names.get()).is_empty());
+}
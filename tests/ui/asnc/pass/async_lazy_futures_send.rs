@@ -0,0 +1,29 @@
+use std::future::Future;
+use laizy::AsyncLazy;
+
+fn assert_send<T: Send> (_: &T) {}
+fn assert_sync<T: Sync> (_: &T) {}
+
+// the futures returned by the cell can be spawned on multi-threaded executors whenever the cell can be shared
+fn send<T: Send + Sync, F: Future<Output = T> + Send> (mut lazy: AsyncLazy<T, F>) {
+    assert_send(&lazy.get());
+    assert_send(&lazy.get_mut());
+    assert_send(&lazy.into_inner());
+}
+
+// ... and shared between threads when the initializer can too
+fn sync<T: Send + Sync, F: Future<Output = T> + Send + Sync> (mut lazy: AsyncLazy<T, F>) {
+    assert_sync(&lazy.get());
+    assert_sync(&lazy.get_mut());
+    assert_sync(&lazy.into_inner());
+}
+
+fn main () {
+    send(laizy::async_lazy(async { 1u8 }));
+    sync(laizy::async_lazy(async { 1u8 }));
+
+    let lazy = laizy::async_lazy(async { std::sync::Arc::new(1u8) });
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let handle = runtime.spawn(async move { **lazy.get().await });
+    assert_eq!(runtime.block_on(handle).unwrap(), 1);
+}
@@ -0,0 +1,14 @@
+use std::sync::Arc;
+use futures::future::BoxFuture;
+use laizy::{AsyncLazy, async_lazy};
+
+fn assert_send<T: Send> (_: &T) {}
+fn assert_send_sync<T: Send + Sync> () {}
+
+fn main () {
+    assert_send_sync::<AsyncLazy<u8, futures::future::Ready<u8>>>();
+    assert_send_sync::<AsyncLazy<Arc<String>, BoxFuture<'static, Arc<String>>>>();
+
+    let lazy = async_lazy(async { Arc::new(1u8) });
+    assert_send(&lazy.get());
+}
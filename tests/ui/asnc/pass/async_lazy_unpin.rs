@@ -0,0 +1,21 @@
+use std::{future::Future, pin::Pin};
+use laizy::{AsyncLazy, async_lazy};
+
+fn assert_unpin<T: Unpin> (_: &T) {}
+
+struct Holder<F: Future<Output = u8>> {
+    lazy: AsyncLazy<u8, F>
+}
+
+fn main () {
+    // async blocks aren't `Unpin`, but the cell is
+    let lazy = async_lazy(async {
+        std::future::ready(()).await;
+        1u8
+    });
+    assert_unpin(&lazy);
+
+    let mut holder = Box::pin(Holder { lazy });
+    let holder: &mut Holder<_> = Pin::into_inner(holder.as_mut());
+    assert_eq!(*futures::executor::block_on(holder.lazy.get()), 1);
+}
@@ -0,0 +1,9 @@
+use laizy::Lazy;
+
+fn main () {
+    // `Lazy` implements `Drop`, so it can't outlive the values it references (see `CopyLazy`)
+    let lazy;
+    let name = String::from("laizy");
+    lazy = Lazy::<&str>::init(name.as_str());
+    assert_eq!(lazy.try_get(), Some(&"laizy"));
+}
@@ -0,0 +1,11 @@
+use laizy::Lazy;
+
+// `Lazy` is invariant over `F`, even if the initializer's type could be shortened
+fn shorten<'a> (lazy: Lazy<u8, &'static dyn Fn() -> u8>) -> Lazy<u8, &'a dyn Fn() -> u8> {
+    lazy
+}
+
+fn main () {
+    let lazy: Lazy<u8, &'static dyn Fn() -> u8> = Lazy::new(&|| 0);
+    drop(shorten(lazy));
+}
@@ -0,0 +1,11 @@
+use laizy::Lazy;
+
+// `Lazy` is invariant over `T`, so it can't be shortened like a `&'static str` could
+fn shorten<'a> (lazy: Lazy<&'static str, fn() -> &'static str>) -> Lazy<&'a str, fn() -> &'static str> {
+    lazy
+}
+
+fn main () {
+    let lazy = Lazy::new((|| "hello") as fn() -> &'static str);
+    drop(shorten(lazy));
+}
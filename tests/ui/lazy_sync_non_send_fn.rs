@@ -0,0 +1,13 @@
+use std::sync::{Mutex, MutexGuard};
+use laizy::Lazy;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn assert_sync<T: Sync> (_: &T) {}
+
+fn main () {
+    // the closure is `Sync` but not `Send`, since it owns a `MutexGuard`
+    let guard: MutexGuard<'static, ()> = LOCK.lock().unwrap();
+    let lazy = Lazy::new(move || { drop(guard); 0u8 });
+    assert_sync(&lazy);
+}
@@ -0,0 +1,12 @@
+use std::sync::{Mutex, MutexGuard};
+use laizy::Lazy;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+fn assert_sync<T: Sync> () {}
+
+fn main () {
+    // `MutexGuard` is `Sync` but not `Send`
+    assert_sync::<Lazy<MutexGuard<'static, ()>>>();
+    let _ = LOCK.lock();
+}
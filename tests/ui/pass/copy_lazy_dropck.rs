@@ -0,0 +1,18 @@
+use laizy::CopyLazy;
+
+// without drop glue, the cell can be dropped in `const` contexts...
+const fn build () -> u8 {
+    let lazy = CopyLazy::<u8>::init(1);
+    let _ = lazy;
+    0
+}
+
+fn main () {
+    const _: u8 = build();
+
+    // ... and it may outlive the values it references
+    let lazy;
+    let name = String::from("laizy");
+    lazy = CopyLazy::<&str>::init(name.as_str());
+    assert_eq!(lazy.try_get(), Some(&"laizy"));
+}
@@ -0,0 +1,13 @@
+use std::sync::Mutex;
+use laizy::Lazy;
+
+fn assert_send_sync<T: Send + Sync> () {}
+
+static SYNC: Lazy<Mutex<Vec<u8>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn main () {
+    assert_send_sync::<Lazy<u8>>();
+    assert_send_sync::<Lazy<Mutex<Vec<u8>>>>();
+    assert_send_sync::<Lazy<String, Box<dyn FnOnce() -> String + Send>>>();
+    SYNC.lock().unwrap().push(1);
+}
@@ -0,0 +1,14 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use std::rc::Rc;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn spawn_init_wasm () {
+    // `Rc` makes both the future and the value `!Send`, like a `JsFuture` would
+    let lazy: &'static _ = Box::leak(Box::new(laizy::async_lazy(async { Rc::new(42u64) })));
+    lazy.spawn_init_wasm();
+    assert_eq!(**lazy.get().await, 42);
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "std")]
+
+use std::{sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+use laizy::Lazy;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn hook (name: &'static str, elapsed: Duration) {
+    assert!(name.contains("Vec<u8>"));
+    assert!(elapsed >= Duration::from_millis(50));
+    CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn slow_init_hook () {
+    laizy::set_slow_init_hook(Duration::from_millis(50), hook);
+
+    let lazy = Lazy::new(|| {
+        std::thread::sleep(Duration::from_millis(200));
+        vec![1u8, 2, 3]
+    });
+
+    std::thread::scope(|s| {
+        s.spawn(|| lazy.len());
+        while lazy.is_uninit() { core::hint::spin_loop() }
+        std::thread::sleep(Duration::from_millis(10));
+
+        let waiters = (0..4).map(|_| s.spawn(|| lazy.len())).collect::<Vec<_>>();
+        for waiter in waiters {
+            assert_eq!(waiter.join().unwrap(), 3);
+        }
+    });
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+    // fast initializations aren't reported
+    let lazy = Lazy::new(|| vec![1u8]);
+    std::thread::scope(|s| {
+        for _ in 0..4 { s.spawn(|| lazy.len()); }
+    });
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+    laizy::clear_slow_init_hook();
+}